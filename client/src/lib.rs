@@ -3,13 +3,22 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{
-    CanvasRenderingContext2d, HtmlCanvasElement, HtmlInputElement,
+    BinaryType, CanvasRenderingContext2d, Element, HtmlCanvasElement, HtmlInputElement,
     HtmlSelectElement, MessageEvent, WebSocket, window,
 };
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
-use verplant::{City, GameMessage, GameState, PlayerAction, LineId, SubwayMap};
+use verplant::{
+    ChatEntry, City, ClientKind, ClientRequest, DailyChallenge, DailySubmission, Emote, GameMessage, GamePhase, GameStateView, Medal, PlayerAction, PlayerInfo, PlayerSheet, LineId,
+    ReactionTarget, PROTOCOL_VERSION, RequestId, ReshuffleReason, RuleSet, SoloGame, StatePatch, SubwayMap, WireFormat, validate_player_name, wire,
+};
+
+const SOLO_BEST_KEY_PREFIX: &str = "verplant-solo-best-";
+const ACCOUNT_TOKEN_KEY: &str = "verplant-account-token"; // Set by the page after logging in
+const MAX_SNAPSHOT_RETRIES: u32 = 3; // Bad snapshots in a row before we stop asking for another
+const SESSION_STORAGE_KEY: &str = "verplant-seat";
+const RECONNECT_DELAY_MS: i32 = 1000;
 
 // Set up panic hook for better error messages
 #[wasm_bindgen(start)]
@@ -24,15 +33,30 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 #[wasm_bindgen]
 pub struct GameClient {
     websocket: Option<WebSocket>,
-    game_state: Option<GameState>,
+    game_state: Option<GameStateView>,
+    state_version: u64,
+    bad_snapshots: u32,
+    wire_format: Cell<WireFormat>, // What we send in, as agreed in `Welcome`
+    next_request_id: Cell<RequestId>,
+    pending_actions: RefCell<Vec<ClientRequest>>, // Sent but not answered yet, resent after reconnecting
+    rejoin_request: Cell<Option<RequestId>>,
     player_id: Option<uuid::Uuid>,
     game_id: Option<uuid::Uuid>,
     subway_map: Option<SubwayMap>,
+    round_deadline: Option<u64>,
+    spectating: bool,
+    spectator_count: usize,
     #[allow(dead_code)]
     canvas: HtmlCanvasElement,
     context: CanvasRenderingContext2d,
     city_select: HtmlSelectElement,
     name_input: HtmlInputElement,
+    chat_messages: Element,
+    roster_list: Element,
+    roster: Vec<PlayerInfo>,
+    last_line_completed: Option<ReactionTarget>,
+    solo: Option<SoloGame>, // Played locally, without the server
+    daily: Option<DailyChallenge>, // Set while the solo game is a daily challenge whose score isn't sent yet
 }
 
 #[wasm_bindgen]
@@ -62,25 +86,59 @@ impl GameClient {
             .ok_or("No name input")?
             .dyn_into::<HtmlInputElement>()?;
         
+        let chat_messages = document
+            .get_element_by_id("chat-messages")
+            .ok_or("No chat panel")?;
+        
+        let roster_list = document
+            .get_element_by_id("roster")
+            .ok_or("No roster list")?;
+        
         canvas.set_width(800);
         canvas.set_height(600);
         
         Ok(GameClient {
             websocket: None,
             game_state: None,
+            state_version: 0,
+            bad_snapshots: 0,
+            wire_format: Cell::new(WireFormat::Json),
+            // Random start so ids don't repeat those of an earlier page load on the same seat
+            next_request_id: Cell::new((js_sys::Math::random() * u32::MAX as f64) as RequestId),
+            pending_actions: RefCell::new(Vec::new()),
+            rejoin_request: Cell::new(None),
             player_id: None,
             game_id: None,
             subway_map: None,
+            round_deadline: None,
+            spectating: false,
+            spectator_count: 0,
             canvas,
             context,
             city_select,
             name_input,
+            chat_messages,
+            roster_list,
+            roster: Vec::new(),
+            last_line_completed: None,
+            solo: None,
+            daily: None,
         })
     }
     
+    /// The game socket lives on `/ws` of the server that served this page.
+    fn server_url() -> Result<String, JsValue> {
+        let location = window().ok_or("No window")?.location();
+        let scheme = if location.protocol()? == "https:" { "wss:" } else { "ws:" };
+        Ok(format!("{}//{}/ws", scheme, location.host()?))
+    }
+    
     #[wasm_bindgen]
     pub fn connect_to_server(&mut self) -> Result<(), JsValue> {
-        let ws = WebSocket::new("ws://127.0.0.1:8080")?;
+        let ws = WebSocket::new(&Self::server_url()?)?;
+        ws.set_binary_type(BinaryType::Arraybuffer);
+        // Every connection starts out in JSON until its own `Welcome`
+        self.wire_format.set(WireFormat::Json);
         
         // Set up message handler
         let client_ref = Rc::new(RefCell::new(self as *mut GameClient));
@@ -88,11 +146,16 @@ impl GameClient {
         let onmessage_callback = {
             let client_ref = client_ref.clone();
             Closure::wrap(Box::new(move |e: MessageEvent| {
-                if let Ok(txt) = e.data().dyn_into::<js_sys::JsString>() {
-                    let message_str = String::from(txt);
+                // Text frames are JSON, binary frames MessagePack
+                let frame = match e.data().dyn_into::<js_sys::JsString>() {
+                    Ok(txt) => Some((String::from(txt).into_bytes(), WireFormat::Json)),
+                    Err(data) => data.dyn_into::<js_sys::ArrayBuffer>().ok()
+                        .map(|buffer| (js_sys::Uint8Array::new(&buffer).to_vec(), WireFormat::MessagePack)),
+                };
+                if let Some((bytes, wire_format)) = frame {
                     unsafe {
                         if let Ok(mut client_ref) = client_ref.try_borrow_mut() {
-                            (**client_ref).handle_server_message(&message_str);
+                            (**client_ref).handle_server_message(&bytes, wire_format);
                         }
                     }
                 }
@@ -102,12 +165,48 @@ impl GameClient {
         onmessage_callback.forget();
         
         // Set up connection handlers
-        let onopen_callback = Closure::wrap(Box::new(move |_| {
-            web_sys::console::log_1(&"Connected to server".into());
-        }) as Box<dyn FnMut(JsValue)>);
+        let onopen_callback = {
+            let client_ref = client_ref.clone();
+            Closure::wrap(Box::new(move |_| {
+                web_sys::console::log_1(&"Connected to server".into());
+                unsafe {
+                    if let Ok(client_ref) = client_ref.try_borrow() {
+                        let _ = (**client_ref).send_message(&GameMessage::Hello {
+                            protocol_version: PROTOCOL_VERSION,
+                            client_kind: ClientKind::Web,
+                            wire_formats: vec![WireFormat::MessagePack, WireFormat::Json],
+                        });
+                        // Log in first, so a game joined on this connection counts for the account
+                        let _ = (**client_ref).authenticate();
+                        // Take our seat back if this tab was already playing
+                        let _ = (**client_ref).rejoin();
+                    }
+                }
+            }) as Box<dyn FnMut(JsValue)>)
+        };
         ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
         onopen_callback.forget();
         
+        let onclose_callback = {
+            let client_ref = client_ref.clone();
+            Closure::wrap(Box::new(move |_| {
+                web_sys::console::warn_1(&"Disconnected from server".into());
+                let client_ref = client_ref.clone();
+                let reconnect = Closure::once_into_js(move || unsafe {
+                    if let Ok(client_ref) = client_ref.try_borrow()
+                        && (**client_ref).has_seat()
+                    {
+                        let _ = (**client_ref).connect_to_server();
+                    }
+                });
+                if let Some(window) = window() {
+                    let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(reconnect.unchecked_ref(), RECONNECT_DELAY_MS);
+                }
+            }) as Box<dyn FnMut(JsValue)>)
+        };
+        ws.set_onclose(Some(onclose_callback.as_ref().unchecked_ref()));
+        onclose_callback.forget();
+        
         let onerror_callback = Closure::wrap(Box::new(move |_e| {
             web_sys::console::error_1(&"WebSocket error".into());
         }) as Box<dyn FnMut(JsValue)>);
@@ -118,46 +217,300 @@ impl GameClient {
         Ok(())
     }
     
+    /// Sends the login token kept in local storage, if any. Games joined before
+    /// don't count for the account.
     #[wasm_bindgen]
-    pub fn join_game(&self) -> Result<(), JsValue> {
-        let player_name = self.name_input.value();
-        let city_value = self.city_select.value();
+    pub fn authenticate(&self) -> Result<(), JsValue> {
+        let storage = window().ok_or("No window")?.local_storage()?.ok_or("No local storage")?;
         
-        let city = match city_value.as_str() {
+        if let Some(token) = storage.get_item(ACCOUNT_TOKEN_KEY)? {
+            let token = uuid::Uuid::parse_str(&token)
+                .map_err(|e| JsValue::from_str(&e.to_string()))?;
+            self.send_message(&GameMessage::Authenticate { token })?;
+        }
+        
+        Ok(())
+    }
+    
+    fn remembered_seat(&self) -> Result<Option<GameMessage>, JsValue> {
+        let storage = window().ok_or("No window")?.session_storage()?.ok_or("No session storage")?;
+        
+        match storage.get_item(SESSION_STORAGE_KEY)? {
+            Some(seat) => serde_json::from_str(&seat)
+                .map(Some)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            None => Ok(None),
+        }
+    }
+    
+    /// Whether there is a seat to take back after losing the connection.
+    fn has_seat(&self) -> bool {
+        self.remembered_seat().is_ok_and(|seat| seat.is_some())
+    }
+    
+    /// Sends a `Rejoin` for the seat remembered in session storage, if any.
+    fn rejoin(&self) -> Result<(), JsValue> {
+        if let Some(seat) = self.remembered_seat()? {
+            self.rejoin_request.set(Some(self.next_request_id.get()));
+            self.send_message(&seat)?;
+        }
+        
+        Ok(())
+    }
+    
+    fn remember_seat(&self, game_id: uuid::Uuid, player_id: uuid::Uuid, token: uuid::Uuid) -> Result<(), JsValue> {
+        let storage = window().ok_or("No window")?.session_storage()?.ok_or("No session storage")?;
+        let seat = serde_json::to_string(&GameMessage::Rejoin { game_id, player_id, token })
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        storage.set_item(SESSION_STORAGE_KEY, &seat)
+    }
+    
+    /// Gives up on the remembered seat and the moves still waiting for it.
+    fn forget_seat(&self) -> Result<(), JsValue> {
+        self.pending_actions.borrow_mut().clear();
+        let storage = window().ok_or("No window")?.session_storage()?.ok_or("No session storage")?;
+        storage.remove_item(SESSION_STORAGE_KEY)
+    }
+    
+    fn selected_city(&self) -> City {
+        match self.city_select.value().as_str() {
             "amsterdam" => City::Amsterdam,
             "berlin" => City::Berlin,
             "paris" => City::Paris,
             "madrid" => City::Madrid,
             _ => City::Amsterdam,
-        };
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn join_game(&mut self) -> Result<(), JsValue> {
+        let player_name = validate_player_name(&self.name_input.value())
+            .map_err(|e| JsValue::from_str(&e))?;
+        let city = self.selected_city();
         
-        let message = GameMessage::JoinGame { player_name, city };
+        let message = GameMessage::JoinGame { player_name, city, game_id: None };
+        self.leave_solo();
         self.send_message(&message)
     }
     
+    /// Takes a seat at a tournament table. Only works when logged in to the
+    /// account of one of the table's players.
     #[wasm_bindgen]
-    pub fn start_game(&self) -> Result<(), JsValue> {
-        let message = GameMessage::StartGame;
+    pub fn join_table(&mut self, game_id: &str) -> Result<(), JsValue> {
+        let player_name = validate_player_name(&self.name_input.value())
+            .map_err(|e| JsValue::from_str(&e))?;
+        let game_id = uuid::Uuid::parse_str(game_id.trim())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        
+        let message = GameMessage::JoinGame { player_name, city: self.selected_city(), game_id: Some(game_id) };
+        self.leave_solo();
         self.send_message(&message)
     }
     
+    /// Starts a single player game on the selected city, dealt from `seed` or
+    /// a random one. Runs without a server connection.
     #[wasm_bindgen]
-    pub fn choose_line(&self, line_id: &str, window_index: usize) -> Result<(), JsValue> {
-        let action = PlayerAction::ChooseLine {
-            line_id: LineId(line_id.to_string()),
-            car_window_index: window_index,
+    pub fn start_solo(&mut self, seed: Option<u32>) -> Result<(), JsValue> {
+        let seed = seed.unwrap_or_else(|| (js_sys::Math::random() * u32::MAX as f64) as u32);
+        let subway_map = SubwayMap::builtin(&self.selected_city());
+        let solo = SoloGame::new(subway_map, RuleSet::default(), seed as u64)
+            .map_err(|e| JsValue::from_str(&e))?;
+        
+        self.daily = None;
+        self.begin_solo(solo, &format!("Solo game, seed {}", seed))
+    }
+    
+    /// Starts the daily challenge, as served by `/api/daily`, on the server's
+    /// map of its city.
+    #[wasm_bindgen]
+    pub fn start_daily(&mut self, challenge_json: &str, map_json: &str) -> Result<(), JsValue> {
+        let challenge: DailyChallenge = serde_json::from_str(challenge_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let subway_map: SubwayMap = serde_json::from_str(map_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let solo = SoloGame::new(subway_map, RuleSet::default(), challenge.seed)
+            .map_err(|e| JsValue::from_str(&e))?;
+        
+        let intro = format!("Daily challenge of {}", challenge.date);
+        self.daily = Some(challenge);
+        self.begin_solo(solo, &intro)
+    }
+    
+    /// The score of a finished daily challenge as JSON for
+    /// `/api/daily/{date}/scores`. Only returned once per game.
+    #[wasm_bindgen]
+    pub fn take_daily_submission(&mut self) -> Result<Option<String>, JsValue> {
+        let Some(solo) = self.solo.as_ref().filter(|solo| solo.is_finished()) else {
+            return Ok(None);
         };
-        let message = GameMessage::PlayerAction(action);
+        if self.daily.take().is_none() {
+            return Ok(None);
+        }
+        
+        let submission = DailySubmission {
+            player_name: validate_player_name(&self.name_input.value()).map_err(|e| JsValue::from_str(&e))?,
+            score: solo.score(),
+            replay: solo.replay.clone(),
+        };
+        serde_json::to_string(&submission)
+            .map(Some)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+    
+    fn begin_solo(&mut self, solo: SoloGame, intro: &str) -> Result<(), JsValue> {
+        self.player_id = Some(solo.player_id);
+        self.game_id = Some(solo.state.id);
+        self.subway_map = Some(solo.subway_map.clone());
+        self.game_state = Some(solo.view());
+        self.state_version = 0;
+        self.round_deadline = None;
+        self.spectating = false;
+        self.chat_messages.set_inner_html("");
+        self.show_roster(solo.state.roster.clone())?;
+        
+        let targets = solo.targets;
+        self.append_chat_line(intro, "chat-reaction")?;
+        self.append_chat_line(&format!("Bronze {}, silver {}, gold {} points", targets.bronze, targets.silver, targets.gold), "chat-reaction")?;
+        if let Some(best) = self.solo_best(&solo.subway_map.city)? {
+            self.append_chat_line(&format!("Your best on this map: {} points", best), "chat-reaction")?;
+        }
+        
+        self.solo = Some(solo);
+        self.draw_game()
+    }
+    
+    /// Ends a solo game, so the server's messages are handled again.
+    fn leave_solo(&mut self) {
+        self.solo = None;
+        self.daily = None;
+    }
+    
+    /// Skips the current card of a solo game.
+    #[wasm_bindgen]
+    pub fn pass(&mut self) -> Result<(), JsValue> {
+        let solo = self.solo.as_mut().ok_or("Only solo games can skip a card")?;
+        let messages = solo.pass().map_err(|e| JsValue::from_str(&e))?;
+        self.after_solo_move(messages)
+    }
+    
+    /// Sends an action to the server, or plays it right away in a solo game.
+    fn play(&mut self, action: PlayerAction) -> Result<(), JsValue> {
+        let Some(solo) = self.solo.as_mut() else {
+            return self.send_message(&GameMessage::PlayerAction(action));
+        };
+        
+        let messages = solo.play(action).map_err(|e| JsValue::from_str(&e))?;
+        self.after_solo_move(messages)
+    }
+    
+    fn after_solo_move(&mut self, messages: Vec<GameMessage>) -> Result<(), JsValue> {
+        let Some(solo) = &self.solo else {
+            return Ok(());
+        };
+        self.game_state = Some(solo.view());
+        let finished = solo.is_finished().then(|| (solo.subway_map.city.clone(), solo.score(), solo.medal()));
+        
+        for message in messages {
+            self.handle_message(message);
+        }
+        if let Some((city, score, medal)) = finished {
+            self.finish_solo(&city, score, medal)?;
+        }
+        self.draw_game()
+    }
+    
+    fn finish_solo(&self, city: &City, score: i32, medal: Option<Medal>) -> Result<(), JsValue> {
+        let medal_text = match medal {
+            Some(Medal::Gold) => "Gold medal! 🥇",
+            Some(Medal::Silver) => "Silver medal! 🥈",
+            Some(Medal::Bronze) => "Bronze medal! 🥉",
+            None => "No medal this time",
+        };
+        self.append_chat_line(medal_text, "chat-reaction")?;
+        
+        let best = self.solo_best(city)?;
+        if best.is_none_or(|best| score > best) {
+            let storage = window().ok_or("No window")?.local_storage()?.ok_or("No local storage")?;
+            storage.set_item(&Self::solo_best_key(city), &score.to_string())?;
+            self.append_chat_line(&format!("New personal best: {} points", score), "chat-reaction")?;
+        }
+        Ok(())
+    }
+    
+    fn solo_best_key(city: &City) -> String {
+        format!("{}{:?}", SOLO_BEST_KEY_PREFIX, city).to_lowercase()
+    }
+    
+    /// The best solo score on a map, kept in local storage.
+    fn solo_best(&self, city: &City) -> Result<Option<i32>, JsValue> {
+        let storage = window().ok_or("No window")?.local_storage()?.ok_or("No local storage")?;
+        Ok(storage.get_item(&Self::solo_best_key(city))?.and_then(|best| best.parse().ok()))
+    }
+    
+    #[wasm_bindgen]
+    pub fn spectate(&mut self, game_id: &str) -> Result<(), JsValue> {
+        let game_id = uuid::Uuid::parse_str(game_id.trim())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.leave_solo();
+        self.send_message(&GameMessage::Spectate { game_id })
+    }
+    
+    #[wasm_bindgen]
+    pub fn send_chat(&self, text: &str) -> Result<(), JsValue> {
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        self.send_message(&GameMessage::SendChat { text: text.to_string() })
+    }
+    
+    /// Sends a quick reaction, attached to the most recent line completion if any.
+    #[wasm_bindgen]
+    pub fn react(&self, emote: &str) -> Result<(), JsValue> {
+        let emote = match emote {
+            "clap" => Emote::Clap,
+            "wow" => Emote::Wow,
+            "laugh" => Emote::Laugh,
+            "sad" => Emote::Sad,
+            _ => Emote::Train,
+        };
+        self.send_message(&GameMessage::React { emote, target: self.last_line_completed.clone() })
+    }
+    
+    #[wasm_bindgen]
+    pub fn start_game(&self) -> Result<(), JsValue> {
+        let message = GameMessage::StartGame;
         self.send_message(&message)
     }
     
     #[wasm_bindgen]
-    pub fn mark_transfer_station(&self, station_id: &str) -> Result<(), JsValue> {
+    pub fn choose_line(&mut self, line_id: &str) -> Result<(), JsValue> {
+        let line_id = LineId(line_id.to_string());
+        
+        // Cards always go into the first empty window of the line
+        let car_window_index = self.game_state.as_ref()
+            .zip(self.player_id)
+            .and_then(|(state, player_id)| state.players.get(&player_id))
+            .and_then(|sheet| sheet.next_empty_window(&line_id))
+            .ok_or("No empty window on this line")?;
+        
+        let action = PlayerAction::ChooseLine { line_id, car_window_index };
+        self.play(action)
+    }
+    
+    #[wasm_bindgen]
+    pub fn mark_transfer_station(&mut self, station_id: &str) -> Result<(), JsValue> {
         let action = PlayerAction::MarkTransferStation {
             station_id: station_id.to_string(),
         };
-        let message = GameMessage::PlayerAction(action);
-        self.send_message(&message)
+        self.play(action)
+    }
+    
+    #[wasm_bindgen]
+    pub fn close_station(&mut self, station_id: &str) -> Result<(), JsValue> {
+        let action = PlayerAction::CloseStation {
+            station_id: station_id.to_string(),
+        };
+        self.play(action)
     }
     
     #[wasm_bindgen]
@@ -166,8 +519,13 @@ impl GameClient {
         self.context.clear_rect(0.0, 0.0, 800.0, 600.0);
         
         if let (Some(game_state), Some(subway_map)) = (&self.game_state, &self.subway_map) {
-            self.draw_subway_map(subway_map)?;
-            self.draw_game_state(game_state)?;
+            if self.spectating {
+                self.draw_spectator_view(game_state, subway_map)?;
+                self.draw_round_info(game_state)?;
+            } else {
+                self.draw_subway_map(subway_map)?;
+                self.draw_game_state(game_state)?;
+            }
         }
         
         Ok(())
@@ -219,69 +577,99 @@ impl GameClient {
         Ok(())
     }
     
-    fn draw_game_state(&self, game_state: &GameState) -> Result<(), JsValue> {
-        if let Some(player_id) = self.player_id {
-            if let Some(player) = game_state.players.get(&player_id) {
-                // Draw train car windows
-                let mut y_offset = 20.0;
-                for (line_id, windows) in &player.train_cars {
-                    self.context.set_fill_style(&"#000000".into());
-                    self.context.set_font("14px Arial");
-                    self.context.fill_text(&format!("Line {}: ", line_id.0), 650.0, y_offset)?;
+    fn draw_game_state(&self, game_state: &GameStateView) -> Result<(), JsValue> {
+        if let Some(player) = self.player_id.and_then(|id| game_state.players.get(&id)) {
+            // Draw train car windows
+            let mut y_offset = 20.0;
+            for (line_id, windows) in &player.train_cars {
+                self.context.set_fill_style(&"#000000".into());
+                self.context.set_font("14px Arial");
+                self.context.fill_text(&format!("Line {}: ", line_id.0), 650.0, y_offset)?;
+                
+                for (i, window) in windows.iter().enumerate() {
+                    let x = 650.0 + (i as f64 * 30.0);
+                    let y = y_offset + 10.0;
                     
-                    for (i, window) in windows.iter().enumerate() {
-                        let x = 650.0 + (i as f64 * 30.0);
-                        let y = y_offset + 10.0;
-                        
-                        // Draw window box
-                        self.context.set_stroke_style(&"#000000".into());
-                        self.context.stroke_rect(x, y, 25.0, 25.0);
-                        
-                        // Draw window content
-                        if let Some(value) = window {
-                            self.context.set_fill_style(&"#000000".into());
-                            self.context.fill_text(value, x + 8.0, y + 18.0)?;
-                        }
-                    }
+                    // Draw window box
+                    self.context.set_stroke_style(&"#000000".into());
+                    self.context.stroke_rect(x, y, 25.0, 25.0);
                     
-                    y_offset += 50.0;
+                    // Draw window content
+                    if let Some(value) = window {
+                        self.context.set_fill_style(&"#000000".into());
+                        self.context.fill_text(value, x + 8.0, y + 18.0)?;
+                    }
                 }
                 
-                // Draw marked stations on the map
-                if let Some(subway_map) = &self.subway_map {
-                    for (station_id, mark) in &player.marked_stations {
-                        if let Some(station) = subway_map.stations.get(station_id) {
-                            match mark {
-                                verplant::StationMark::Cross => {
-                                    // Draw X mark
-                                    self.context.set_stroke_style(&"#FF0000".into());
-                                    self.context.set_line_width(3.0);
-                                    self.context.begin_path();
-                                    self.context.move_to(station.x as f64 - 6.0, station.y as f64 - 6.0);
-                                    self.context.line_to(station.x as f64 + 6.0, station.y as f64 + 6.0);
-                                    self.context.move_to(station.x as f64 + 6.0, station.y as f64 - 6.0);
-                                    self.context.line_to(station.x as f64 - 6.0, station.y as f64 + 6.0);
-                                    self.context.stroke();
-                                },
-                                verplant::StationMark::TransferNumber(num) => {
-                                    // Draw transfer number in square
-                                    self.context.set_stroke_style(&"#0000FF".into());
-                                    self.context.set_line_width(2.0);
-                                    self.context.stroke_rect(station.x as f64 - 8.0, station.y as f64 - 8.0, 16.0, 16.0);
-                                    
-                                    self.context.set_fill_style(&"#0000FF".into());
-                                    self.context.set_font("12px Arial");
-                                    self.context.fill_text(&num.to_string(), station.x as f64 - 4.0, station.y as f64 + 4.0)?;
-                                }
-                            }
-                        }
-                    }
+                y_offset += 50.0;
+            }
+            
+            // Draw marked stations on the map
+            if let Some(subway_map) = &self.subway_map {
+                self.draw_station_marks(player, subway_map)?;
+            }
+        }
+        
+        self.draw_round_info(game_state)?;
+        
+        Ok(())
+    }
+    
+    fn draw_station_marks(&self, player: &PlayerSheet, subway_map: &SubwayMap) -> Result<(), JsValue> {
+        for (station_id, mark) in &player.marked_stations {
+            if let Some(station) = subway_map.stations.get(station_id) {
+                match mark {
+                    verplant::StationMark::Cross => {
+                        // Draw X mark
+                        self.context.set_stroke_style(&"#FF0000".into());
+                        self.context.set_line_width(3.0);
+                        self.context.begin_path();
+                        self.context.move_to(station.x as f64 - 6.0, station.y as f64 - 6.0);
+                        self.context.line_to(station.x as f64 + 6.0, station.y as f64 + 6.0);
+                        self.context.move_to(station.x as f64 + 6.0, station.y as f64 - 6.0);
+                        self.context.line_to(station.x as f64 - 6.0, station.y as f64 + 6.0);
+                        self.context.stroke();
+                    },
+                    verplant::StationMark::TransferNumber(num) => {
+                        // Draw transfer number in square
+                        self.context.set_stroke_style(&"#0000FF".into());
+                        self.context.set_line_width(2.0);
+                        self.context.stroke_rect(station.x as f64 - 8.0, station.y as f64 - 8.0, 16.0, 16.0);
+                        
+                        self.context.set_fill_style(&"#0000FF".into());
+                        self.context.set_font("12px Arial");
+                        self.context.fill_text(&num.to_string(), station.x as f64 - 4.0, station.y as f64 + 4.0)?;
+                    },
                 }
             }
         }
         
-        // Draw current card
-        if let Some(card) = &game_state.current_card {
+        for station_id in &player.closed_stations {
+            if let Some(station) = subway_map.stations.get(station_id) {
+                // Draw a grey block over the station
+                self.context.set_fill_style(&"#888888".into());
+                self.context.fill_rect(station.x as f64 - 7.0, station.y as f64 - 7.0, 14.0, 14.0);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn draw_round_info(&self, game_state: &GameStateView) -> Result<(), JsValue> {
+        let phase_text = match game_state.phase {
+            GamePhase::Lobby => Some("Waiting for the game to start"),
+            GamePhase::RoundOver => Some("Round over"),
+            GamePhase::ResolvingCardSix => Some("Round over, the 6 was shuffled back in"),
+            GamePhase::Finished => Some("Game over"),
+            GamePhase::WaitingForActions => None,
+        };
+        
+        // The phase takes the card's line while nobody can play the card
+        if let Some(phase_text) = phase_text {
+            self.context.set_fill_style(&"#000000".into());
+            self.context.set_font("16px Arial");
+            self.context.fill_text(phase_text, 20.0, 550.0)?;
+        } else if let Some(card) = &game_state.current_card {
             self.context.set_fill_style(&"#000000".into());
             self.context.set_font("16px Arial");
             let card_text = match card {
@@ -293,110 +681,401 @@ impl GameClient {
                 },
                 verplant::Card::Transfer => "Current Card: Transfer (+)".to_string(),
                 verplant::Card::FreeRide => "Current Card: Free Ride".to_string(),
+                verplant::Card::Delay => "Current Card: Delay (-)".to_string(),
+                verplant::Card::Double(n) => format!("Current Card: Double {} (play twice)", n),
+                verplant::Card::Closure => "Current Card: Closure".to_string(),
             };
             self.context.fill_text(&card_text, 20.0, 550.0)?;
+            
+            if let Some(deadline) = self.round_deadline {
+                let remaining = (deadline as f64 - js_sys::Date::now()).max(0.0) / 1000.0;
+                self.context.fill_text(&format!("Time left: {}s", remaining.ceil()), 20.0, 575.0)?;
+            }
+        }
+        
+        self.context.set_fill_style(&"#000000".into());
+        self.context.set_font("14px Arial");
+        let pile_text = format!("Deck: {}  Discard: {}", game_state.deck_count, game_state.discard_pile.len());
+        self.context.fill_text(&pile_text, 650.0, 570.0)?;
+        
+        if let Some(solo) = &self.solo {
+            let targets = solo.targets;
+            self.context.set_fill_style(&"#000000".into());
+            self.context.set_font("14px Arial");
+            let solo_text = format!("Score: {}  (bronze {}, silver {}, gold {})", solo.score(), targets.bronze, targets.silver, targets.gold);
+            self.context.fill_text(&solo_text, 20.0, 20.0)?;
+        }
+        
+        if self.spectator_count > 0 {
+            self.context.set_fill_style(&"#000000".into());
+            self.context.set_font("14px Arial");
+            self.context.fill_text(&format!("Spectators: {}", self.spectator_count), 650.0, 590.0)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Spectators see every sheet at once: one scaled-down copy of the map per
+    /// player, laid out in a grid, with that player's marks and train cars.
+    fn draw_spectator_view(&self, game_state: &GameStateView, subway_map: &SubwayMap) -> Result<(), JsValue> {
+        let mut player_ids: Vec<&uuid::Uuid> = game_state.players.keys().collect();
+        player_ids.sort();
+        
+        if player_ids.is_empty() {
+            self.context.set_fill_style(&"#000000".into());
+            self.context.set_font("16px Arial");
+            self.context.fill_text("Waiting for players...", 20.0, 30.0)?;
+            return Ok(());
+        }
+        
+        let columns = (player_ids.len() as f64).sqrt().ceil() as usize;
+        let rows = player_ids.len().div_ceil(columns);
+        let cell_width = 800.0 / columns as f64;
+        let cell_height = 530.0 / rows as f64;
+        
+        // Fit the whole map (plus room for station names) into the upper part of a cell
+        let (min_x, min_y, max_x, max_y) = subway_map.stations.values().fold(
+            (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+            |(min_x, min_y, max_x, max_y), station| (
+                min_x.min(station.x as f64), min_y.min(station.y as f64),
+                max_x.max(station.x as f64), max_y.max(station.y as f64),
+            ),
+        );
+        let map_width = (max_x - min_x).max(1.0) + 80.0;
+        let map_height = (max_y - min_y).max(1.0) + 40.0;
+        let car_rows = subway_map.lines.len() as f64 * 14.0;
+        let scale = ((cell_width - 10.0) / map_width).min((cell_height - 30.0 - car_rows) / map_height).max(0.1);
+        
+        for (i, player_id) in player_ids.iter().enumerate() {
+            let player = &game_state.players[*player_id];
+            let x = (i % columns) as f64 * cell_width;
+            let y = (i / columns) as f64 * cell_height;
+            
+            self.context.save();
+            self.context.translate(x, y)?;
+            
+            self.context.set_stroke_style(&"#999999".into());
+            self.context.set_line_width(1.0);
+            self.context.stroke_rect(2.0, 2.0, cell_width - 4.0, cell_height - 4.0);
+            
+            self.context.set_fill_style(&"#000000".into());
+            self.context.set_font("bold 12px Arial");
+            if let Some(info) = self.roster.iter().find(|info| info.id == **player_id) {
+                self.context.set_fill_style(&info.color.as_str().into());
+            }
+            self.context.fill_text(&self.player_label(player_id), 8.0, 16.0)?;
+            self.context.set_fill_style(&"#000000".into());
+            
+            // Train cars as text below the map
+            let mut line_ids: Vec<&LineId> = player.train_cars.keys().collect();
+            line_ids.sort_by(|a, b| a.0.cmp(&b.0));
+            self.context.set_font("11px Arial");
+            for (row, line_id) in line_ids.iter().enumerate() {
+                let windows: Vec<&str> = player.train_cars[*line_id].iter()
+                    .map(|window| window.as_deref().unwrap_or("_"))
+                    .collect();
+                let text_y = cell_height - 8.0 - (line_ids.len() - 1 - row) as f64 * 14.0;
+                self.context.fill_text(&format!("{}: {}", line_id.0, windows.join(" ")), 8.0, text_y)?;
+            }
+            
+            self.context.translate(5.0, 22.0)?;
+            self.context.scale(scale, scale)?;
+            self.context.translate(20.0 - min_x, 20.0 - min_y)?;
+            self.draw_subway_map(subway_map)?;
+            self.draw_station_marks(player, subway_map)?;
+            
+            self.context.restore();
+        }
+        
+        Ok(())
+    }
+    
+    fn player_label(&self, player_id: &uuid::Uuid) -> String {
+        if Some(*player_id) == self.player_id {
+            return "You".to_string();
+        }
+        
+        match self.roster.iter().find(|info| info.id == *player_id) {
+            Some(info) => info.name.clone(),
+            None => format!("Player {}", &player_id.to_string()[..8]),
+        }
+    }
+    
+    fn show_roster(&mut self, roster: Vec<PlayerInfo>) -> Result<(), JsValue> {
+        self.roster = roster;
+        
+        let document = window().ok_or("No window")?.document().ok_or("No document")?;
+        self.roster_list.set_inner_html("");
+        
+        for info in &self.roster {
+            let entry = document.create_element("li")?;
+            entry.set_attribute("style", &format!("color: {}", info.color))?;
+            
+            let mut text = info.name.clone();
+            if Some(info.id) == self.player_id {
+                text.push_str(" (you)");
+            }
+            if info.is_conductor {
+                text.push_str(" 🎩");
+            }
+            if info.is_bot {
+                text.push_str(" 🤖");
+            }
+            if !info.connected {
+                text.push_str(" (disconnected)");
+            }
+            entry.set_text_content(Some(&text));
+            self.roster_list.append_child(&entry)?;
         }
         
         Ok(())
     }
     
+    fn append_chat_line(&self, text: &str, class_name: &str) -> Result<(), JsValue> {
+        let document = window().ok_or("No window")?.document().ok_or("No document")?;
+        let line = document.create_element("div")?;
+        line.set_class_name(class_name);
+        line.set_text_content(Some(text));
+        self.chat_messages.append_child(&line)?;
+        self.chat_messages.set_scroll_top(self.chat_messages.scroll_height());
+        Ok(())
+    }
+    
+    fn show_chat_entry(&self, entry: &ChatEntry) -> Result<(), JsValue> {
+        let sender = if entry.from_spectator {
+            format!("{} (spectator)", self.player_label(&entry.sender))
+        } else {
+            self.player_label(&entry.sender)
+        };
+        self.append_chat_line(&format!("{}: {}", sender, entry.text), "chat-line")
+    }
+    
+    fn show_reaction(&self, sender: &uuid::Uuid, emote: Emote, target: Option<ReactionTarget>) -> Result<(), JsValue> {
+        let symbol = match emote {
+            Emote::Clap => "👏",
+            Emote::Wow => "😮",
+            Emote::Laugh => "😂",
+            Emote::Sad => "😢",
+            Emote::Train => "🚇",
+        };
+        let text = match target {
+            Some(ReactionTarget::LineCompleted { player_id, line_id }) => {
+                format!("{} {} ({} completed line {})", self.player_label(sender), symbol, self.player_label(&player_id), line_id.0)
+            },
+            Some(ReactionTarget::Player(player_id)) => {
+                format!("{} {} to {}", self.player_label(sender), symbol, self.player_label(&player_id))
+            },
+            None => format!("{} {}", self.player_label(sender), symbol),
+        };
+        self.append_chat_line(&text, "chat-reaction")
+    }
+    
     fn send_message(&self, message: &GameMessage) -> Result<(), JsValue> {
+        let request_id = self.next_request_id.get();
+        self.next_request_id.set(request_id.wrapping_add(1));
+        
+        let request = ClientRequest { request_id: Some(request_id), message: message.clone() };
+        // Moves keep their id until answered, so the server can tell a resent one
+        if matches!(message, GameMessage::PlayerAction(_)) {
+            self.pending_actions.borrow_mut().push(request.clone());
+        }
+        self.send_request(&request)
+    }
+    
+    fn send_request(&self, request: &ClientRequest) -> Result<(), JsValue> {
         if let Some(ws) = &self.websocket {
-            let message_str = serde_json::to_string(message)
-                .map_err(|e| JsValue::from_str(&e.to_string()))?;
-            ws.send_with_str(&message_str)?;
+            let wire_format = self.wire_format.get();
+            let bytes = wire::encode(request, wire_format)
+                .map_err(|e| JsValue::from_str(&e))?;
+            match wire_format {
+                WireFormat::Json => ws.send_with_str(&String::from_utf8_lossy(&bytes))?,
+                WireFormat::MessagePack => ws.send_with_u8_array(&bytes)?,
+            }
         }
         Ok(())
     }
     
-    fn handle_server_message(&mut self, message_str: &str) {
-        if let Ok(message) = serde_json::from_str::<GameMessage>(message_str) {
-            match message {
-                GameMessage::GameJoined { player_id, game_id } => {
-                    self.player_id = Some(player_id);
-                    self.game_id = Some(game_id);
-                    web_sys::console::log_1(&format!("Joined game {} as player {}", game_id, player_id).into());
-                },
-                GameMessage::GameState(state) => {
-                    self.game_state = Some(state);
-                    self.subway_map = Some(self.create_subway_map_for_city());
-                    let _ = self.draw_game();
-                },
-                GameMessage::CardRevealed(card) => {
-                    if let Some(ref mut state) = self.game_state {
-                        state.current_card = Some(card);
-                        let _ = self.draw_game();
-                    }
-                },
-                GameMessage::LineCompleted { player_id, line_id } => {
-                    web_sys::console::log_1(&format!("Player {} completed line {}", player_id, line_id.0).into());
-                },
-                GameMessage::GameEnded { scores } => {
-                    let score_text = format!("Game ended! Scores: {:?}", scores);
-                    web_sys::console::log_1(&score_text.into());
-                },
-                GameMessage::Error(error) => {
-                    web_sys::console::error_1(&error.into());
-                },
-                _ => {}
+    /// Sends the moves that were never answered again, with their original ids,
+    /// so the server plays each of them at most once.
+    fn resend_pending_actions(&self) -> Result<(), JsValue> {
+        let pending = self.pending_actions.borrow().clone();
+        for request in &pending {
+            self.send_request(request)?;
+        }
+        Ok(())
+    }
+    
+    /// Forgets a request once the server answered it.
+    fn settle_request(&self, request_id: Option<RequestId>) {
+        if let Some(request_id) = request_id {
+            self.pending_actions.borrow_mut().retain(|request| request.request_id != Some(request_id));
+        }
+    }
+    
+    /// Applies a delta to the local state. Fails if a delta was missed or the
+    /// result does not match the server's checksum, in which case the state is
+    /// dropped until a new snapshot arrives.
+    fn apply_delta(&mut self, version: u64, checksum: u64, patches: Vec<StatePatch>) -> Result<(), String> {
+        let Some(state) = self.game_state.as_mut() else {
+            return Ok(()); // The snapshot sent on joining is still on its way
+        };
+        if version <= self.state_version {
+            return Ok(());
+        }
+        if version != self.state_version + 1 {
+            self.game_state = None;
+            return Err(format!("Expected state version {}, got {}", self.state_version + 1, version));
+        }
+        
+        for patch in patches {
+            if let Err(error) = state.apply(patch) {
+                self.game_state = None;
+                return Err(error);
             }
         }
+        if state.checksum() != checksum {
+            self.game_state = None;
+            return Err(format!("State checksum mismatch at version {}", version));
+        }
+        self.state_version = version;
+        
+        Ok(())
+    }
+    
+    fn handle_server_message(&mut self, bytes: &[u8], wire_format: WireFormat) {
+        // A solo game owns the board until the player joins or spectates a room again
+        if self.solo.is_some() {
+            return;
+        }
+        
+        if let Ok(message) = wire::decode::<GameMessage>(bytes, wire_format) {
+            self.handle_message(message);
+        }
     }
     
-    fn create_subway_map_for_city(&self) -> SubwayMap {
-        // Create the same subway map as the server
-        use std::collections::HashMap;
-        use verplant::{Station, SubwayLine};
-        
-        let mut stations = HashMap::new();
-        let mut lines = HashMap::new();
-        
-        // Simple Amsterdam map for testing
-        stations.insert("central".to_string(), Station {
-            id: "central".to_string(),
-            x: 100.0,
-            y: 100.0,
-            lines: vec![LineId("red".to_string()), LineId("blue".to_string())],
-            is_transfer_hub: true,
-        });
-        
-        stations.insert("dam".to_string(), Station {
-            id: "dam".to_string(),
-            x: 150.0,
-            y: 100.0, 
-            lines: vec![LineId("red".to_string())],
-            is_transfer_hub: false,
-        });
-        
-        stations.insert("museum".to_string(), Station {
-            id: "museum".to_string(),
-            x: 200.0,
-            y: 100.0,
-            lines: vec![LineId("red".to_string())],
-            is_transfer_hub: false,
-        });
-        
-        lines.insert(LineId("red".to_string()), SubwayLine {
-            id: LineId("red".to_string()),
-            color: "#FF0000".to_string(),
-            stations: vec!["central".to_string(), "dam".to_string(), "museum".to_string()],
-            is_ring: false,
-            completion_points: (6, 3),
-        });
-        
-        lines.insert(LineId("blue".to_string()), SubwayLine {
-            id: LineId("blue".to_string()),
-            color: "#0000FF".to_string(),
-            stations: vec!["central".to_string()],
-            is_ring: false,
-            completion_points: (4, 2),
-        });
-        
-        SubwayMap {
-            city: City::Amsterdam,
-            stations,
-            lines,
-            special_stations: Vec::new(),
+    fn handle_message(&mut self, message: GameMessage) {
+        match message {
+            GameMessage::Welcome { protocol_version, capabilities, wire_format, .. } => {
+                self.wire_format.set(wire_format);
+                web_sys::console::log_1(&format!("Speaking protocol {} with a server offering {:?}", protocol_version, capabilities).into());
+            },
+            GameMessage::HelloRejected { reason, .. } => {
+                let _ = self.forget_seat(); // Or we would keep reconnecting
+                if let Some(window) = window() {
+                    let _ = window.alert_with_message(&reason);
+                }
+            },
+            GameMessage::Authenticated { username } => {
+                let _ = self.append_chat_line(&format!("Logged in as {}", username), "chat-reaction");
+            },
+            GameMessage::Queued { waiting } => {
+                let others = waiting.saturating_sub(1);
+                let _ = self.append_chat_line(&format!("Looking for a room, {} other player(s) waiting", others), "chat-reaction");
+            },
+            GameMessage::GameJoined { player_id, game_id, subway_map, roster, rejoin_token } => {
+                let rejoined = self.game_id == Some(game_id) && self.player_id == Some(player_id);
+                self.player_id = Some(player_id);
+                self.game_id = Some(game_id);
+                self.subway_map = Some(subway_map);
+                let _ = self.show_roster(roster);
+                let _ = self.remember_seat(game_id, player_id, rejoin_token);
+                if rejoined {
+                    let _ = self.resend_pending_actions();
+                } else {
+                    self.pending_actions.borrow_mut().clear();
+                }
+                web_sys::console::log_1(&format!("Joined game {} as player {}", game_id, player_id).into());
+            },
+            GameMessage::Spectating { game_id, subway_map } => {
+                self.spectating = true;
+                self.game_id = Some(game_id);
+                self.subway_map = Some(subway_map);
+                web_sys::console::log_1(&format!("Spectating game {}", game_id).into());
+            },
+            GameMessage::SpectatorsChanged { count } => {
+                self.spectator_count = count;
+                let _ = self.draw_game();
+            },
+            GameMessage::RosterUpdated(roster) => {
+                let _ = self.show_roster(roster);
+            },
+            GameMessage::StateSnapshot { version, checksum, state } => {
+                if state.checksum() != checksum {
+                    // Like a bad delta, but give up if the server keeps sending bad ones
+                    self.game_state = None;
+                    self.bad_snapshots += 1;
+                    if self.bad_snapshots <= MAX_SNAPSHOT_RETRIES {
+                        web_sys::console::warn_1(&"Snapshot does not match its checksum, requesting another".into());
+                        let _ = self.send_message(&GameMessage::RequestSnapshot);
+                    } else {
+                        web_sys::console::error_1(&"Snapshots keep failing their checksum, please reload".into());
+                    }
+                    return;
+                }
+                self.bad_snapshots = 0;
+                let _ = self.show_roster(state.roster.clone());
+                self.state_version = version;
+                self.game_state = Some(state);
+                let _ = self.draw_game();
+            },
+            GameMessage::StateDelta { version, checksum, patches } => {
+                if let Err(error) = self.apply_delta(version, checksum, patches) {
+                    web_sys::console::warn_1(&format!("Requesting a new snapshot: {}", error).into());
+                    let _ = self.send_message(&GameMessage::RequestSnapshot);
+                }
+                let _ = self.draw_game();
+            },
+            GameMessage::CardRevealed { deadline, .. } => {
+                self.round_deadline = deadline;
+            },
+            GameMessage::PlayerTimedOut { player_id, action } => {
+                web_sys::console::log_1(&format!("Player {} ran out of time, played {:?}", player_id, action).into());
+            },
+            GameMessage::LineCompleted { player_id, line_id } => {
+                let _ = self.append_chat_line(&format!("{} completed the {} line", self.player_label(&player_id), line_id.0), "chat-reaction");
+                self.last_line_completed = Some(ReactionTarget::LineCompleted { player_id, line_id });
+            },
+            GameMessage::DeckReshuffled { reason, deck_count, .. } => {
+                let text = match reason {
+                    ReshuffleReason::CardSix => format!("The 6 was played, the deck was reshuffled ({} cards)", deck_count),
+                    ReshuffleReason::DeckEmpty => format!("The deck ran out and was reshuffled ({} cards)", deck_count),
+                };
+                let _ = self.append_chat_line(&text, "chat-reaction");
+            },
+            GameMessage::Chat(entry) => {
+                let _ = self.show_chat_entry(&entry);
+            },
+            GameMessage::ChatHistory(entries) => {
+                self.chat_messages.set_inner_html("");
+                for entry in &entries {
+                    let _ = self.show_chat_entry(entry);
+                }
+            },
+            GameMessage::Reaction { sender, emote, target } => {
+                let _ = self.show_reaction(&sender, emote, target);
+            },
+            GameMessage::GameEnded { scores } => {
+                let mut scores: Vec<(uuid::Uuid, i32)> = scores.into_iter().collect();
+                scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+                
+                let _ = self.append_chat_line("Game ended!", "chat-reaction");
+                for (player_id, score) in scores {
+                    let _ = self.append_chat_line(&format!("{}: {} points", self.player_label(&player_id), score), "chat-reaction");
+                }
+            },
+            GameMessage::PlayerActionResult { request_id, message, .. } => {
+                self.settle_request(request_id);
+                web_sys::console::log_1(&format!("Request {:?}: {}", request_id, message).into());
+            },
+            GameMessage::Error { request_id, message } => {
+                self.settle_request(request_id);
+                if request_id.is_some() && request_id == self.rejoin_request.get() {
+                    // The game is over or gone, there is nothing to take back
+                    let _ = self.forget_seat();
+                }
+                web_sys::console::error_1(&format!("Request {:?} failed: {}", request_id, message).into());
+            },
+            _ => {}
         }
     }
 }
\ No newline at end of file
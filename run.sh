@@ -41,8 +41,7 @@ echo ""
 echo "🎮 Verplant is ready to play!"
 echo ""
 echo "To start the game:"
-echo "1. Terminal 1: cargo run -p verplant_server"
-echo "2. Terminal 2: cd client && python -m http.server 8000"
-echo "3. Open http://localhost:8000 in your browser"
+echo "1. cargo run -p verplant_server"
+echo "2. Open http://localhost:8080 in your browser"
 echo ""
 echo "Have fun playing the subway game! 🚇"
\ No newline at end of file
@@ -0,0 +1,576 @@
+// Generated by verplant_schema from the shared crate, do not edit.
+// Checksums are 64-bit and can exceed Number.MAX_SAFE_INTEGER, parse them as bigint if you verify them.
+
+export type Card = "Six" | "Transfer" | "FreeRide" | "Delay" | "Closure" | {
+    Number: number;
+} | {
+    Express: number;
+} | {
+    Double: number;
+};
+
+export type ChatEntry = {
+    from_spectator: boolean;
+    sender: string;
+    sent_at: number;
+    text: string;
+};
+
+export type City = "Amsterdam" | "Berlin" | "Paris" | "Madrid";
+
+export type ClientKind = "Web" | "Bot" | "Other";
+
+/**
+ * The envelope every client to server message travels in.
+ */
+export type ClientRequest = {
+    message: GameMessage;
+    request_id?: number | null;
+};
+
+export type CompletionStatus = "NotCompleted" | {
+    FirstToComplete: number;
+} | {
+    LaterCompletion: number;
+};
+
+/**
+ * The solo game everyone plays on a given day: the same map and the same seed,
+ * so the same cards in the same order.
+ */
+export type DailyChallenge = {
+    city: City;
+    date: string;
+    seed: number;
+};
+
+/**
+ * A verified score on a daily leaderboard.
+ */
+export type DailyEntry = {
+    id: string;
+    player_name: string;
+    replay: Replay;
+    score: number;
+    submitted_at: number;
+    submitter?: string;
+};
+
+/**
+ * A finished daily challenge sent in for the leaderboard. The score is checked
+ * by playing the replay again.
+ */
+export type DailySubmission = {
+    player_name: string;
+    replay: Replay;
+    score: number;
+};
+
+/**
+ * How many of each card go into the deck.
+ */
+export type DeckConfig = {
+    express: number[];
+    free_rides: number;
+    number_copies: number;
+    sixes: number;
+    transfers: number;
+};
+
+export type Emote = "Clap" | "Wow" | "Laugh" | "Sad" | "Train";
+
+/**
+ * Optional cards a room can add on top of its deck.
+ */
+export type Expansion = "Delay" | "Double" | "Closure";
+
+/**
+ * What an express card does when it reaches a station that is already marked.
+ */
+export type ExpressRule = "SkipMarked" | "StopAtMarked";
+
+export type GameEndRule = "AllSheetsFull" | "FirstSheetFull" | {
+    Rounds: number;
+};
+
+export type GameMessage = "StartGame" | "RequestSnapshot" | {
+    Hello: {
+        client_kind: ClientKind;
+        protocol_version: number;
+        wire_formats?: WireFormat[];
+    };
+} | {
+    JoinGame: {
+        city: City;
+        game_id?: string | null;
+        player_name: string;
+    };
+} | {
+    Rejoin: {
+        game_id: string;
+        player_id: string;
+        token: string;
+    };
+} | {
+    Authenticate: {
+        token: string;
+    };
+} | {
+    PlayerAction: PlayerAction;
+} | {
+    Spectate: {
+        game_id: string;
+    };
+} | {
+    SendChat: {
+        text: string;
+    };
+} | {
+    React: {
+        emote: Emote;
+        target?: ReactionTarget | null;
+    };
+} | {
+    Welcome: {
+        capabilities: string[];
+        protocol_version: number;
+        supported_versions: number[];
+        wire_format?: WireFormat;
+    };
+} | {
+    HelloRejected: {
+        reason: string;
+        supported_versions: number[];
+    };
+} | {
+    Authenticated: {
+        username: string;
+    };
+} | {
+    Queued: {
+        waiting: number;
+    };
+} | {
+    GameJoined: {
+        game_id: string;
+        player_id: string;
+        rejoin_token: string;
+        roster: PlayerInfo[];
+        subway_map: SubwayMap;
+    };
+} | {
+    RosterUpdated: PlayerInfo[];
+} | {
+    Spectating: {
+        game_id: string;
+        subway_map: SubwayMap;
+    };
+} | {
+    SpectatorsChanged: {
+        count: number;
+    };
+} | {
+    Chat: ChatEntry;
+} | {
+    ChatHistory: ChatEntry[];
+} | {
+    Reaction: {
+        emote: Emote;
+        sender: string;
+        target?: ReactionTarget | null;
+    };
+} | {
+    StateSnapshot: {
+        checksum: number;
+        state: GameStateView;
+        version: number;
+    };
+} | {
+    StateDelta: {
+        checksum: number;
+        patches: StatePatch[];
+        version: number;
+    };
+} | {
+    CardRevealed: {
+        card: Card;
+        deadline?: number | null;
+        time_banks: { [key: string]: number };
+    };
+} | {
+    PlayerTimedOut: {
+        action?: PlayerAction | null;
+        player_id: string;
+    };
+} | {
+    PlayerActionResult: {
+        message: string;
+        request_id?: number | null;
+        success: boolean;
+    };
+} | {
+    LineCompleted: {
+        line_id: LineId;
+        player_id: string;
+    };
+} | {
+    DeckReshuffled: {
+        deck_count: number;
+        discard_count: number;
+        reason: ReshuffleReason;
+    };
+} | {
+    GameEnded: {
+        scores: { [key: string]: number };
+    };
+} | {
+    Error: {
+        message: string;
+        request_id?: number | null;
+    };
+};
+
+/**
+ * Where a game is in its lifecycle. Only the transitions allowed by
+ * [`GamePhase::can_become`] are ever taken.
+ */
+export type GamePhase = "Lobby" | "WaitingForActions" | "RoundOver" | "ResolvingCardSix" | "Finished";
+
+/**
+ * Everything needed to look at a finished game again: who played, how they
+ * scored and every card and move in order.
+ */
+export type GameRecord = {
+    accounts?: { [key: string]: string };
+    city: City;
+    completed_lines?: { [key: string]: LineId[] };
+    finished_at: number;
+    game_id: string;
+    players: string[];
+    replay: Replay;
+    roster?: PlayerInfo[];
+    scores: { [key: string]: number };
+};
+
+export type GameState = {
+    acted_this_round?: string[];
+    city: City;
+    conductor: string;
+    current_card?: Card | null;
+    deck: Card[];
+    discard_pile: Card[];
+    id: string;
+    pending_closures?: string[];
+    pending_completions?: PendingCompletion[];
+    phase?: GamePhase;
+    players: { [key: string]: PlayerSheet };
+    plays_this_round?: { [key: string]: number };
+    rng_state?: number;
+    roster?: PlayerInfo[];
+    round: number;
+    rules?: RuleSet;
+};
+
+/**
+ * What a client is allowed to know about a game. Unlike `GameState` it does not
+ * contain the order of the remaining deck, only how many cards are left.
+ */
+export type GameStateView = {
+    acted_this_round: string[];
+    city: City;
+    conductor: string;
+    current_card?: Card | null;
+    deck_count: number;
+    discard_pile: Card[];
+    id: string;
+    phase: GamePhase;
+    players: { [key: string]: PlayerSheet };
+    roster: PlayerInfo[];
+    round: number;
+    scores: { [key: string]: number };
+    viewer?: string | null;
+};
+
+/**
+ * How often an account completed a line.
+ */
+export type LineCount = {
+    city: City;
+    count: number;
+    line_id: LineId;
+};
+
+export type LineId = string;
+
+/**
+ * A line completed during the current round. It is scored when the round
+ * ends, see `GameState::next_round`.
+ */
+export type PendingCompletion = {
+    line_id: LineId;
+    player_id: string;
+    points: [number, number];
+};
+
+export type PlayerAction = {
+    ChooseLine: {
+        car_window_index: number;
+        line_id: LineId;
+    };
+} | {
+    MarkTransferStation: {
+        station_id: string;
+    };
+} | {
+    MarkFreeRideStation: {
+        station_id: string;
+    };
+} | {
+    CloseStation: {
+        station_id: string;
+    };
+} | {
+    CompleteLineAnnouncement: {
+        line_id: LineId;
+    };
+};
+
+/**
+ * Public information about a player in a room, shared with everyone in it.
+ */
+export type PlayerInfo = {
+    color: string;
+    connected: boolean;
+    id: string;
+    is_bot: boolean;
+    is_conductor: boolean;
+    name: string;
+};
+
+export type PlayerSheet = {
+    city: City;
+    closed_stations?: string[];
+    completed_lines: LineId[];
+    line_completion_status: { [key: string]: CompletionStatus };
+    marked_stations: { [key: string]: StationMark };
+    player_id: string;
+    train_cars: { [key: string]: (string | null)[] };
+};
+
+/**
+ * An account's record over every finished game it played.
+ */
+export type PlayerStats = {
+    average_score: number;
+    best_scores: {
+        Amsterdam?: number;
+        Berlin?: number;
+        Madrid?: number;
+        Paris?: number;
+    };
+    favourite_lines: LineCount[];
+    games_played: number;
+    wins: number;
+};
+
+/**
+ * What a reaction refers to, so clients can show it next to the event.
+ */
+export type ReactionTarget = {
+    LineCompleted: {
+        line_id: LineId;
+        player_id: string;
+    };
+} | {
+    Player: string;
+};
+
+export type Replay = {
+    rounds: ReplayRound[];
+};
+
+export type ReplayAction = {
+    action?: PlayerAction | null;
+    player_id: string;
+};
+
+export type ReplayRound = {
+    actions: ReplayAction[];
+    card: Card;
+};
+
+/**
+ * Why the discard pile went back into the deck.
+ */
+export type ReshuffleReason = "CardSix" | "DeckEmpty";
+
+/**
+ * The house rules of a game, fixed when its room is created.
+ */
+export type RuleSet = {
+    deck: DeckConfig;
+    double_transfers: boolean;
+    empty_station_penalty_divisor: number;
+    expansions?: Expansion[];
+    express: ExpressRule;
+    game_end: GameEndRule;
+};
+
+/**
+ * How players are spread over the tables of a round.
+ */
+export type SeatingRule = "Fixed" | "Random" | "ByStandings";
+
+/**
+ * A player's place in the tournament. Every game is worth a point per
+ * opponent beaten and half a point per opponent tied, a bye is worth
+ * `Tournament::BYE_POINTS`.
+ */
+export type Standing = {
+    completed_lines: number;
+    games_played: number;
+    player_name: string;
+    points: number;
+    total_score: number;
+    wins: number;
+};
+
+/**
+ * One change to a `GameStateView`. The server sends these in `StateDelta`
+ * messages so clients don't need a full snapshot after every move.
+ */
+export type StatePatch = {
+    CardRevealed: Card | null;
+} | {
+    WindowFilled: {
+        index: number;
+        line_id: LineId;
+        player_id: string;
+        value?: string | null;
+    };
+} | {
+    StationMarked: {
+        mark?: StationMark | null;
+        player_id: string;
+        station_id: string;
+    };
+} | {
+    LineCompleted: {
+        line_id: LineId;
+        player_id: string;
+    };
+} | {
+    LineStatusChanged: {
+        line_id: LineId;
+        player_id: string;
+        status: CompletionStatus;
+    };
+} | {
+    ScoreChanged: {
+        player_id: string;
+        score: number;
+    };
+} | {
+    SheetReplaced: PlayerSheet;
+} | {
+    PlayerRemoved: string;
+} | {
+    RosterChanged: PlayerInfo[];
+} | {
+    DeckChanged: {
+        deck_count: number;
+        discard_pile: Card[];
+    };
+} | {
+    RoundChanged: number;
+} | {
+    PhaseChanged: GamePhase;
+} | {
+    ConductorChanged: string;
+} | {
+    ActedChanged: string[];
+};
+
+export type Station = {
+    id: string;
+    is_transfer_hub: boolean;
+    lines: LineId[];
+    x: number;
+    y: number;
+};
+
+export type StationMark = "Cross" | {
+    TransferNumber: number;
+};
+
+export type SubwayLine = {
+    color: string;
+    completion_points: [number, number];
+    id: LineId;
+    is_ring: boolean;
+    stations: string[];
+};
+
+export type SubwayMap = {
+    city: City;
+    lines: { [key: string]: SubwayLine };
+    special_stations: string[];
+    stations: { [key: string]: Station };
+};
+
+/**
+ * A player's result at a finished table.
+ */
+export type TableResult = {
+    completed_lines: number;
+    forfeited?: boolean;
+    player_name: string;
+    score: number;
+};
+
+export type Tournament = {
+    accounts?: string[];
+    cities: City[];
+    created_at: number;
+    id: string;
+    name: string;
+    organizer?: string;
+    players: string[];
+    rounds: TournamentRound[];
+    seating: SeatingRule;
+    seed: number;
+    table_size: number;
+};
+
+export type TournamentRound = {
+    bye?: string | null;
+    city: City;
+    tables: TournamentTable[];
+};
+
+/**
+ * What a tournament is created from.
+ */
+export type TournamentSettings = {
+    cities: City[];
+    name: string;
+    players: string[];
+    seating?: SeatingRule;
+    table_size: number;
+};
+
+/**
+ * One game of a round.
+ */
+export type TournamentTable = {
+    game_id: string;
+    players: string[];
+    results?: TableResult[] | null;
+};
+
+/**
+ * How messages are encoded on a connection. JSON is always understood and is
+ * used for the handshake; a client may ask for MessagePack in `Hello`, after
+ * which the server sends binary frames.
+ */
+export type WireFormat = "Json" | "MessagePack";
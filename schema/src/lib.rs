@@ -0,0 +1,153 @@
+//! JSON Schema and TypeScript definitions of the Verplant protocol, for tools
+//! that talk to the server without using the shared crate.
+
+use schemars::generate::SchemaSettings;
+use serde_json::{Map, Value, json};
+
+use verplant::{
+    ClientRequest, DailyChallenge, DailyEntry, DailySubmission, GameMessage, GameRecord, GameState, GameStateView, PlayerAction, PlayerStats,
+    Standing, SubwayMap, Tournament, TournamentSettings,
+};
+
+pub const JSON_SCHEMA_FILE: &str = "protocol.schema.json";
+pub const TYPESCRIPT_FILE: &str = "protocol.d.ts";
+
+/// One JSON Schema document with every protocol type under `$defs`.
+pub fn json_schema() -> Value {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    generator.subschema_for::<ClientRequest>();
+    generator.subschema_for::<GameMessage>();
+    generator.subschema_for::<PlayerAction>();
+    generator.subschema_for::<GameState>();
+    generator.subschema_for::<GameStateView>();
+    generator.subschema_for::<SubwayMap>();
+    generator.subschema_for::<GameRecord>();
+    generator.subschema_for::<DailyChallenge>();
+    generator.subschema_for::<DailySubmission>();
+    generator.subschema_for::<DailyEntry>();
+    generator.subschema_for::<PlayerStats>();
+    generator.subschema_for::<TournamentSettings>();
+    generator.subschema_for::<Tournament>();
+    generator.subschema_for::<Standing>();
+    
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Verplant protocol",
+        "$defs": generator.take_definitions(true),
+    })
+}
+
+/// TypeScript declarations for every definition in `json_schema()`.
+pub fn typescript(schema: &Value) -> Result<String, String> {
+    let definitions = schema.get("$defs").and_then(Value::as_object)
+        .ok_or("Schema has no $defs")?;
+    
+    let mut output = String::from(concat!(
+        "// Generated by verplant_schema from the shared crate, do not edit.\n",
+        "// Checksums are 64-bit and can exceed Number.MAX_SAFE_INTEGER, parse them as bigint if you verify them.\n",
+    ));
+    for (name, definition) in definitions {
+        output.push('\n');
+        if let Some(description) = definition.get("description").and_then(Value::as_str) {
+            output.push_str("/**\n");
+            for line in description.lines() {
+                output.push_str(&format!(" * {}\n", line).replace(" \n", "\n"));
+            }
+            output.push_str(" */\n");
+        }
+        output.push_str(&format!("export type {} = {};\n", name, ts_type(definition, 0)?));
+    }
+    
+    Ok(output)
+}
+
+fn ts_type(schema: &Value, indent: usize) -> Result<String, String> {
+    let Some(schema) = schema.as_object() else {
+        // `true` accepts anything, `false` nothing
+        return Ok(if schema.as_bool() == Some(false) { "never" } else { "unknown" }.to_string());
+    };
+    
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.strip_prefix("#/$defs/")
+            .ok_or_else(|| format!("Unsupported reference {}", reference))?;
+        return Ok(name.to_string());
+    }
+    if let Some(value) = schema.get("const") {
+        return Ok(value.to_string());
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return Ok(values.iter().map(Value::to_string).collect::<Vec<_>>().join(" | "));
+    }
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(variants) = schema.get(keyword).and_then(Value::as_array) {
+            let variants = variants.iter()
+                .map(|variant| ts_type(variant, indent))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(variants.join(" | "));
+        }
+    }
+    
+    match schema.get("type") {
+        Some(Value::String(kind)) => ts_type_of_kind(kind, schema, indent),
+        Some(Value::Array(kinds)) => {
+            let kinds = kinds.iter()
+                .map(|kind| ts_type_of_kind(kind.as_str().unwrap_or_default(), schema, indent))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(kinds.join(" | "))
+        },
+        _ => Ok("unknown".to_string()),
+    }
+}
+
+fn ts_type_of_kind(kind: &str, schema: &Map<String, Value>, indent: usize) -> Result<String, String> {
+    match kind {
+        "string" => Ok("string".to_string()),
+        "integer" | "number" => Ok("number".to_string()),
+        "boolean" => Ok("boolean".to_string()),
+        "null" => Ok("null".to_string()),
+        "array" => {
+            if let Some(items) = schema.get("prefixItems").and_then(Value::as_array) {
+                let items = items.iter()
+                    .map(|item| ts_type(item, indent))
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Ok(format!("[{}]", items.join(", ")));
+            }
+            
+            let item = ts_type(schema.get("items").unwrap_or(&Value::Bool(true)), indent)?;
+            if item.contains(' ') {
+                Ok(format!("({})[]", item))
+            } else {
+                Ok(format!("{}[]", item))
+            }
+        },
+        "object" => ts_object(schema, indent),
+        _ => Err(format!("Unsupported type {}", kind)),
+    }
+}
+
+fn ts_object(schema: &Map<String, Value>, indent: usize) -> Result<String, String> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let additional = schema.get("additionalProperties").filter(|additional| additional.is_object());
+    
+    if let (None, Some(additional)) = (properties, additional) {
+        return Ok(format!("{{ [key: string]: {} }}", ts_type(additional, indent)?));
+    }
+    let Some(properties) = properties else {
+        return Ok("{}".to_string());
+    };
+    
+    let required: Vec<&str> = schema.get("required").and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let padding = "    ".repeat(indent + 1);
+    
+    let mut output = String::from("{\n");
+    for (name, property) in properties {
+        let optional = if required.contains(&name.as_str()) { "" } else { "?" };
+        output.push_str(&format!("{}{}{}: {};\n", padding, name, optional, ts_type(property, indent + 1)?));
+    }
+    output.push_str(&"    ".repeat(indent));
+    output.push('}');
+    
+    Ok(output)
+}
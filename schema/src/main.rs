@@ -0,0 +1,37 @@
+//! Writes the protocol definitions to `schema/generated`, or to the directory
+//! given as the only argument.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use verplant_schema::{JSON_SCHEMA_FILE, TYPESCRIPT_FILE, json_schema, typescript};
+
+fn main() -> ExitCode {
+    let output_dir = std::env::args_os().nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("generated"));
+    
+    let schema = json_schema();
+    let typescript = match typescript(&schema) {
+        Ok(typescript) => typescript,
+        Err(e) => {
+            eprintln!("Could not generate TypeScript: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    
+    let files = [
+        (JSON_SCHEMA_FILE, serde_json::to_string_pretty(&schema).unwrap() + "\n"),
+        (TYPESCRIPT_FILE, typescript),
+    ];
+    for (name, contents) in files {
+        let path = output_dir.join(name);
+        if let Err(e) = std::fs::create_dir_all(&output_dir).and_then(|_| std::fs::write(&path, contents)) {
+            eprintln!("Could not write {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+        println!("Wrote {}", path.display());
+    }
+    
+    ExitCode::SUCCESS
+}
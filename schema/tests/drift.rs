@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+use verplant_schema::{JSON_SCHEMA_FILE, TYPESCRIPT_FILE, json_schema, typescript};
+
+fn checked_in(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("generated").join(name);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e))
+}
+
+const REGENERATE: &str = "the protocol types changed, run `cargo run -p verplant_schema` and commit the result";
+
+#[test]
+fn json_schema_is_up_to_date() {
+    let expected = serde_json::to_string_pretty(&json_schema()).unwrap() + "\n";
+    assert!(checked_in(JSON_SCHEMA_FILE) == expected, "{} is out of date: {}", JSON_SCHEMA_FILE, REGENERATE);
+}
+
+#[test]
+fn typescript_is_up_to_date() {
+    let expected = typescript(&json_schema()).unwrap();
+    assert!(checked_in(TYPESCRIPT_FILE) == expected, "{} is out of date: {}", TYPESCRIPT_FILE, REGENERATE);
+}
@@ -0,0 +1,237 @@
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::Argon2;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use verplant::{DEFAULT_RATING, GameRecord, RatedPlayer, StatsTally, update_ratings, validate_player_name};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Logins kept per account. Logging in once more ends the oldest one.
+const MAX_TOKENS_PER_ACCOUNT: usize = 8;
+
+/// How long a login lasts, in milliseconds.
+const TOKEN_LIFETIME_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+/// Checked against when a login names an unknown account, so that it takes as
+/// long as one with a wrong password and doesn't tell which names exist.
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| hash_password("not anyone's password").unwrap());
+
+/// A registered player. Only an argon2 hash of the password is stored, and
+/// only SHA-256 hashes of the random tokens handed out on login.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub id: Uuid,
+    pub username: String,
+    pub created_at: u64, // Unix time in milliseconds
+    #[serde(default = "default_rating")]
+    pub rating: f64,
+    #[serde(default)]
+    pub rated_games: u32,
+    #[serde(default)]
+    pub stats: StatsTally, // Every finished game the account played
+    #[serde(default)]
+    pub game_ids: Vec<Uuid>, // The same games, oldest first
+    password_hash: String,
+    tokens: Vec<StoredToken>, // Oldest first
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    hash: String, // Hex SHA-256 of the token
+    expires_at: u64, // Unix time in milliseconds
+}
+
+impl StoredToken {
+    fn new(token: Uuid, now: u64) -> Self {
+        Self { hash: hash_token(token), expires_at: now + TOKEN_LIFETIME_MS }
+    }
+}
+
+/// Player accounts, kept as one JSON file below the persistence path.
+pub struct Accounts {
+    path: PathBuf,
+    accounts: Mutex<Vec<Account>>,
+}
+
+impl Accounts {
+    pub fn open(root: &Path) -> std::io::Result<Accounts> {
+        let path = root.join("accounts.json");
+        let accounts = match std::fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Accounts { path, accounts: Mutex::new(accounts) })
+    }
+    
+    /// Creates an account and logs it in.
+    pub async fn register(&self, username: &str, password: &str) -> Result<(Account, Uuid), String> {
+        let username = validate_player_name(username)?;
+        let length = password.chars().count();
+        if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&length) {
+            return Err(format!("Passwords must have {} to {} characters", MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH));
+        }
+        
+        // Hashing takes a while on purpose, keep it off the async workers
+        let password = password.to_string();
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await
+            .map_err(|e| e.to_string())??;
+        
+        let mut accounts = self.accounts.lock().await;
+        if find(&accounts, &username).is_some() {
+            return Err(format!("The name {} is already taken", username));
+        }
+        
+        let token = Uuid::new_v4();
+        let now = now_ms();
+        let account = Account {
+            id: Uuid::new_v4(),
+            username,
+            created_at: now,
+            rating: DEFAULT_RATING,
+            rated_games: 0,
+            stats: StatsTally::default(),
+            game_ids: Vec::new(),
+            password_hash,
+            tokens: vec![StoredToken::new(token, now)],
+        };
+        accounts.push(account.clone());
+        self.save(&accounts).await?;
+        
+        Ok((account, token))
+    }
+    
+    /// Checks the password and hands out a new token.
+    pub async fn login(&self, username: &str, password: &str) -> Result<(Account, Uuid), String> {
+        let invalid = || "Wrong name or password".to_string();
+        
+        let password_hash = self.find(username).await
+            .map(|account| account.password_hash);
+        let known = password_hash.is_some();
+        let password = password.to_string();
+        let check = move || {
+            let password_hash = password_hash.unwrap_or_else(|| DUMMY_PASSWORD_HASH.clone());
+            verify_password(&password, &password_hash)
+        };
+        let valid = tokio::task::spawn_blocking(check)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !known || !valid {
+            return Err(invalid());
+        }
+        
+        let mut accounts = self.accounts.lock().await;
+        let account = accounts.iter_mut()
+            .find(|account| account.username.eq_ignore_ascii_case(username.trim()))
+            .ok_or_else(invalid)?;
+        
+        let token = Uuid::new_v4();
+        let now = now_ms();
+        account.tokens.retain(|stored| stored.expires_at > now);
+        account.tokens.push(StoredToken::new(token, now));
+        if account.tokens.len() > MAX_TOKENS_PER_ACCOUNT {
+            account.tokens.remove(0);
+        }
+        let account = account.clone();
+        self.save(&accounts).await?;
+        
+        Ok((account, token))
+    }
+    
+    /// The account a token from `register` or `login` belongs to, unless the
+    /// token expired.
+    pub async fn authenticate(&self, token: Uuid) -> Option<Account> {
+        let hash = hash_token(token);
+        let now = now_ms();
+        self.accounts.lock().await.iter()
+            .find(|account| account.tokens.iter().any(|stored| stored.hash == hash && stored.expires_at > now))
+            .cloned()
+    }
+    
+    /// Looks an account up by name, ignoring case.
+    pub async fn find(&self, username: &str) -> Option<Account> {
+        find(&self.accounts.lock().await, username).cloned()
+    }
+    
+    /// Updates the ratings of the accounts that played a game together, given
+    /// each account's final score.
+    pub async fn record_results(&self, results: &[(Uuid, i32)]) -> Result<(), String> {
+        let mut accounts = self.accounts.lock().await;
+        let players: Vec<RatedPlayer> = results.iter()
+            .filter_map(|(account_id, score)| {
+                let account = accounts.iter().find(|account| account.id == *account_id)?;
+                Some(RatedPlayer { id: account.id, rating: account.rating, score: *score })
+            })
+            .collect();
+        if players.len() < 2 {
+            return Ok(());
+        }
+        
+        let ratings = update_ratings(&players);
+        for account in accounts.iter_mut() {
+            if let Some(rating) = ratings.get(&account.id) {
+                account.rating = *rating;
+                account.rated_games += 1;
+            }
+        }
+        self.save(&accounts).await
+    }
+    
+    /// Adds a finished game to the stats and games of every account that
+    /// played in it.
+    pub async fn record_game(&self, record: &GameRecord) -> Result<(), String> {
+        let mut accounts = self.accounts.lock().await;
+        for account in accounts.iter_mut() {
+            if let Some(player_id) = record.player_for(account.id) {
+                account.stats.add_game(record, player_id);
+                account.game_ids.push(record.game_id);
+            }
+        }
+        self.save(&accounts).await
+    }
+    
+    async fn save(&self, accounts: &[Account]) -> Result<(), String> {
+        let contents = serde_json::to_vec_pretty(accounts).map_err(|e| e.to_string())?;
+        tokio::fs::write(&self.path, contents).await.map_err(|e| {
+            log::error!("Could not save {}: {}", self.path.display(), e);
+            "Could not save the account".to_string()
+        })
+    }
+}
+
+fn find<'a>(accounts: &'a [Account], username: &str) -> Option<&'a Account> {
+    accounts.iter().find(|account| account.username.eq_ignore_ascii_case(username.trim()))
+}
+
+fn default_rating() -> f64 {
+    DEFAULT_RATING
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+fn hash_token(token: Uuid) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes()).map_err(|e| e.to_string())?;
+    Argon2::default().hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+}
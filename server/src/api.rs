@@ -0,0 +1,736 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use verplant::{
+    City, DailyChallenge, DailyEntry, DailySubmission, Expansion, GamePhase, GameRecord, GameStateView, LineId, PlayerInfo, PlayerStats, Replay,
+    RoundSeating, RulePreset, RuleSet, SoloGame, SoloTargets, Standing, SubwayMap, Tournament, TournamentRound, TournamentSettings, TournamentTable,
+    validate_player_name,
+};
+
+use crate::accounts::Account;
+use crate::config::MAX_PLAYERS_LIMIT;
+use crate::{GameServer, GameSession};
+
+type ApiResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+/// Scores one player name may have on a single daily leaderboard.
+const DAILY_ENTRIES_PER_SUBMITTER: usize = 3;
+
+/// How long after midnight (UTC) yesterday's challenge still takes scores, so
+/// games started before it can be sent in.
+const DAILY_GRACE_SECS: u64 = 60 * 60;
+
+/// Leaderboard entries returned when the request doesn't ask for a number, and
+/// the most it may ask for.
+const LEADERBOARD_PAGE: usize = 50;
+const LEADERBOARD_PAGE_LIMIT: usize = 200;
+
+/// The same for the games of an account.
+const HISTORY_PAGE: usize = 20;
+const HISTORY_PAGE_LIMIT: usize = 100;
+
+/// Settings for a new room. Everything except the city falls back to the
+/// server configuration.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoomOptions {
+    pub city: City,
+    #[serde(default)]
+    pub max_players: Option<usize>,
+    #[serde(default)]
+    pub round_timeout_secs: Option<u64>, // 0 disables the deadline
+    #[serde(default)]
+    pub time_bank_secs: Option<u64>,     // 0 disables the time bank
+    #[serde(default)]
+    pub spectator_chat: Option<bool>,    // Whether spectators may chat, defaults to true
+    #[serde(default)]
+    pub preset: Option<RulePreset>,
+    #[serde(default)]
+    pub rules: Option<RuleSet>,          // Custom rules, take precedence over the preset
+    #[serde(default)]
+    pub expansions: Option<Vec<Expansion>>, // Replaces the expansions of the chosen rules
+}
+
+impl RoomOptions {
+    pub fn for_city(city: City) -> Self {
+        Self {
+            city,
+            max_players: None,
+            round_timeout_secs: None,
+            time_bank_secs: None,
+            spectator_chat: None,
+            preset: None,
+            rules: None,
+            expansions: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+enum RoomStatus {
+    Waiting,
+    InProgress,
+    Finished,
+}
+
+#[derive(Debug, Serialize)]
+struct RoomSummary {
+    id: Uuid,
+    city: City,
+    status: RoomStatus,
+    players: usize,
+    max_players: usize,
+    spectators: usize,
+    round: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct RoomDetails {
+    #[serde(flatten)]
+    summary: RoomSummary,
+    rules: RuleSet,
+    state: GameStateView,
+    scores: Option<HashMap<Uuid, i32>>, // Once the game is over
+}
+
+#[derive(Debug, Serialize)]
+struct GameSummary {
+    game_id: Uuid,
+    city: City,
+    players: Vec<Uuid>,
+    roster: Vec<PlayerInfo>,
+    scores: HashMap<Uuid, i32>,
+    finished_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct DailyDetails {
+    #[serde(flatten)]
+    challenge: DailyChallenge,
+    targets: SoloTargets,
+}
+
+/// `?offset=&limit=` of a leaderboard request.
+#[derive(Debug, Deserialize)]
+struct Page {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct LeaderboardEntry {
+    rank: usize,
+    id: Uuid,
+    player_name: String,
+    score: i32,
+    submitted_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct SubmissionResult {
+    id: Uuid,
+    rank: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Credentials {
+    username: String,
+    password: String,
+}
+
+/// Returned on registering and logging in. Send the token in `Authenticate`.
+#[derive(Debug, Serialize)]
+struct LoginResult {
+    username: String,
+    token: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+struct AccountDetails {
+    username: String,
+    created_at: u64,
+    rating: f64,
+    rated_games: u32,
+    stats: PlayerStats,
+}
+
+#[derive(Debug, Serialize)]
+struct HistoryEntry {
+    game_id: Uuid,
+    city: City,
+    score: i32,
+    won: bool,
+    finished_at: u64,
+    replay: String, // Where to fetch the replay
+}
+
+#[derive(Debug, Serialize)]
+struct TournamentSummary {
+    id: Uuid,
+    name: String,
+    players: usize,
+    rounds_started: usize,
+    rounds: usize,
+    finished: bool,
+    created_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct TournamentDetails {
+    #[serde(flatten)]
+    tournament: Tournament,
+    finished: bool,
+    standings: Vec<Standing>,
+}
+
+/// Body of a forfeit request.
+#[derive(Debug, Deserialize)]
+struct Forfeit {
+    players: Vec<String>, // Everyone else at the table wins against these
+}
+
+#[derive(Debug, Serialize)]
+struct MapSummary {
+    city: City,
+    stations: usize,
+    lines: Vec<LineId>,
+}
+
+/// REST endpoints, mounted below `/api`.
+pub fn router() -> Router<GameServer> {
+    Router::new()
+        .route("/rooms", get(list_rooms).post(create_room))
+        .route("/rooms/{id}", get(get_room))
+        .route("/games", get(list_games))
+        .route("/games/{id}", get(get_game))
+        .route("/games/{id}/replay", get(get_replay))
+        .route("/maps", get(list_maps))
+        .route("/maps/{city}", get(get_map))
+        .route("/accounts", post(register))
+        .route("/login", post(login))
+        .route("/accounts/{username}", get(get_account))
+        .route("/accounts/{username}/games", get(get_account_games))
+        .route("/daily", get(get_daily))
+        .route("/daily/{date}/leaderboard", get(get_leaderboard))
+        .route("/daily/{date}/scores", post(submit_daily_score))
+        .route("/daily/{date}/replays/{id}", get(get_daily_replay))
+        .route("/tournaments", get(list_tournaments).post(create_tournament))
+        .route("/tournaments/{id}", get(get_tournament))
+        .route("/tournaments/{id}/rounds", post(start_tournament_round))
+        .route("/tournaments/{id}/tables/{game_id}/forfeit", post(forfeit_tournament_table))
+        .route("/tournaments/{id}/tables/{game_id}/reopen", post(reopen_tournament_table))
+}
+
+fn rooms_full() -> (StatusCode, String) {
+    (StatusCode::SERVICE_UNAVAILABLE, "The server has no room for more games, try again later".to_string())
+}
+
+fn not_found(what: &str) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("{} not found", what))
+}
+
+fn room_summary(session: &GameSession) -> RoomSummary {
+    let status = match session.game_state.phase {
+        GamePhase::Lobby => RoomStatus::Waiting,
+        GamePhase::Finished => RoomStatus::Finished,
+        _ => RoomStatus::InProgress,
+    };
+    
+    RoomSummary {
+        id: session.game_state.id,
+        city: session.game_state.city.clone(),
+        status,
+        players: session.players.len(),
+        max_players: session.max_players,
+        spectators: session.spectators.len(),
+        round: session.game_state.round,
+    }
+}
+
+async fn list_rooms(State(server): State<GameServer>) -> Json<Vec<RoomSummary>> {
+    let sessions: Vec<_> = server.sessions.read().await.values().cloned().collect();
+    
+    let mut rooms = Vec::new();
+    for session in sessions {
+        rooms.push(room_summary(&*session.lock().await));
+    }
+    rooms.sort_by_key(|room| room.id);
+    
+    Json(rooms)
+}
+
+/// Opens a room. Every room costs a timer task until it is reaped, so their
+/// number is capped and each address may only open a few per minute.
+async fn create_room(
+    State(server): State<GameServer>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(options): Json<RoomOptions>,
+) -> ApiResult<RoomSummary> {
+    if let Some(max_players) = options.max_players
+        && !(1..=MAX_PLAYERS_LIMIT).contains(&max_players)
+    {
+        return Err((StatusCode::BAD_REQUEST, format!("max_players must be between 1 and {}", MAX_PLAYERS_LIMIT)));
+    }
+    if let Some(rules) = &options.rules {
+        rules.validate().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    }
+    if let Some(expansions) = &options.expansions {
+        let rules = RuleSet { expansions: expansions.clone(), ..RuleSet::default() };
+        rules.validate().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    }
+    
+    if server.session_count().await >= server.config.max_rooms {
+        return Err(rooms_full());
+    }
+    if !server.room_throttle.try_acquire(addr.ip(), Instant::now()) {
+        return Err((StatusCode::TOO_MANY_REQUESTS, "You are creating rooms too quickly".to_string()));
+    }
+    
+    // Another request may have taken the last room in the meantime
+    let sessions = server.try_create_sessions(vec![options]).await.ok_or_else(rooms_full)?;
+    let summary = room_summary(&*sessions[0].lock().await);
+    Ok(Json(summary))
+}
+
+async fn get_room(State(server): State<GameServer>, Path(id): Path<Uuid>) -> ApiResult<RoomDetails> {
+    let session = server.sessions.read().await.get(&id).cloned()
+        .ok_or_else(|| not_found("Room"))?;
+    let session = session.lock().await;
+    
+    Ok(Json(RoomDetails {
+        summary: room_summary(&session),
+        rules: session.game_state.rules.clone(),
+        state: session.game_state.public_view(&session.subway_map),
+        scores: (session.game_state.phase == GamePhase::Finished)
+            .then(|| session.game_state.calculate_final_scores(&session.subway_map)),
+    }))
+}
+
+async fn list_games(State(server): State<GameServer>) -> Json<Vec<GameSummary>> {
+    let games = server.storage.list_games().await.into_iter()
+        .map(|record| GameSummary {
+            game_id: record.game_id,
+            city: record.city,
+            players: record.players,
+            roster: record.roster,
+            scores: record.scores,
+            finished_at: record.finished_at,
+        })
+        .collect();
+    
+    Json(games)
+}
+
+async fn get_game(State(server): State<GameServer>, Path(id): Path<Uuid>) -> ApiResult<GameRecord> {
+    server.storage.load_game(id).await
+        .map(Json)
+        .ok_or_else(|| not_found("Game"))
+}
+
+async fn get_replay(State(server): State<GameServer>, Path(id): Path<Uuid>) -> ApiResult<Replay> {
+    server.storage.load_game(id).await
+        .map(|record| Json(record.replay))
+        .ok_or_else(|| not_found("Game"))
+}
+
+async fn list_maps(State(server): State<GameServer>) -> Json<Vec<MapSummary>> {
+    let mut maps: Vec<MapSummary> = server.maps.values()
+        .map(|subway_map| {
+            let mut lines: Vec<LineId> = subway_map.lines.keys().cloned().collect();
+            lines.sort_by(|a, b| a.0.cmp(&b.0));
+            
+            MapSummary {
+                city: subway_map.city.clone(),
+                stations: subway_map.stations.len(),
+                lines,
+            }
+        })
+        .collect();
+    maps.sort_by_key(|summary| format!("{:?}", summary.city));
+    
+    Json(maps)
+}
+
+async fn get_map(State(server): State<GameServer>, Path(city): Path<City>) -> ApiResult<SubwayMap> {
+    server.maps.get(&city).cloned()
+        .map(Json)
+        .ok_or_else(|| not_found("Map"))
+}
+
+fn check_account_throttle(server: &GameServer, addr: SocketAddr) -> Result<(), (StatusCode, String)> {
+    if !server.account_throttle.try_acquire(addr.ip(), Instant::now()) {
+        return Err((StatusCode::TOO_MANY_REQUESTS, "Too many attempts, try again in a minute".to_string()));
+    }
+    Ok(())
+}
+
+async fn register(
+    State(server): State<GameServer>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(credentials): Json<Credentials>,
+) -> ApiResult<LoginResult> {
+    check_account_throttle(&server, addr)?;
+    if server.accounts.find(&credentials.username).await.is_some() {
+        return Err((StatusCode::CONFLICT, format!("The name {} is already taken", credentials.username.trim())));
+    }
+    
+    let (account, token) = server.accounts.register(&credentials.username, &credentials.password).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    Ok(Json(LoginResult { username: account.username, token }))
+}
+
+async fn login(
+    State(server): State<GameServer>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(credentials): Json<Credentials>,
+) -> ApiResult<LoginResult> {
+    check_account_throttle(&server, addr)?;
+    let (account, token) = server.accounts.login(&credentials.username, &credentials.password).await
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e))?;
+    Ok(Json(LoginResult { username: account.username, token }))
+}
+
+async fn get_account(State(server): State<GameServer>, Path(username): Path<String>) -> ApiResult<AccountDetails> {
+    let account = server.accounts.find(&username).await
+        .ok_or_else(|| not_found("Account"))?;
+    
+    Ok(Json(AccountDetails {
+        stats: account.stats.stats(),
+        username: account.username,
+        created_at: account.created_at,
+        rating: account.rating,
+        rated_games: account.rated_games,
+    }))
+}
+
+/// The finished games of an account, newest first. Only the games on the
+/// requested page are read.
+async fn get_account_games(
+    State(server): State<GameServer>,
+    Path(username): Path<String>,
+    Query(page): Query<Page>,
+) -> ApiResult<Vec<HistoryEntry>> {
+    let limit = page.limit.unwrap_or(HISTORY_PAGE);
+    if limit > HISTORY_PAGE_LIMIT {
+        return Err((StatusCode::BAD_REQUEST, format!("limit must be at most {}", HISTORY_PAGE_LIMIT)));
+    }
+    let account = server.accounts.find(&username).await
+        .ok_or_else(|| not_found("Account"))?;
+    
+    let mut history = Vec::new();
+    for game_id in account.game_ids.iter().rev().skip(page.offset).take(limit) {
+        // Games whose record was lost are left out
+        let Some(record) = server.storage.load_game(*game_id).await else {
+            continue;
+        };
+        let Some(player_id) = record.player_for(account.id) else {
+            continue;
+        };
+        history.push(HistoryEntry {
+            game_id: record.game_id,
+            score: record.scores.get(&player_id).copied().unwrap_or(0),
+            won: record.is_winner(player_id),
+            finished_at: record.finished_at,
+            replay: format!("/api/games/{}/replay", record.game_id),
+            city: record.city,
+        });
+    }
+    
+    Ok(Json(history))
+}
+
+/// Daily challenges are always played with the default rules.
+fn daily_rules() -> RuleSet {
+    RuleSet::default()
+}
+
+fn daily_map(server: &GameServer, challenge: &DailyChallenge) -> Result<SubwayMap, (StatusCode, String)> {
+    server.maps.get(&challenge.city).cloned()
+        .ok_or_else(|| not_found("Map"))
+}
+
+fn daily_challenge(date: &str) -> Result<DailyChallenge, (StatusCode, String)> {
+    DailyChallenge::for_date(date).map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+async fn get_daily(State(server): State<GameServer>) -> ApiResult<DailyDetails> {
+    let challenge = DailyChallenge::today();
+    let targets = SoloTargets::for_map(&daily_map(&server, &challenge)?, &daily_rules());
+    Ok(Json(DailyDetails { challenge, targets }))
+}
+
+/// One page of a leaderboard, best score first.
+async fn get_leaderboard(
+    State(server): State<GameServer>,
+    Path(date): Path<String>,
+    Query(page): Query<Page>,
+) -> ApiResult<Vec<LeaderboardEntry>> {
+    let challenge = daily_challenge(&date)?;
+    let limit = page.limit.unwrap_or(LEADERBOARD_PAGE);
+    if limit > LEADERBOARD_PAGE_LIMIT {
+        return Err((StatusCode::BAD_REQUEST, format!("limit must be at most {}", LEADERBOARD_PAGE_LIMIT)));
+    }
+    
+    let entries = server.storage.load_daily(&challenge.date).await.into_iter()
+        .enumerate()
+        .skip(page.offset)
+        .take(limit)
+        .map(|(i, entry)| LeaderboardEntry {
+            rank: i + 1,
+            id: entry.id,
+            player_name: entry.player_name,
+            score: entry.score,
+            submitted_at: entry.submitted_at,
+        })
+        .collect();
+    
+    Ok(Json(entries))
+}
+
+/// Takes a score for the challenge of the day the game started on. The replay
+/// is played again on the server, and the score only counts if it comes out
+/// the same. The scores per day are capped for the logged in account, or for
+/// the address when sent without a login, never for the name on the score.
+async fn submit_daily_score(
+    State(server): State<GameServer>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(date): Path<String>,
+    Json(submission): Json<DailySubmission>,
+) -> ApiResult<SubmissionResult> {
+    let challenge = daily_challenge(&date)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    if !challenge.accepts_scores_at(now.as_secs(), DAILY_GRACE_SECS) {
+        return Err((StatusCode::BAD_REQUEST, "This challenge no longer takes scores".to_string()));
+    }
+    let player_name = validate_player_name(&submission.player_name)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let submitter = if headers.contains_key(AUTHORIZATION) {
+        let account = bearer_account(&server, &headers).await
+            .map_err(|_| (StatusCode::UNAUTHORIZED, "Log in again to send scores with your account".to_string()))?;
+        format!("account:{}", account.id)
+    } else {
+        format!("address:{}", addr.ip())
+    };
+    if !server.daily_throttle.try_acquire(addr.ip(), Instant::now()) {
+        return Err((StatusCode::TOO_MANY_REQUESTS, "You are sending scores too quickly".to_string()));
+    }
+    
+    let subway_map = daily_map(&server, &challenge)?;
+    let score = SoloGame::verify(subway_map, daily_rules(), challenge.seed, &submission.replay)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid replay: {}", e)))?;
+    if score != submission.score {
+        return Err((StatusCode::BAD_REQUEST, format!("The replay scores {} points, not {}", score, submission.score)));
+    }
+    
+    let entry = DailyEntry {
+        id: Uuid::new_v4(),
+        player_name,
+        score,
+        submitted_at: now.as_millis() as u64,
+        replay: submission.replay,
+        submitter,
+    };
+    let id = entry.id;
+    let rank = server.storage.add_daily_entry(&challenge.date, entry, DAILY_ENTRIES_PER_SUBMITTER).await
+        .map_err(|e| {
+            log::error!("Could not save a score for {}: {}", challenge.date, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Could not save the score".to_string())
+        })?
+        .ok_or_else(|| (StatusCode::TOO_MANY_REQUESTS, format!("Only {} scores per player and day count", DAILY_ENTRIES_PER_SUBMITTER)))?;
+    
+    Ok(Json(SubmissionResult { id, rank }))
+}
+
+async fn get_daily_replay(State(server): State<GameServer>, Path((date, id)): Path<(String, Uuid)>) -> ApiResult<Replay> {
+    let challenge = daily_challenge(&date)?;
+    
+    server.storage.load_daily(&challenge.date).await.into_iter()
+        .find(|entry| entry.id == id)
+        .map(|entry| Json(entry.replay))
+        .ok_or_else(|| not_found("Score"))
+}
+
+async fn list_tournaments(State(server): State<GameServer>) -> Json<Vec<TournamentSummary>> {
+    let tournaments = server.storage.list_tournaments().await.into_iter()
+        .map(|tournament| TournamentSummary {
+            id: tournament.id,
+            players: tournament.players.len(),
+            rounds_started: tournament.rounds.len(),
+            rounds: tournament.cities.len(),
+            finished: tournament.is_finished(),
+            created_at: tournament.created_at,
+            name: tournament.name,
+        })
+        .collect();
+    
+    Json(tournaments)
+}
+
+/// The account logged in with the token sent as `Authorization: Bearer <token>`.
+async fn bearer_account(server: &GameServer, headers: &HeaderMap) -> Result<Account, (StatusCode, String)> {
+    let unauthorized = || (StatusCode::UNAUTHORIZED, "Log in to manage tournaments".to_string());
+    let token = headers.get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| Uuid::parse_str(token.trim()).ok())
+        .ok_or_else(unauthorized)?;
+    
+    server.accounts.authenticate(token).await.ok_or_else(unauthorized)
+}
+
+/// Loads a tournament for a change only its organizer may make. Call with the
+/// guard of `lock_tournaments` held.
+async fn organized_tournament(server: &GameServer, headers: &HeaderMap, id: Uuid) -> Result<Tournament, (StatusCode, String)> {
+    let account = bearer_account(server, headers).await?;
+    let tournament = server.storage.load_tournament(id).await
+        .ok_or_else(|| not_found("Tournament"))?;
+    if tournament.organizer != account.id {
+        return Err((StatusCode::FORBIDDEN, "Only the organizer can change this tournament".to_string()));
+    }
+    Ok(tournament)
+}
+
+async fn save_tournament(server: &GameServer, tournament: &Tournament) -> Result<(), (StatusCode, String)> {
+    server.storage.save_tournament(tournament).await.map_err(|e| {
+        log::error!("Could not save tournament {}: {}", tournament.id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Could not save the tournament".to_string())
+    })
+}
+
+/// Creates a tournament organized by the logged in account. Every player needs
+/// an account too, their seats are kept for it.
+async fn create_tournament(
+    State(server): State<GameServer>,
+    headers: HeaderMap,
+    Json(mut settings): Json<TournamentSettings>,
+) -> ApiResult<Tournament> {
+    let organizer = bearer_account(&server, &headers).await?;
+    if settings.table_size > MAX_PLAYERS_LIMIT {
+        return Err((StatusCode::BAD_REQUEST, format!("Tables can seat at most {} players", MAX_PLAYERS_LIMIT)));
+    }
+    
+    let mut accounts = Vec::new();
+    for player in &mut settings.players {
+        let account = server.accounts.find(player).await
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("{} has no account", player.trim())))?;
+        *player = account.username;
+        accounts.push(account.id);
+    }
+    
+    let seed = Uuid::new_v4().as_u64_pair().0;
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    let tournament = Tournament::new(settings, organizer.id, accounts, seed, created_at)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    
+    let _guard = server.storage.lock_tournaments().await;
+    save_tournament(&server, &tournament).await?;
+    Ok(Json(tournament))
+}
+
+async fn get_tournament(State(server): State<GameServer>, Path(id): Path<Uuid>) -> ApiResult<TournamentDetails> {
+    let tournament = server.storage.load_tournament(id).await
+        .ok_or_else(|| not_found("Tournament"))?;
+    
+    Ok(Json(TournamentDetails {
+        finished: tournament.is_finished(),
+        standings: tournament.standings(),
+        tournament,
+    }))
+}
+
+/// Opens the rooms of tournament tables and returns their game ids, or fails
+/// without opening any if the server can't hold them all. Only a table's
+/// players can join, logged in to their accounts.
+async fn open_tables(server: &GameServer, tournament: &Tournament, city: &City, seating: &[Vec<String>]) -> Result<Vec<Uuid>, (StatusCode, String)> {
+    let options = seating.iter()
+        .map(|players| RoomOptions { max_players: Some(players.len()), ..RoomOptions::for_city(city.clone()) })
+        .collect();
+    let sessions = server.try_create_sessions(options).await.ok_or_else(rooms_full)?;
+    
+    let mut game_ids = Vec::new();
+    for (session, players) in sessions.iter().zip(seating) {
+        let mut session = session.lock().await;
+        let seats = players.iter()
+            .filter_map(|player| Some((tournament.account_of(player)?, player.clone())))
+            .collect();
+        session.reserve_for_tournament(tournament.id, seats);
+        game_ids.push(session.game_state.id);
+    }
+    Ok(game_ids)
+}
+
+/// Seats the players for the next round and opens a room for every table.
+async fn start_tournament_round(State(server): State<GameServer>, headers: HeaderMap, Path(id): Path<Uuid>) -> ApiResult<TournamentRound> {
+    let _guard = server.storage.lock_tournaments().await;
+    let mut tournament = organized_tournament(&server, &headers, id).await?;
+    let RoundSeating { city, tables: seating, bye } = tournament.next_round()
+        .map_err(|e| (StatusCode::CONFLICT, e))?;
+    
+    let game_ids = open_tables(&server, &tournament, &city, &seating).await?;
+    let tables = game_ids.into_iter().zip(seating)
+        .map(|(game_id, players)| TournamentTable { game_id, players, results: None })
+        .collect();
+    
+    let round = TournamentRound { city, tables, bye };
+    tournament.rounds.push(round.clone());
+    save_tournament(&server, &tournament).await?;
+    
+    Ok(Json(round))
+}
+
+fn find_table(tournament: &Tournament, game_id: Uuid) -> Option<(City, TournamentTable)> {
+    tournament.rounds.iter()
+        .find_map(|round| Some((round.city.clone(), round.tables.iter().find(|table| table.game_id == game_id)?.clone())))
+}
+
+/// Settles a table whose game can't be played to the end, like when players
+/// don't show up, and closes its room.
+async fn forfeit_tournament_table(
+    State(server): State<GameServer>,
+    headers: HeaderMap,
+    Path((id, game_id)): Path<(Uuid, Uuid)>,
+    Json(forfeit): Json<Forfeit>,
+) -> ApiResult<TournamentTable> {
+    let _guard = server.storage.lock_tournaments().await;
+    let mut tournament = organized_tournament(&server, &headers, id).await?;
+    tournament.forfeit_table(game_id, &forfeit.players)
+        .map_err(|e| (StatusCode::CONFLICT, e))?;
+    save_tournament(&server, &tournament).await?;
+    
+    server.remove_sessions(&[game_id]).await;
+    let (_, table) = find_table(&tournament, game_id).ok_or_else(|| not_found("Table"))?;
+    Ok(Json(table))
+}
+
+/// Seats a table's players at a new game, for when theirs was lost to a server
+/// restart or started wrong. The old room is closed and its game won't count.
+async fn reopen_tournament_table(
+    State(server): State<GameServer>,
+    headers: HeaderMap,
+    Path((id, game_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<TournamentTable> {
+    let _guard = server.storage.lock_tournaments().await;
+    let mut tournament = organized_tournament(&server, &headers, id).await?;
+    let (city, table) = find_table(&tournament, game_id)
+        .filter(|(_, table)| table.results.is_none())
+        .ok_or_else(|| not_found("Table"))?;
+    
+    let game_ids = open_tables(&server, &tournament, &city, std::slice::from_ref(&table.players)).await?;
+    let new_game_id = game_ids[0];
+    tournament.replace_game(game_id, new_game_id);
+    save_tournament(&server, &tournament).await?;
+    
+    server.remove_sessions(&[game_id]).await;
+    Ok(Json(TournamentTable { game_id: new_game_id, ..table }))
+}
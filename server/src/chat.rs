@@ -0,0 +1,44 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+pub const MAX_CHAT_LENGTH: usize = 200;
+pub const CHAT_HISTORY_LENGTH: usize = 50;
+
+const RATE_LIMIT_MESSAGES: usize = 5;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Allows at most `RATE_LIMIT_MESSAGES` chat messages or reactions per
+/// connection within a sliding `RATE_LIMIT_WINDOW`.
+#[derive(Default)]
+pub struct RateLimiter {
+    sent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        while self.sent.front().is_some_and(|sent| now.duration_since(*sent) >= RATE_LIMIT_WINDOW) {
+            self.sent.pop_front();
+        }
+        
+        if self.sent.len() >= RATE_LIMIT_MESSAGES {
+            return false;
+        }
+        
+        self.sent.push_back(now);
+        true
+    }
+}
+
+/// Trims a chat message and checks it against the length limit.
+pub fn clean_chat_text(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    
+    if text.is_empty() {
+        return Err("Chat message is empty".to_string());
+    }
+    if text.chars().count() > MAX_CHAT_LENGTH {
+        return Err(format!("Chat messages are limited to {} characters", MAX_CHAT_LENGTH));
+    }
+    
+    Ok(text.to_string())
+}
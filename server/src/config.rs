@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::Parser;
+use log::LevelFilter;
+use serde::{Deserialize, Deserializer};
+
+use verplant::{City, RulePreset, SubwayMap};
+
+use crate::RoundTimer;
+
+pub const MAX_PLAYERS_LIMIT: usize = 32;
+
+/// Command line flags. Every setting can also be given through an environment
+/// variable or the TOML config file; flags win over the environment, which wins
+/// over the file.
+#[derive(Debug, Parser)]
+#[command(name = "verplant_server", about = "Verplant subway game server")]
+struct Cli {
+    /// Path to a TOML config file
+    #[arg(short, long, env = "VERPLANT_CONFIG")]
+    config: Option<PathBuf>,
+    
+    #[command(flatten)]
+    settings: Settings,
+}
+
+#[derive(Debug, Default, clap::Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    /// Address to listen on [default: 127.0.0.1]
+    #[arg(long, env = "VERPLANT_BIND_ADDRESS")]
+    bind_address: Option<IpAddr>,
+    
+    /// Port to listen on [default: 8080]
+    #[arg(long, env = "VERPLANT_PORT")]
+    port: Option<u16>,
+    
+    /// Directory containing the web client's `index.html` and built `pkg/` [default: client]
+    #[arg(long, env = "VERPLANT_CLIENT_DIR")]
+    client_dir: Option<PathBuf>,
+    
+    /// Directory with additional `*.json` subway maps that replace the built-in ones
+    #[arg(long, env = "VERPLANT_MAPS_DIR")]
+    maps_dir: Option<PathBuf>,
+    
+    /// Maximum number of players per room [default: 6]
+    #[arg(long, env = "VERPLANT_MAX_PLAYERS")]
+    max_players: Option<usize>,
+    
+    /// Seconds each player has per card, 0 disables the deadline [default: 60]
+    #[arg(long, env = "VERPLANT_ROUND_TIMEOUT")]
+    round_timeout_secs: Option<u64>,
+    
+    /// Seconds of chess-clock time bank per player, 0 disables it [default: 0]
+    #[arg(long, env = "VERPLANT_TIME_BANK")]
+    time_bank_secs: Option<u64>,
+    
+    /// Rule preset for rooms that don't ask for one: classic, family, quick or strict [default: classic]
+    #[arg(long, env = "VERPLANT_RULES")]
+    #[serde(deserialize_with = "preset_by_name")]
+    rules: Option<RulePreset>,
+    
+    /// Most rooms that may exist at once; the API and the matchmaker open no more [default: 200]
+    #[arg(long, env = "VERPLANT_MAX_ROOMS")]
+    max_rooms: Option<usize>,
+    
+    /// Players the matchmaking queue puts in a room together at the least [default: 2]
+    #[arg(long, env = "VERPLANT_MIN_MATCH_PLAYERS")]
+    min_match_players: Option<usize>,
+    
+    /// Seconds a player waits in the queue before getting a room with whoever is there [default: 30]
+    #[arg(long, env = "VERPLANT_MATCH_WAIT")]
+    match_wait_secs: Option<u64>,
+    
+    /// Directory where the server stores its data [default: data]
+    #[arg(long, env = "VERPLANT_PERSISTENCE_PATH")]
+    persistence_path: Option<PathBuf>,
+    
+    /// One of off, error, warn, info, debug, trace [default: info]
+    #[arg(long, env = "VERPLANT_LOG_LEVEL")]
+    log_level: Option<String>,
+}
+
+impl Settings {
+    /// Fills every unset value from `fallback`.
+    fn or(self, fallback: Settings) -> Settings {
+        Settings {
+            bind_address: self.bind_address.or(fallback.bind_address),
+            port: self.port.or(fallback.port),
+            client_dir: self.client_dir.or(fallback.client_dir),
+            maps_dir: self.maps_dir.or(fallback.maps_dir),
+            max_players: self.max_players.or(fallback.max_players),
+            round_timeout_secs: self.round_timeout_secs.or(fallback.round_timeout_secs),
+            time_bank_secs: self.time_bank_secs.or(fallback.time_bank_secs),
+            rules: self.rules.or(fallback.rules),
+            max_rooms: self.max_rooms.or(fallback.max_rooms),
+            min_match_players: self.min_match_players.or(fallback.min_match_players),
+            match_wait_secs: self.match_wait_secs.or(fallback.match_wait_secs),
+            persistence_path: self.persistence_path.or(fallback.persistence_path),
+            log_level: self.log_level.or(fallback.log_level),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind_address: IpAddr,
+    pub port: u16,
+    pub client_dir: PathBuf,
+    pub maps_dir: Option<PathBuf>,
+    pub max_players: usize,
+    pub round_timeout: Option<Duration>,
+    pub time_bank: Option<Duration>,
+    pub rules: RulePreset,
+    pub max_rooms: usize,
+    pub min_match_players: usize,
+    pub match_wait: Duration,
+    pub persistence_path: PathBuf,
+    pub log_level: LevelFilter,
+}
+
+impl Config {
+    /// Reads the command line, the environment and the config file.
+    pub fn load() -> Result<Config, String> {
+        Config::from_cli(Cli::parse())
+    }
+    
+    /// Like `load`, with `args` instead of the process's command line.
+    pub fn load_from<I, T>(args: I) -> Result<Config, String>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let cli = Cli::try_parse_from(args).map_err(|e| e.to_string())?;
+        Config::from_cli(cli)
+    }
+    
+    fn from_cli(cli: Cli) -> Result<Config, String> {
+        let file_settings = match &cli.config {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Could not read config file {}: {}", path.display(), e))?;
+                toml::from_str(&contents)
+                    .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?
+            },
+            None => Settings::default(),
+        };
+        
+        Config::from_settings(cli.settings.or(file_settings))
+    }
+    
+    fn from_settings(settings: Settings) -> Result<Config, String> {
+        let max_players = settings.max_players.unwrap_or(6);
+        if !(1..=MAX_PLAYERS_LIMIT).contains(&max_players) {
+            return Err(format!("max_players must be between 1 and {}", MAX_PLAYERS_LIMIT));
+        }
+        
+        let max_rooms = settings.max_rooms.unwrap_or(200);
+        if max_rooms == 0 {
+            return Err("max_rooms must be at least 1".to_string());
+        }
+        
+        let min_match_players = settings.min_match_players.unwrap_or(2.min(max_players));
+        if min_match_players == 0 {
+            return Err("min_match_players must be at least 1".to_string());
+        }
+        if min_match_players > max_players {
+            return Err(format!("min_match_players can't be more than max_players ({})", max_players));
+        }
+        
+        if let Some(maps_dir) = &settings.maps_dir
+            && !maps_dir.is_dir()
+        {
+            return Err(format!("Maps directory {} does not exist", maps_dir.display()));
+        }
+        
+        let log_level = settings.log_level.as_deref().unwrap_or("info");
+        let log_level = log_level.parse::<LevelFilter>()
+            .map_err(|_| format!("Unknown log level {}", log_level))?;
+        
+        Ok(Config {
+            bind_address: settings.bind_address.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            port: settings.port.unwrap_or(8080),
+            client_dir: settings.client_dir.unwrap_or_else(|| PathBuf::from("client")),
+            maps_dir: settings.maps_dir,
+            max_players,
+            round_timeout: seconds_or_disabled(settings.round_timeout_secs.unwrap_or(60)),
+            time_bank: seconds_or_disabled(settings.time_bank_secs.unwrap_or(0)),
+            rules: settings.rules.unwrap_or_default(),
+            max_rooms,
+            min_match_players,
+            match_wait: Duration::from_secs(settings.match_wait_secs.unwrap_or(30)),
+            persistence_path: settings.persistence_path.unwrap_or_else(|| PathBuf::from("data")),
+            log_level,
+        })
+    }
+    
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+    
+    pub fn round_timer(&self) -> RoundTimer {
+        RoundTimer {
+            round_timeout: self.round_timeout,
+            time_bank: self.time_bank,
+        }
+    }
+}
+
+/// Reads a preset name from the config file the way the flag parses it, so
+/// case doesn't matter in either.
+fn preset_by_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<RulePreset>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+fn seconds_or_disabled(seconds: u64) -> Option<Duration> {
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+/// Loads the built-in map of every city and replaces them with the `*.json`
+/// maps found in `maps_dir`.
+pub fn load_maps(maps_dir: Option<&Path>) -> Result<HashMap<City, SubwayMap>, String> {
+    let mut maps: HashMap<City, SubwayMap> = City::ALL.iter()
+        .map(|city| (city.clone(), SubwayMap::builtin(city)))
+        .collect();
+    
+    let Some(maps_dir) = maps_dir else {
+        return Ok(maps);
+    };
+    
+    let entries = std::fs::read_dir(maps_dir)
+        .map_err(|e| format!("Could not read maps directory {}: {}", maps_dir.display(), e))?;
+    
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read map {}: {}", path.display(), e))?;
+        let subway_map: SubwayMap = serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid map {}: {}", path.display(), e))?;
+        subway_map.validate()
+            .map_err(|e| format!("Invalid map {}: {}", path.display(), e))?;
+        
+        log::info!("Loaded {:?} map from {}", subway_map.city, path.display());
+        maps.insert(subway_map.city.clone(), subway_map);
+    }
+    
+    Ok(maps)
+}
@@ -0,0 +1,31 @@
+use std::net::SocketAddr;
+use std::path::Path;
+
+use axum::Router;
+use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
+use axum::response::Response;
+use axum::routing::get;
+use tower_http::services::{ServeDir, ServeFile};
+
+use crate::{GameServer, api};
+
+/// Serves the web client from `client_dir`, the game WebSocket on `/ws` and the
+/// REST API below `/api`.
+pub fn router(server: GameServer, client_dir: &Path) -> Router {
+    Router::new()
+        .route("/ws", get(websocket_handler))
+        .nest("/api", api::router())
+        .route_service("/", ServeFile::new(client_dir.join("index.html")))
+        .nest_service("/pkg", ServeDir::new(client_dir.join("pkg")))
+        .with_state(server)
+}
+
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(server): State<GameServer>,
+) -> Response {
+    ws.on_upgrade(move |socket| async move {
+        server.handle_connection(socket, addr).await;
+    })
+}
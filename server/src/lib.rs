@@ -0,0 +1,1305 @@
+//! The Verplant game server: game sessions behind a WebSocket, the REST API
+//! and the web client, all served over one HTTP listener.
+
+pub mod accounts;
+mod api;
+pub mod chat;
+pub mod config;
+pub mod http;
+pub mod matchmaking;
+pub mod storage;
+pub mod throttle;
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use axum::extract::ws::{Message, WebSocket};
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::{Mutex, RwLock, mpsc};
+use uuid::Uuid;
+
+use verplant::{
+    Card, ChatEntry, City, ClientRequest, Emote, GameMessage, GamePhase, GameRecord, GameState, GameStateView, MatchSettings, PROTOCOL_VERSION,
+    PlayerAction, ReactionTarget, Replay, RequestId, RuleSet, SubwayMap, TableResult, WireFormat, validate_player_name, wire,
+};
+
+use crate::accounts::{Account, Accounts};
+use crate::api::RoomOptions;
+use crate::chat::{CHAT_HISTORY_LENGTH, RateLimiter, clean_chat_text};
+use crate::config::Config;
+use crate::matchmaking::{Matchmaker, RATING_WINDOW, SeatSlot, Ticket, WINDOW_GROWTH};
+use crate::storage::Storage;
+use crate::throttle::IpThrottle;
+
+/// The sending half of a client's socket, encoding messages in the format the
+/// client asked for in `Hello`. Frames are queued for a writer task, so a slow
+/// client never holds up the session sending to it.
+pub struct WebSocketSender {
+    frames: mpsc::UnboundedSender<Message>,
+    wire_format: WireFormat,
+}
+
+impl WebSocketSender {
+    /// Speaks JSON until the handshake picks another format.
+    pub fn new(frames: mpsc::UnboundedSender<Message>) -> Self {
+        Self { frames, wire_format: WireFormat::Json }
+    }
+    
+    fn send(&mut self, frame: Message) {
+        // Fails only once the connection is gone, which its handler notices
+        let _ = self.frames.send(frame);
+    }
+}
+
+fn encode_frame(message: &GameMessage, wire_format: WireFormat) -> Message {
+    match wire_format {
+        WireFormat::Json => Message::Text(serde_json::to_string(message).unwrap().into()),
+        WireFormat::MessagePack => Message::Binary(wire::encode(message, wire_format).unwrap().into()),
+    }
+}
+
+/// Text frames are JSON and binary frames MessagePack, whatever was negotiated.
+fn decode_frame(frame: &Message) -> Result<ClientRequest, String> {
+    match frame {
+        Message::Text(text) => wire::decode(text.as_bytes(), WireFormat::Json),
+        Message::Binary(bytes) => wire::decode(bytes, WireFormat::MessagePack),
+        _ => Err("Not a data frame".to_string()),
+    }
+}
+
+const TIMER_RESOLUTION: Duration = Duration::from_millis(250);
+
+/// How many answered action requests are kept per player to recognise retries.
+const ANSWERED_REQUESTS_LENGTH: usize = 32;
+
+/// Protocol versions this server can speak, see `Hello`. Older clients don't
+/// know the game phase, `DeckReshuffled`, the expansion cards, accounts or
+/// the matchmaking queue.
+const SUPPORTED_PROTOCOL_VERSIONS: &[u32] = &[PROTOCOL_VERSION];
+
+/// Announced in `Welcome` so clients can hide features the server lacks.
+const SERVER_CAPABILITIES: &[&str] = &["state-deltas", "checksums", "spectators", "chat", "rejoin", "accounts", "matchmaking"];
+
+/// How often the matchmaking queue is checked for players who waited long enough.
+const MATCHMAKING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often sessions are checked for being idle, and how long they may be
+/// before they are removed. Tournament tables get longer, players may be late.
+const REAPER_INTERVAL: Duration = Duration::from_secs(60);
+pub const IDLE_SESSION_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+pub const IDLE_TABLE_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Rooms each IP address may create through the API per `ROOM_CREATION_WINDOW`.
+const ROOM_CREATION_LIMIT: usize = 10;
+const ROOM_CREATION_WINDOW: Duration = Duration::from_secs(60);
+
+/// Daily challenge scores each IP address may send in per `DAILY_SCORE_WINDOW`.
+/// Every one costs a full replay of the game.
+const DAILY_SCORE_LIMIT: usize = 10;
+const DAILY_SCORE_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Registrations and logins each IP address may try per `ACCOUNT_ATTEMPT_WINDOW`,
+/// so passwords can't be guessed quickly.
+const ACCOUNT_ATTEMPT_LIMIT: usize = 10;
+const ACCOUNT_ATTEMPT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Deadline settings for a session. `round_timeout` is the time every player gets
+/// for each card; `time_bank` enables a chess-clock style reserve that is drawn
+/// from once a player exceeds the round timeout.
+#[derive(Debug, Clone, Copy)]
+pub struct RoundTimer {
+    pub round_timeout: Option<Duration>,
+    pub time_bank: Option<Duration>,
+}
+
+pub struct PlayerConnection {
+    pub id: Uuid,
+    pub sender: Arc<Mutex<WebSocketSender>>,
+    pub rejoin_token: Uuid,
+}
+
+/// A connection that receives every broadcast of a session but has no sheet.
+struct SpectatorConnection {
+    sender: Arc<Mutex<WebSocketSender>>,
+}
+
+/// Tells a client it can't be served with `HelloRejected` and closes the connection.
+async fn reject_client(sender: &Mutex<WebSocketSender>, reason: String) {
+    send_message(sender, &GameMessage::HelloRejected {
+        reason,
+        supported_versions: SUPPORTED_PROTOCOL_VERSIONS.to_vec(),
+    }).await;
+    sender.lock().await.send(Message::Close(None));
+}
+
+async fn send_message(sender: &Mutex<WebSocketSender>, message: &GameMessage) {
+    let mut sender = sender.lock().await;
+    let frame = encode_frame(message, sender.wire_format);
+    sender.send(frame);
+}
+
+/// Plays a connection's move in the game it sits in. A connection without a
+/// seat still gets its request id back, in an error.
+pub async fn play_action(
+    sender: &Mutex<WebSocketSender>,
+    seat: Option<(Uuid, &Arc<Mutex<GameSession>>)>,
+    request_id: Option<RequestId>,
+    action: PlayerAction,
+) {
+    match seat {
+        Some((player_id, session)) => session.lock().await.handle_player_action(player_id, request_id, action).await,
+        None => send_message(sender, &GameMessage::Error { request_id, message: "Not in a game".to_string() }).await,
+    }
+}
+
+pub struct GameSession {
+    pub game_state: GameState,
+    players: HashMap<Uuid, PlayerConnection>,
+    accounts: HashMap<Uuid, Uuid>, // Account of each logged in player
+    spectators: HashMap<Uuid, SpectatorConnection>,
+    subway_map: SubwayMap,
+    max_players: usize,
+    timer: RoundTimer,
+    pub round_started: Option<Instant>,
+    time_banks: HashMap<Uuid, Duration>,
+    replay: Replay,
+    chat_history: VecDeque<ChatEntry>,
+    spectator_chat: bool,
+    storage: Arc<Storage>,
+    account_store: Arc<Accounts>,
+    tournament: Option<Uuid>, // Tournament this game is a table of
+    reserved_seats: Vec<(Uuid, String)>, // Account and name of the only players who may join, unless empty
+    state_version: u64,
+    synced_view: GameStateView, // The public view as of `state_version`
+    answered_requests: HashMap<Uuid, VecDeque<(RequestId, Vec<GameMessage>)>>, // Replies to each player's latest actions
+    idle_since: Option<Instant>, // As of the last `idle_time`
+}
+
+impl GameSession {
+    pub fn new(
+        subway_map: SubwayMap,
+        rules: RuleSet,
+        max_players: usize,
+        timer: RoundTimer,
+        spectator_chat: bool,
+        storage: Arc<Storage>,
+        account_store: Arc<Accounts>,
+    ) -> Self {
+        // The first player to join becomes the conductor
+        let game_state = GameState::with_rules(subway_map.city.clone(), Uuid::nil(), rules);
+        let synced_view = game_state.public_view(&subway_map);
+        
+        Self {
+            game_state,
+            players: HashMap::new(),
+            accounts: HashMap::new(),
+            spectators: HashMap::new(),
+            subway_map,
+            max_players,
+            timer,
+            round_started: None,
+            time_banks: HashMap::new(),
+            replay: Replay::default(),
+            chat_history: VecDeque::new(),
+            spectator_chat,
+            storage,
+            account_store,
+            tournament: None,
+            reserved_seats: Vec::new(),
+            state_version: 0,
+            synced_view,
+            answered_requests: HashMap::new(),
+            idle_since: None,
+        }
+    }
+    
+    fn has_started(&self) -> bool {
+        self.game_state.phase != GamePhase::Lobby
+    }
+    
+    fn is_joinable(&self) -> bool {
+        !self.has_started() && self.players.len() < self.max_players
+    }
+    
+    /// A session is idle once its game is over or nobody is connected to it.
+    fn is_idle(&self) -> bool {
+        if self.game_state.is_finished() {
+            return true;
+        }
+        let connected = !self.spectators.is_empty() || self.game_state.roster.iter().any(|info| info.connected);
+        !connected
+    }
+    
+    /// How long the session may be idle before it is removed.
+    pub fn idle_timeout(&self) -> Duration {
+        match self.tournament {
+            Some(_) => IDLE_TABLE_TIMEOUT,
+            None => IDLE_SESSION_TIMEOUT,
+        }
+    }
+    
+    /// The tournament and seats of a table nobody finished, which has to be
+    /// forfeited when its room is removed.
+    fn unfinished_table(&self) -> Option<(Uuid, Vec<String>)> {
+        let tournament_id = self.tournament.filter(|_| !self.game_state.is_finished())?;
+        Some((tournament_id, self.reserved_seats.iter().map(|(_, name)| name.clone()).collect()))
+    }
+    
+    /// How long the session has been idle, as far as calls to this tell.
+    pub fn idle_time(&mut self, now: Instant) -> Duration {
+        if !self.is_idle() {
+            self.idle_since = None;
+            return Duration::ZERO;
+        }
+        now.saturating_duration_since(*self.idle_since.get_or_insert(now))
+    }
+    
+    /// Makes the game a table of a tournament. Only the accounts of `seats`
+    /// may join, each under its tournament name, and the game can't start
+    /// before all of them did.
+    pub fn reserve_for_tournament(&mut self, tournament_id: Uuid, seats: Vec<(Uuid, String)>) {
+        self.tournament = Some(tournament_id);
+        self.reserved_seats = seats;
+    }
+    
+    pub async fn add_player(&mut self, player: PlayerConnection, name: String, account: Option<&Account>) -> Result<(), String> {
+        if !self.is_joinable() {
+            return Err("Game is full or already started".to_string());
+        }
+        let name = if self.reserved_seats.is_empty() {
+            name
+        } else {
+            let seat = account.and_then(|account| self.reserved_seats.iter().find(|(account_id, _)| *account_id == account.id));
+            let Some((_, seat_name)) = seat else {
+                let names: Vec<&str> = self.reserved_seats.iter().map(|(_, name)| name.as_str()).collect();
+                return Err(format!("This table is reserved for {}, log in as one of them to join", names.join(", ")));
+            };
+            seat_name.clone()
+        };
+        if self.game_state.is_name_taken(&name) {
+            return Err(format!("The name {} is already taken in this game", name));
+        }
+        if let Some(account) = account
+            && self.accounts.values().any(|id| *id == account.id)
+        {
+            return Err(format!("{} is already playing in this game", account.username));
+        }
+        
+        if let Some(account) = account {
+            self.accounts.insert(player.id, account.id);
+        }
+        if self.game_state.players.is_empty() {
+            self.game_state.conductor = player.id;
+        }
+        self.game_state.add_player(player.id, name, &self.subway_map);
+        if let Some(time_bank) = self.timer.time_bank {
+            self.time_banks.insert(player.id, time_bank);
+        }
+        self.players.insert(player.id, player);
+        
+        self.broadcast_message(&GameMessage::RosterUpdated(self.game_state.roster.clone())).await;
+        self.sync_state().await;
+        Ok(())
+    }
+    
+    /// Hands a player's seat to a new connection after they lost the old one.
+    pub async fn rejoin_player(&mut self, player_id: Uuid, token: Uuid, sender: Arc<Mutex<WebSocketSender>>) -> Result<(), String> {
+        let player = self.players.get_mut(&player_id)
+            .filter(|player| player.rejoin_token == token)
+            .ok_or("Unknown player or invalid rejoin token")?;
+        
+        player.sender = sender;
+        self.game_state.set_connected(&player_id, true);
+        self.broadcast_message(&GameMessage::RosterUpdated(self.game_state.roster.clone())).await;
+        self.sync_state().await;
+        Ok(())
+    }
+    
+    async fn disconnect_player(&mut self, player_id: Uuid, sender: &Arc<Mutex<WebSocketSender>>) {
+        // The player may already have rejoined on another connection
+        let is_current = self.players.get(&player_id)
+            .is_some_and(|player| Arc::ptr_eq(&player.sender, sender));
+        
+        if is_current {
+            self.game_state.set_connected(&player_id, false);
+            self.broadcast_message(&GameMessage::RosterUpdated(self.game_state.roster.clone())).await;
+            self.sync_state().await;
+        }
+    }
+    
+    /// Everything a player needs after joining or rejoining the session.
+    fn welcome_messages(&self, player_id: Uuid) -> Vec<GameMessage> {
+        let mut messages = Vec::new();
+        
+        if let Some(player) = self.players.get(&player_id) {
+            messages.push(GameMessage::GameJoined {
+                player_id,
+                game_id: self.game_state.id,
+                subway_map: self.subway_map.clone(),
+                roster: self.game_state.roster.clone(),
+                rejoin_token: player.rejoin_token,
+            });
+        }
+        messages.push(self.snapshot(Some(player_id)));
+        messages.push(self.chat_history());
+        
+        messages
+    }
+    
+    async fn add_spectator(&mut self, spectator_id: Uuid, spectator: SpectatorConnection) {
+        self.spectators.insert(spectator_id, spectator);
+        self.broadcast_message(&GameMessage::SpectatorsChanged { count: self.spectators.len() }).await;
+    }
+    
+    async fn remove_spectator(&mut self, spectator_id: Uuid) {
+        if self.spectators.remove(&spectator_id).is_some() {
+            self.broadcast_message(&GameMessage::SpectatorsChanged { count: self.spectators.len() }).await;
+        }
+    }
+    
+    fn check_can_chat(&self, sender: &Uuid) -> Result<bool, String> {
+        let from_spectator = self.spectators.contains_key(sender);
+        if from_spectator && !self.spectator_chat {
+            return Err("Spectators cannot chat in this room".to_string());
+        }
+        Ok(from_spectator)
+    }
+    
+    pub async fn post_chat(&mut self, sender: Uuid, text: &str) -> Result<(), String> {
+        let from_spectator = self.check_can_chat(&sender)?;
+        
+        let entry = ChatEntry {
+            sender,
+            text: clean_chat_text(text)?,
+            sent_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+            from_spectator,
+        };
+        
+        self.chat_history.push_back(entry.clone());
+        if self.chat_history.len() > CHAT_HISTORY_LENGTH {
+            self.chat_history.pop_front();
+        }
+        
+        self.broadcast_message(&GameMessage::Chat(entry)).await;
+        Ok(())
+    }
+    
+    /// Reactions may only point at players of this game and lines they completed.
+    fn check_reaction_target(&self, target: &ReactionTarget) -> Result<(), String> {
+        let exists = match target {
+            ReactionTarget::Player(player_id) => self.game_state.players.contains_key(player_id),
+            ReactionTarget::LineCompleted { player_id, line_id } => self.game_state.players.get(player_id)
+                .is_some_and(|sheet| sheet.completed_lines.contains(line_id)),
+        };
+        if !exists {
+            return Err("There is nothing like that to react to".to_string());
+        }
+        Ok(())
+    }
+    
+    pub async fn react(&mut self, sender: Uuid, emote: Emote, target: Option<ReactionTarget>) -> Result<(), String> {
+        self.check_can_chat(&sender)?;
+        if let Some(target) = &target {
+            self.check_reaction_target(target)?;
+        }
+        self.broadcast_message(&GameMessage::Reaction { sender, emote, target }).await;
+        Ok(())
+    }
+    
+    pub fn chat_history(&self) -> GameMessage {
+        GameMessage::ChatHistory(self.chat_history.iter().cloned().collect())
+    }
+    
+    async fn broadcast_message(&self, message: &GameMessage) {
+        // Encode once per format in use rather than once per client
+        let mut frames: HashMap<WireFormat, Message> = HashMap::new();
+        
+        let senders = self.players.values().map(|player| &player.sender)
+            .chain(self.spectators.values().map(|spectator| &spectator.sender));
+        
+        for sender in senders {
+            let mut sender = sender.lock().await;
+            let frame = frames.entry(sender.wire_format)
+                .or_insert_with(|| encode_frame(message, sender.wire_format))
+                .clone();
+            sender.send(frame);
+        }
+    }
+    
+    /// The last synced state for one player, or for spectators if `viewer` is None.
+    /// The full `GameState` never leaves the server.
+    fn snapshot(&self, viewer: Option<Uuid>) -> GameMessage {
+        GameMessage::StateSnapshot {
+            version: self.state_version,
+            checksum: self.synced_view.checksum(),
+            state: GameStateView {
+                viewer,
+                ..self.synced_view.clone()
+            },
+        }
+    }
+    
+    /// Broadcasts what changed since the last sync as one versioned delta. Every
+    /// client gets the same patches; only snapshots are made per viewer.
+    async fn sync_state(&mut self) {
+        let view = self.game_state.public_view(&self.subway_map);
+        let patches = self.synced_view.diff(&view);
+        if patches.is_empty() {
+            return;
+        }
+        
+        self.state_version += 1;
+        let checksum = view.checksum();
+        self.synced_view = view;
+        self.broadcast_message(&GameMessage::StateDelta { version: self.state_version, checksum, patches }).await;
+    }
+    
+    async fn send_to_player(&self, player_id: Uuid, message: &GameMessage) {
+        if let Some(player) = self.players.get(&player_id) {
+            send_message(&player.sender, message).await;
+        }
+    }
+    
+    /// Applies a player's move. A request id whose move was already played gets
+    /// the same replies again instead of playing the move twice.
+    pub async fn handle_player_action(&mut self, player_id: Uuid, request_id: Option<RequestId>, action: PlayerAction) {
+        if let Some(request_id) = request_id
+            && let Some(replies) = self.answered_request(player_id, request_id)
+        {
+            for reply in &replies {
+                self.send_to_player(player_id, reply).await;
+            }
+            return;
+        }
+        
+        let acted_before = self.game_state.has_acted(&player_id);
+        let recorded_action = action.clone();
+        let result = self.game_state.process_player_action(player_id, action, &self.subway_map);
+        
+        let mut replies = Vec::new();
+        match &result {
+            Ok(messages) => {
+                for message in messages {
+                    match message {
+                        GameMessage::PlayerActionResult { success, message, .. } => {
+                            replies.push(GameMessage::PlayerActionResult { request_id, success: *success, message: message.clone() });
+                        },
+                        _ => replies.push(message.clone()),
+                    }
+                }
+            },
+            Err(error) => replies.push(GameMessage::Error { request_id, message: error.clone() }),
+        }
+        
+        for reply in &replies {
+            self.send_to_player(player_id, reply).await;
+        }
+        // A failed move changed nothing, so a retry may well succeed
+        if let Some(request_id) = request_id
+            && result.is_ok()
+        {
+            let answered = self.answered_requests.entry(player_id).or_default();
+            answered.push_back((request_id, replies));
+            if answered.len() > ANSWERED_REQUESTS_LENGTH {
+                answered.pop_front();
+            }
+        }
+        
+        if result.is_ok() {
+            if !matches!(recorded_action, PlayerAction::CompleteLineAnnouncement { .. }) {
+                self.replay.record(player_id, Some(recorded_action));
+            }
+            if !acted_before && self.game_state.has_acted(&player_id) {
+                self.charge_time_bank(player_id, Instant::now());
+            }
+            
+            self.sync_state().await;
+            if self.game_state.all_players_acted() {
+                self.advance_round().await;
+            }
+        }
+    }
+    
+    fn answered_request(&self, player_id: Uuid, request_id: RequestId) -> Option<Vec<GameMessage>> {
+        self.answered_requests.get(&player_id)?.iter()
+            .find(|(answered_id, _)| *answered_id == request_id)
+            .map(|(_, replies)| replies.clone())
+    }
+    
+    pub async fn start_game(&mut self) -> Result<(), String> {
+        if self.players.len() < self.reserved_seats.len() {
+            return Err("Every player of this table has to join before the game starts".to_string());
+        }
+        let card = self.game_state.start_game()?;
+        self.begin_round(card).await;
+        Ok(())
+    }
+    
+    async fn start_new_round(&mut self) {
+        match self.game_state.reveal_card() {
+            Some(card) => self.begin_round(card).await,
+            None if self.game_state.is_finished() => self.finish_game().await,
+            None => {},
+        }
+    }
+    
+    async fn begin_round(&mut self, card: Card) {
+        self.round_started = Some(Instant::now());
+        self.replay.start_round(card.clone());
+        
+        let deadline = self.timer.round_timeout.map(|timeout| {
+            let deadline = SystemTime::now() + timeout;
+            deadline.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+        });
+        let time_banks = self.time_banks.iter()
+            .map(|(player_id, bank)| (*player_id, bank.as_millis() as u64))
+            .collect();
+        
+        self.broadcast_message(&GameMessage::CardRevealed { card, deadline, time_banks }).await;
+        self.sync_state().await;
+    }
+    
+    async fn advance_round(&mut self) {
+        let messages = match self.game_state.next_round() {
+            Ok(messages) => messages,
+            Err(e) => {
+                log::warn!("Could not end round {}: {}", self.game_state.round, e);
+                return;
+            },
+        };
+        self.round_started = None;
+        
+        // Clients see the resolved points and the new deck before the announcements
+        self.sync_state().await;
+        for message in &messages {
+            self.broadcast_message(message).await;
+        }
+        
+        if self.game_state.is_finished() {
+            self.finish_game().await;
+        } else {
+            self.start_new_round().await;
+        }
+    }
+    
+    async fn finish_game(&mut self) {
+        let scores = self.game_state.calculate_final_scores(&self.subway_map);
+        let record = self.game_record(scores.clone());
+        self.save_record(&record).await;
+        self.rate_players(&scores).await;
+        self.report_to_tournament(&scores).await;
+        self.sync_state().await;
+        self.broadcast_message(&GameMessage::GameEnded { scores }).await;
+    }
+    
+    /// Updates the ratings of the logged in players. Games with fewer than two
+    /// of them leave ratings alone.
+    async fn rate_players(&self, scores: &HashMap<Uuid, i32>) {
+        let results: Vec<(Uuid, i32)> = self.accounts.iter()
+            .filter_map(|(player_id, account_id)| Some((*account_id, *scores.get(player_id)?)))
+            .collect();
+        
+        if let Err(e) = self.account_store.record_results(&results).await {
+            log::error!("Could not rate game {}: {}", self.game_state.id, e);
+        }
+    }
+    
+    async fn report_to_tournament(&self, scores: &HashMap<Uuid, i32>) {
+        let Some(tournament_id) = self.tournament else {
+            return;
+        };
+        
+        let results = self.game_state.roster.iter()
+            .filter_map(|info| Some(TableResult {
+                player_name: info.name.clone(),
+                score: *scores.get(&info.id)?,
+                completed_lines: self.game_state.players.get(&info.id)?.completed_lines.len(),
+                forfeited: false,
+            }))
+            .collect();
+        
+        if let Err(e) = self.storage.record_tournament_game(tournament_id, self.game_state.id, results).await {
+            log::error!("Could not record game {} in tournament {}: {}", self.game_state.id, tournament_id, e);
+        }
+    }
+    
+    fn game_record(&self, scores: HashMap<Uuid, i32>) -> GameRecord {
+        let mut players: Vec<Uuid> = self.game_state.players.keys().copied().collect();
+        players.sort();
+        
+        GameRecord {
+            game_id: self.game_state.id,
+            city: self.game_state.city.clone(),
+            players,
+            roster: self.game_state.roster.clone(),
+            scores,
+            finished_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+            replay: self.replay.clone(),
+            accounts: self.accounts.clone(),
+            completed_lines: self.game_state.players.iter()
+                .map(|(player_id, sheet)| (*player_id, sheet.completed_lines.clone()))
+                .collect(),
+        }
+    }
+    
+    /// Stores the game and adds it to the stats of the accounts that played.
+    async fn save_record(&self, record: &GameRecord) {
+        if let Err(e) = self.storage.save_game(record).await {
+            log::error!("Could not save game {}: {}", record.game_id, e);
+        }
+        if let Err(e) = self.account_store.record_game(record).await {
+            log::error!("Could not add game {} to the account stats: {}", record.game_id, e);
+        }
+    }
+    
+    /// The moment a player's clock runs out for the current card, or `None` if
+    /// the round is untimed.
+    pub fn player_deadline(&self, player_id: &Uuid) -> Option<Instant> {
+        let round_started = self.round_started?;
+        
+        if self.timer.round_timeout.is_none() && self.timer.time_bank.is_none() {
+            return None;
+        }
+        
+        let round_timeout = self.timer.round_timeout.unwrap_or_default();
+        let time_bank = self.time_banks.get(player_id).copied().unwrap_or_default();
+        Some(round_started + round_timeout + time_bank)
+    }
+    
+    /// Deducts the time a player spent beyond the round timeout from their bank.
+    pub fn charge_time_bank(&mut self, player_id: Uuid, acted_at: Instant) {
+        let (Some(round_started), Some(time_bank)) = (self.round_started, self.time_banks.get_mut(&player_id)) else {
+            return;
+        };
+        
+        let elapsed = acted_at.saturating_duration_since(round_started);
+        let overtime = elapsed.saturating_sub(self.timer.round_timeout.unwrap_or_default());
+        *time_bank = time_bank.saturating_sub(overtime);
+    }
+    
+    /// Plays a default move for every player whose deadline has passed and
+    /// advances the round once nobody is left to wait for.
+    pub async fn check_timeouts(&mut self, now: Instant) {
+        if self.game_state.phase != GamePhase::WaitingForActions {
+            return;
+        }
+        
+        let mut timed_out: Vec<Uuid> = self.players.keys()
+            .filter(|player_id| !self.game_state.has_acted(player_id))
+            .filter(|player_id| self.player_deadline(player_id).is_some_and(|deadline| deadline <= now))
+            .copied()
+            .collect();
+        timed_out.sort();
+        
+        for player_id in timed_out {
+            let mut action = self.game_state.default_action(player_id);
+            
+            if let Some(default_action) = action.clone()
+                && self.game_state.process_player_action(player_id, default_action, &self.subway_map).is_err()
+            {
+                action = None;
+            }
+            
+            // Cards played more than once only get one default move
+            if !self.game_state.has_acted(&player_id) {
+                self.game_state.pass(player_id);
+            }
+            self.replay.record(player_id, action.clone());
+            
+            if let Some(time_bank) = self.time_banks.get_mut(&player_id) {
+                *time_bank = Duration::ZERO;
+            }
+            
+            self.broadcast_message(&GameMessage::PlayerTimedOut { player_id, action }).await;
+        }
+        
+        self.sync_state().await;
+        if self.game_state.all_players_acted() {
+            self.advance_round().await;
+        }
+    }
+}
+
+/// Drives the round deadlines of a session until its game is over.
+fn spawn_round_timer(session: &Arc<Mutex<GameSession>>) {
+    let session = Arc::downgrade(session);
+    
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TIMER_RESOLUTION);
+        
+        loop {
+            interval.tick().await;
+            
+            let Some(session) = session.upgrade() else {
+                break;
+            };
+            let mut session_guard = session.lock().await;
+            if session_guard.game_state.is_finished() {
+                break;
+            }
+            session_guard.check_timeouts(Instant::now()).await;
+        }
+    });
+}
+
+/// Checks the matchmaking queue regularly, for players whose wait ran out.
+pub fn spawn_matchmaker(server: &GameServer) {
+    let server = server.clone();
+    
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(MATCHMAKING_INTERVAL);
+        
+        loop {
+            interval.tick().await;
+            server.run_matchmaking().await;
+        }
+    });
+}
+
+/// Removes idle sessions regularly, see `GameSession::idle_timeout`.
+pub fn spawn_reaper(server: &GameServer) {
+    let server = server.clone();
+    
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REAPER_INTERVAL);
+        
+        loop {
+            interval.tick().await;
+            server.reap_sessions(Instant::now()).await;
+        }
+    });
+}
+
+#[derive(Clone)]
+pub struct GameServer {
+    sessions: Arc<RwLock<HashMap<Uuid, Arc<Mutex<GameSession>>>>>,
+    config: Arc<Config>,
+    maps: Arc<HashMap<City, SubwayMap>>,
+    storage: Arc<Storage>,
+    accounts: Arc<Accounts>,
+    matchmaker: Arc<Mutex<Matchmaker>>,
+    room_throttle: Arc<IpThrottle>,
+    daily_throttle: Arc<IpThrottle>,
+    account_throttle: Arc<IpThrottle>,
+}
+
+impl GameServer {
+    pub fn new(config: Config, maps: HashMap<City, SubwayMap>, storage: Storage, accounts: Accounts) -> Self {
+        Self {
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(config),
+            maps: Arc::new(maps),
+            storage: Arc::new(storage),
+            accounts: Arc::new(accounts),
+            matchmaker: Arc::new(Mutex::new(Matchmaker::default())),
+            room_throttle: Arc::new(IpThrottle::new(ROOM_CREATION_LIMIT, ROOM_CREATION_WINDOW)),
+            daily_throttle: Arc::new(IpThrottle::new(DAILY_SCORE_LIMIT, DAILY_SCORE_WINDOW)),
+            account_throttle: Arc::new(IpThrottle::new(ACCOUNT_ATTEMPT_LIMIT, ACCOUNT_ATTEMPT_WINDOW)),
+        }
+    }
+    
+    async fn handle_connection(&self, socket: WebSocket, addr: SocketAddr) {
+        log::info!("New WebSocket connection from: {}", addr);
+        
+        let (mut sink, mut receiver) = socket.split();
+        let (frames, mut outgoing) = mpsc::unbounded_channel();
+        let sender = Arc::new(Mutex::new(WebSocketSender::new(frames)));
+        tokio::spawn(async move {
+            while let Some(frame) = outgoing.recv().await {
+                if sink.send(frame).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let connection_id = Uuid::new_v4();
+        let seat: SeatSlot = Default::default();
+        let mut player_id: Option<Uuid> = None;
+        let mut spectator_id: Option<Uuid> = None;
+        let mut game_session: Option<Arc<Mutex<GameSession>>> = None;
+        let mut account: Option<Account> = None;
+        let mut rate_limiter = RateLimiter::default();
+        let mut greeted = false;
+        
+        while let Some(message) = receiver.next().await {
+            // The matchmaker may have found a room since the last message
+            if let Some((seated_id, session)) = seat.lock().unwrap().pick_up() {
+                player_id = Some(seated_id);
+                game_session = Some(session);
+            }
+            
+            match message {
+                Ok(frame @ (Message::Text(_) | Message::Binary(_))) => {
+                    if let Ok(ClientRequest { request_id, message: game_message }) = decode_frame(&frame) {
+                        let reply_error = |message: String| GameMessage::Error { request_id, message };
+                        
+                        match game_message {
+                            GameMessage::Hello { protocol_version, client_kind, wire_formats } => {
+                                if greeted {
+                                    send_message(&sender, &reply_error("Already said hello".to_string())).await;
+                                    continue;
+                                }
+                                if !SUPPORTED_PROTOCOL_VERSIONS.contains(&protocol_version) {
+                                    log::info!("Rejecting {}: unsupported protocol version {}", addr, protocol_version);
+                                    reject_client(&sender, format!("Protocol version {} is not supported, please reload the client", protocol_version)).await;
+                                    break;
+                                }
+                                
+                                greeted = true;
+                                // We speak every format there is, so the client's favourite wins
+                                let wire_format = wire_formats.first().copied().unwrap_or_default();
+                                log::info!("{} is a {:?} client speaking protocol {} in {:?}", addr, client_kind, protocol_version, wire_format);
+                                
+                                send_message(&sender, &GameMessage::Welcome {
+                                    protocol_version,
+                                    supported_versions: SUPPORTED_PROTOCOL_VERSIONS.to_vec(),
+                                    capabilities: SERVER_CAPABILITIES.iter().map(|capability| capability.to_string()).collect(),
+                                    wire_format,
+                                }).await;
+                                sender.lock().await.wire_format = wire_format;
+                            },
+                            
+                            _ if !greeted => {
+                                log::info!("Rejecting {}: no Hello before other messages", addr);
+                                reject_client(&sender, "Expected Hello as the first message, please reload the client".to_string()).await;
+                                break;
+                            },
+                            
+                            GameMessage::JoinGame { player_name, city, game_id } => {
+                                if game_session.is_some() || seat.lock().unwrap().waiting {
+                                    send_message(&sender, &reply_error("Already in a game".to_string())).await;
+                                    continue;
+                                }
+                                
+                                let player_name = match validate_player_name(&player_name) {
+                                    Ok(name) => name,
+                                    Err(error) => {
+                                        send_message(&sender, &reply_error(error)).await;
+                                        continue;
+                                    }
+                                };
+                                
+                                let Some(game_id) = game_id else {
+                                    let queued = self.queue_player(Ticket {
+                                        id: connection_id,
+                                        player_name,
+                                        city,
+                                        account: account.clone(),
+                                        sender: sender.clone(),
+                                        seat: seat.clone(),
+                                        queued_at: Instant::now(),
+                                    }).await;
+                                    if let Err(error) = queued {
+                                        send_message(&sender, &reply_error(error)).await;
+                                    }
+                                    continue;
+                                };
+                                let Some(session) = self.sessions.read().await.get(&game_id).cloned() else {
+                                    send_message(&sender, &reply_error("Game not found".to_string())).await;
+                                    continue;
+                                };
+                                
+                                let new_player_id = Uuid::new_v4();
+                                let player = PlayerConnection {
+                                    id: new_player_id,
+                                    sender: sender.clone(),
+                                    rejoin_token: Uuid::new_v4(),
+                                };
+                                
+                                let mut session_guard = session.lock().await;
+                                if let Err(error) = session_guard.add_player(player, player_name, account.as_ref()).await {
+                                    send_message(&sender, &reply_error(error)).await;
+                                    continue;
+                                }
+                                
+                                player_id = Some(new_player_id);
+                                game_session = Some(session.clone());
+                                
+                                // Send confirmation
+                                for message in session_guard.welcome_messages(new_player_id) {
+                                    send_message(&sender, &message).await;
+                                }
+                            },
+                            
+                            GameMessage::Rejoin { game_id, player_id: rejoining_id, token } => {
+                                if game_session.is_some() || seat.lock().unwrap().waiting {
+                                    send_message(&sender, &reply_error("Already in a game".to_string())).await;
+                                    continue;
+                                }
+                                
+                                let Some(session) = self.sessions.read().await.get(&game_id).cloned() else {
+                                    send_message(&sender, &reply_error("Game not found".to_string())).await;
+                                    continue;
+                                };
+                                
+                                let mut session_guard = session.lock().await;
+                                if let Err(error) = session_guard.rejoin_player(rejoining_id, token, sender.clone()).await {
+                                    send_message(&sender, &reply_error(error)).await;
+                                    continue;
+                                }
+                                
+                                player_id = Some(rejoining_id);
+                                game_session = Some(session.clone());
+                                
+                                for message in session_guard.welcome_messages(rejoining_id) {
+                                    send_message(&sender, &message).await;
+                                }
+                            },
+                            
+                            GameMessage::Authenticate { token } => {
+                                if game_session.is_some() || seat.lock().unwrap().waiting {
+                                    send_message(&sender, &reply_error("Log in before joining a game".to_string())).await;
+                                    continue;
+                                }
+                                
+                                match self.accounts.authenticate(token).await {
+                                    Some(found) => {
+                                        log::info!("{} logged in as {}", addr, found.username);
+                                        send_message(&sender, &GameMessage::Authenticated { username: found.username.clone() }).await;
+                                        account = Some(found);
+                                    },
+                                    None => send_message(&sender, &reply_error("Unknown or expired login, please log in again".to_string())).await,
+                                }
+                            },
+                            
+                            GameMessage::Spectate { game_id } => {
+                                if game_session.is_some() || seat.lock().unwrap().waiting {
+                                    send_message(&sender, &reply_error("Already in a game".to_string())).await;
+                                    continue;
+                                }
+                                
+                                let Some(session) = self.sessions.read().await.get(&game_id).cloned() else {
+                                    send_message(&sender, &reply_error("Game not found".to_string())).await;
+                                    continue;
+                                };
+                                
+                                let new_spectator_id = Uuid::new_v4();
+                                spectator_id = Some(new_spectator_id);
+                                game_session = Some(session.clone());
+                                
+                                let mut session_guard = session.lock().await;
+                                send_message(&sender, &GameMessage::Spectating {
+                                    game_id,
+                                    subway_map: session_guard.subway_map.clone(),
+                                }).await;
+                                send_message(&sender, &session_guard.snapshot(None)).await;
+                                send_message(&sender, &session_guard.chat_history()).await;
+                                session_guard.add_spectator(new_spectator_id, SpectatorConnection { sender: sender.clone() }).await;
+                            },
+                            
+                            GameMessage::SendChat { .. } | GameMessage::React { .. } => {
+                                let (Some(sender_id), Some(session)) = (player_id.or(spectator_id), &game_session) else {
+                                    send_message(&sender, &reply_error("Join or spectate a game to chat".to_string())).await;
+                                    continue;
+                                };
+                                
+                                if !rate_limiter.try_acquire(Instant::now()) {
+                                    send_message(&sender, &reply_error("You are sending messages too quickly".to_string())).await;
+                                    continue;
+                                }
+                                
+                                let mut session_guard = session.lock().await;
+                                let result = match game_message {
+                                    GameMessage::SendChat { text } => session_guard.post_chat(sender_id, &text).await,
+                                    GameMessage::React { emote, target } => session_guard.react(sender_id, emote, target).await,
+                                    _ => unreachable!(),
+                                };
+                                drop(session_guard);
+                                
+                                if let Err(error) = result {
+                                    send_message(&sender, &reply_error(error)).await;
+                                }
+                            },
+                            
+                            GameMessage::PlayerAction(_) | GameMessage::StartGame if spectator_id.is_some() => {
+                                send_message(&sender, &reply_error("Spectators cannot play".to_string())).await;
+                            },
+                            
+                            GameMessage::PlayerAction(action) => {
+                                play_action(&sender, player_id.zip(game_session.as_ref()), request_id, action).await;
+                            },
+                            
+                            GameMessage::StartGame => {
+                                if let Some(session) = &game_session {
+                                    let mut session_guard = session.lock().await;
+                                    if let Err(e) = session_guard.start_game().await {
+                                        send_message(&sender, &reply_error(e)).await;
+                                    }
+                                }
+                            },
+                            
+                            GameMessage::RequestSnapshot => {
+                                if let Some(session) = &game_session {
+                                    let snapshot = session.lock().await.snapshot(player_id);
+                                    send_message(&sender, &snapshot).await;
+                                }
+                            },
+                            
+                            _ => {
+                                // Handle other message types as needed
+                            }
+                        }
+                    } else if !greeted {
+                        reject_client(&sender, "Could not read the first message, please reload the client".to_string()).await;
+                        break;
+                    } else {
+                        send_message(&sender, &GameMessage::Error { request_id: None, message: "Invalid message".to_string() }).await;
+                    }
+                },
+                Ok(Message::Close(_)) => {
+                    log::info!("Client {} disconnected", addr);
+                    break;
+                },
+                Err(e) => {
+                    log::warn!("WebSocket error for {}: {}", addr, e);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        
+        // A ticket the matchmaker already took out of the queue may still get
+        // a room; closing the seat makes it give that up
+        self.matchmaker.lock().await.leave(connection_id);
+        let taken = {
+            let mut seat = seat.lock().unwrap();
+            seat.closed = true;
+            seat.pick_up()
+        };
+        if let Some((seated_id, session)) = taken {
+            player_id = Some(seated_id);
+            game_session = Some(session);
+        }
+        
+        if let (Some(spectator_id), Some(session)) = (spectator_id, &game_session) {
+            session.lock().await.remove_spectator(spectator_id).await;
+        }
+        if let (Some(player_id), Some(session)) = (player_id, &game_session) {
+            session.lock().await.disconnect_player(player_id, &sender).await;
+        }
+    }
+    
+    /// Puts a player in the matchmaking queue for a city. `GameJoined` follows
+    /// once the matchmaker found them a room. A connection whose seat is still
+    /// waiting, in the queue or for its room, can't queue again.
+    pub async fn queue_player(&self, ticket: Ticket) -> Result<(), String> {
+        let sender = ticket.sender.clone();
+        let city = ticket.city.clone();
+        
+        {
+            let mut seat = ticket.seat.lock().unwrap();
+            if seat.waiting {
+                return Err("Already in a game".to_string());
+            }
+            seat.waiting = true;
+        }
+        
+        let mut matchmaker = self.matchmaker.lock().await;
+        matchmaker.join(ticket);
+        let waiting = matchmaker.waiting(&city);
+        drop(matchmaker);
+        
+        send_message(&sender, &GameMessage::Queued { waiting }).await;
+        self.run_matchmaking().await;
+        Ok(())
+    }
+    
+    fn match_settings(&self) -> MatchSettings {
+        MatchSettings {
+            min_players: self.config.min_match_players,
+            max_players: self.config.max_players,
+            max_wait: self.config.match_wait,
+            rating_window: RATING_WINDOW,
+            window_growth: WINDOW_GROWTH,
+        }
+    }
+    
+    /// Gives every group the queue has ready a room of its own. The queue is
+    /// only locked while the groups are taken out, not while they are seated.
+    /// A group that lost players on the way, or finds no free room, waits in
+    /// the queue again.
+    async fn run_matchmaking(&self) {
+        let settings = self.match_settings();
+        let groups = self.matchmaker.lock().await.take_matches(Instant::now(), &settings);
+        
+        for group in groups {
+            // A group that waited out its time may be smaller than usual
+            let needed = settings.min_players.min(group.len());
+            
+            // Connections that left are dropped, and of two players with the
+            // same name the second waits for the next room
+            let mut seated: Vec<Ticket> = Vec::new();
+            let mut waiting: Vec<Ticket> = Vec::new();
+            for ticket in group {
+                if ticket.seat.lock().unwrap().closed {
+                    continue;
+                }
+                if seated.iter().any(|other| other.player_name.eq_ignore_ascii_case(&ticket.player_name)) {
+                    waiting.push(ticket);
+                } else {
+                    seated.push(ticket);
+                }
+            }
+            
+            let session = match seated.len() >= needed && !seated.is_empty() {
+                true => self.try_create_sessions(vec![RoomOptions::for_city(seated[0].city.clone())]).await,
+                false => None,
+            };
+            let Some(session) = session.and_then(|sessions| sessions.into_iter().next()) else {
+                log::info!("No room for a group of {} yet, it waits in the queue", seated.len() + waiting.len());
+                waiting.append(&mut seated);
+                self.requeue(waiting).await;
+                continue;
+            };
+            self.requeue(waiting).await;
+            
+            let mut session_guard = session.lock().await;
+            let mut players = 0;
+            for ticket in seated {
+                let new_player_id = Uuid::new_v4();
+                let player = PlayerConnection {
+                    id: new_player_id,
+                    sender: ticket.sender.clone(),
+                    rejoin_token: Uuid::new_v4(),
+                };
+                
+                if let Err(error) = session_guard.add_player(player, ticket.player_name.clone(), ticket.account.as_ref()).await {
+                    log::info!("Could not seat {}: {}", ticket.player_name, error);
+                    self.requeue(vec![ticket]).await;
+                    continue;
+                }
+                
+                let closed = {
+                    let mut seat = ticket.seat.lock().unwrap();
+                    if !seat.closed {
+                        seat.taken = Some((new_player_id, session.clone()));
+                    }
+                    seat.closed
+                };
+                if closed {
+                    session_guard.disconnect_player(new_player_id, &ticket.sender).await;
+                    continue;
+                }
+                players += 1;
+                for message in session_guard.welcome_messages(new_player_id) {
+                    send_message(&ticket.sender, &message).await;
+                }
+            }
+            
+            if players == 0 {
+                let game_id = session_guard.game_state.id;
+                drop(session_guard);
+                self.remove_sessions(&[game_id]).await;
+            }
+        }
+    }
+    
+    /// Puts tickets back in the queue, unless their connection is gone.
+    async fn requeue(&self, tickets: Vec<Ticket>) {
+        if tickets.is_empty() {
+            return;
+        }
+        
+        let mut matchmaker = self.matchmaker.lock().await;
+        for ticket in tickets {
+            if !ticket.seat.lock().unwrap().closed {
+                matchmaker.join(ticket);
+            }
+        }
+    }
+    
+    /// Drops the sessions that have been idle for too long.
+    pub async fn reap_sessions(&self, now: Instant) {
+        let sessions: Vec<(Uuid, Arc<Mutex<GameSession>>)> = self.sessions.read().await.iter()
+            .map(|(id, session)| (*id, session.clone()))
+            .collect();
+        
+        let mut idle = Vec::new();
+        let mut abandoned_tables = Vec::new();
+        for (id, session) in sessions {
+            let mut session = session.lock().await;
+            if session.idle_time(now) >= session.idle_timeout() {
+                idle.push(id);
+                abandoned_tables.extend(session.unfinished_table().map(|(tournament_id, players)| (tournament_id, id, players)));
+            }
+        }
+        if idle.is_empty() {
+            return;
+        }
+        
+        // Nobody showed up or stayed, so everyone at the table forfeits
+        for (tournament_id, game_id, players) in abandoned_tables {
+            if let Err(e) = self.storage.forfeit_tournament_table(tournament_id, game_id, &players).await {
+                log::error!("Could not forfeit game {} in tournament {}: {}", game_id, tournament_id, e);
+            }
+        }
+        
+        let left = self.remove_sessions(&idle).await;
+        log::info!("Removed {} idle sessions, {} left", idle.len(), left);
+    }
+    
+    /// Drops sessions and returns how many are left. Connections still
+    /// holding one keep it until they leave; the round timer stops by itself.
+    async fn remove_sessions(&self, ids: &[Uuid]) -> usize {
+        let mut sessions = self.sessions.write().await;
+        for id in ids {
+            sessions.remove(id);
+        }
+        sessions.len()
+    }
+    
+    pub async fn session_count(&self) -> usize {
+        self.sessions.read().await.len()
+    }
+    
+    /// Opens a room for each of `options`, or none at all if that would take
+    /// the server past `max_rooms`.
+    async fn try_create_sessions(&self, options: Vec<RoomOptions>) -> Option<Vec<Arc<Mutex<GameSession>>>> {
+        let new_sessions: Vec<Arc<Mutex<GameSession>>> = options.into_iter()
+            .map(|options| self.new_session(options))
+            .collect();
+        
+        let mut sessions = self.sessions.write().await;
+        if sessions.len() + new_sessions.len() > self.config.max_rooms {
+            return None;
+        }
+        for new_session in &new_sessions {
+            let session_id = new_session.lock().await.game_state.id;
+            spawn_round_timer(new_session);
+            sessions.insert(session_id, new_session.clone());
+        }
+        
+        Some(new_sessions)
+    }
+    
+    fn new_session(&self, options: RoomOptions) -> Arc<Mutex<GameSession>> {
+        let subway_map = self.maps.get(&options.city).cloned()
+            .unwrap_or_else(|| SubwayMap::builtin(&options.city));
+        let max_players = options.max_players.unwrap_or(self.config.max_players);
+        
+        let mut timer = self.config.round_timer();
+        if let Some(seconds) = options.round_timeout_secs {
+            timer.round_timeout = (seconds > 0).then(|| Duration::from_secs(seconds));
+        }
+        if let Some(seconds) = options.time_bank_secs {
+            timer.time_bank = (seconds > 0).then(|| Duration::from_secs(seconds));
+        }
+        
+        let mut rules = options.rules
+            .unwrap_or_else(|| options.preset.unwrap_or(self.config.rules).rules());
+        if let Some(expansions) = options.expansions {
+            rules.expansions = expansions;
+        }
+        let spectator_chat = options.spectator_chat.unwrap_or(true);
+        Arc::new(Mutex::new(GameSession::new(
+            subway_map,
+            rules,
+            max_players,
+            timer,
+            spectator_chat,
+            self.storage.clone(),
+            self.accounts.clone(),
+        )))
+    }
+}
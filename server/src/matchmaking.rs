@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use verplant::{City, DEFAULT_RATING, MatchSettings, QueuedPlayer, find_matches};
+
+use crate::accounts::Account;
+use crate::{GameSession, WebSocketSender};
+
+/// How far apart ratings may be when a player joins the queue.
+pub const RATING_WINDOW: f64 = 100.0;
+
+/// How much wider the window gets per second of waiting.
+pub const WINDOW_GROWTH: f64 = 10.0;
+
+/// Where the matchmaker leaves the player id and session once a queued
+/// connection got a room, for the connection to pick up.
+pub type SeatSlot = Arc<std::sync::Mutex<Seat>>;
+
+#[derive(Default)]
+pub struct Seat {
+    pub waiting: bool, // Queued, or matched with a room the connection hasn't picked up yet
+    pub taken: Option<(Uuid, Arc<Mutex<GameSession>>)>,
+    pub closed: bool, // The connection is gone, a room found now is given up
+}
+
+impl Seat {
+    /// Hands the connection the room the matchmaker found, if any.
+    pub fn pick_up(&mut self) -> Option<(Uuid, Arc<Mutex<GameSession>>)> {
+        let taken = self.taken.take()?;
+        self.waiting = false;
+        Some(taken)
+    }
+}
+
+/// A connection waiting for a room.
+pub struct Ticket {
+    pub id: Uuid, // Of the connection
+    pub player_name: String,
+    pub city: City,
+    pub account: Option<Account>,
+    pub sender: Arc<Mutex<WebSocketSender>>,
+    pub seat: SeatSlot,
+    pub queued_at: Instant,
+}
+
+impl Ticket {
+    fn rating(&self) -> f64 {
+        self.account.as_ref().map_or(DEFAULT_RATING, |account| account.rating)
+    }
+}
+
+/// The matchmaking queue. Players without an account are matched as if they
+/// had the starting rating.
+#[derive(Default)]
+pub struct Matchmaker {
+    tickets: Vec<Ticket>, // Oldest first
+}
+
+impl Matchmaker {
+    pub fn join(&mut self, ticket: Ticket) {
+        self.tickets.push(ticket);
+    }
+    
+    pub fn leave(&mut self, id: Uuid) -> Option<Ticket> {
+        let index = self.tickets.iter().position(|ticket| ticket.id == id)?;
+        Some(self.tickets.remove(index))
+    }
+    
+    /// How many players are waiting for a room in a city.
+    pub fn waiting(&self, city: &City) -> usize {
+        self.tickets.iter().filter(|ticket| ticket.city == *city).count()
+    }
+    
+    /// Takes the groups that should get a room now out of the queue.
+    pub fn take_matches(&mut self, now: Instant, settings: &MatchSettings) -> Vec<Vec<Ticket>> {
+        let queue: Vec<QueuedPlayer> = self.tickets.iter()
+            .map(|ticket| QueuedPlayer {
+                id: ticket.id,
+                city: ticket.city.clone(),
+                rating: ticket.rating(),
+                waited: now.saturating_duration_since(ticket.queued_at),
+            })
+            .collect();
+        
+        find_matches(&queue, settings).into_iter()
+            .map(|group| group.into_iter().filter_map(|id| self.leave(id)).collect())
+            .collect()
+    }
+}
@@ -0,0 +1,186 @@
+use std::path::PathBuf;
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+use verplant::{DailyEntry, GameRecord, TableResult, Tournament};
+
+/// Keeps finished games, daily leaderboards and tournaments as JSON files
+/// below the persistence path.
+pub struct Storage {
+    games_dir: PathBuf,
+    daily_dir: PathBuf,
+    daily_lock: Mutex<()>, // Held while an entry is checked against a leaderboard and appended
+    tournaments_dir: PathBuf,
+    tournament_lock: Mutex<()>, // Same for tournaments
+}
+
+impl Storage {
+    pub fn open(root: PathBuf) -> std::io::Result<Storage> {
+        let games_dir = root.join("games");
+        let daily_dir = root.join("daily");
+        let tournaments_dir = root.join("tournaments");
+        std::fs::create_dir_all(&games_dir)?;
+        std::fs::create_dir_all(&daily_dir)?;
+        std::fs::create_dir_all(&tournaments_dir)?;
+        Ok(Storage {
+            games_dir,
+            daily_dir,
+            daily_lock: Mutex::new(()),
+            tournaments_dir,
+            tournament_lock: Mutex::new(()),
+        })
+    }
+    
+    pub async fn save_game(&self, record: &GameRecord) -> std::io::Result<()> {
+        let contents = serde_json::to_vec_pretty(record)?;
+        tokio::fs::write(self.game_path(record.game_id), contents).await
+    }
+    
+    pub async fn load_game(&self, game_id: Uuid) -> Option<GameRecord> {
+        let contents = tokio::fs::read(self.game_path(game_id)).await.ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+    
+    pub async fn list_games(&self) -> Vec<GameRecord> {
+        let mut games = Vec::new();
+        
+        let Ok(mut entries) = tokio::fs::read_dir(&self.games_dir).await else {
+            return games;
+        };
+        
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let Ok(contents) = tokio::fs::read(entry.path()).await else {
+                continue;
+            };
+            match serde_json::from_slice::<GameRecord>(&contents) {
+                Ok(record) => games.push(record),
+                Err(e) => log::warn!("Skipping unreadable game record {}: {}", entry.path().display(), e),
+            }
+        }
+        
+        games.sort_by_key(|record| std::cmp::Reverse(record.finished_at));
+        games
+    }
+    
+    fn game_path(&self, game_id: Uuid) -> PathBuf {
+        self.games_dir.join(format!("{}.json", game_id))
+    }
+    
+    /// The leaderboard of a daily challenge, best score first. Equal scores keep
+    /// the order they came in. `date` must be one of `DailyChallenge::date`, it
+    /// becomes part of a file name.
+    pub async fn load_daily(&self, date: &str) -> Vec<DailyEntry> {
+        let Ok(contents) = tokio::fs::read_to_string(self.daily_path(date)).await else {
+            return Vec::new();
+        };
+        
+        let mut entries: Vec<DailyEntry> = contents.lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line)
+                .inspect_err(|e| log::warn!("Skipping unreadable score on the {} leaderboard: {}", date, e))
+                .ok())
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries
+    }
+    
+    /// Appends an entry to a daily leaderboard and returns its rank, starting
+    /// at 1, or `None` if its submitter already has `max_per_submitter`
+    /// entries there.
+    pub async fn add_daily_entry(&self, date: &str, entry: DailyEntry, max_per_submitter: usize) -> std::io::Result<Option<usize>> {
+        let _guard = self.daily_lock.lock().await;
+        
+        let entries = self.load_daily(date).await;
+        let submitter_entries = entries.iter()
+            .filter(|other| other.submitter == entry.submitter)
+            .count();
+        if submitter_entries >= max_per_submitter {
+            return Ok(None);
+        }
+        let rank = entries.iter().take_while(|other| other.score >= entry.score).count();
+        
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.daily_path(date))
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(Some(rank + 1))
+    }
+    
+    fn daily_path(&self, date: &str) -> PathBuf {
+        self.daily_dir.join(format!("{}.jsonl", date))
+    }
+    
+    /// Held while a tournament is loaded, changed and saved.
+    pub async fn lock_tournaments(&self) -> MutexGuard<'_, ()> {
+        self.tournament_lock.lock().await
+    }
+    
+    pub async fn load_tournament(&self, id: Uuid) -> Option<Tournament> {
+        let contents = tokio::fs::read(self.tournament_path(id)).await.ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+    
+    /// Every tournament, newest first.
+    pub async fn list_tournaments(&self) -> Vec<Tournament> {
+        let mut tournaments = Vec::new();
+        
+        let Ok(mut entries) = tokio::fs::read_dir(&self.tournaments_dir).await else {
+            return tournaments;
+        };
+        
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let Ok(contents) = tokio::fs::read(entry.path()).await else {
+                continue;
+            };
+            match serde_json::from_slice::<Tournament>(&contents) {
+                Ok(tournament) => tournaments.push(tournament),
+                Err(e) => log::warn!("Skipping unreadable tournament {}: {}", entry.path().display(), e),
+            }
+        }
+        
+        tournaments.sort_by_key(|tournament| std::cmp::Reverse(tournament.created_at));
+        tournaments
+    }
+    
+    /// Call with the guard of `lock_tournaments` held.
+    pub async fn save_tournament(&self, tournament: &Tournament) -> std::io::Result<()> {
+        let contents = serde_json::to_vec_pretty(tournament)?;
+        tokio::fs::write(self.tournament_path(tournament.id), contents).await
+    }
+    
+    /// Adds the results of a finished tournament game to its tournament.
+    pub async fn record_tournament_game(&self, id: Uuid, game_id: Uuid, results: Vec<TableResult>) -> std::io::Result<()> {
+        let _guard = self.lock_tournaments().await;
+        
+        let Some(mut tournament) = self.load_tournament(id).await else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("Tournament {} not found", id)));
+        };
+        if tournament.record_results(game_id, results) {
+            self.save_tournament(&tournament).await?;
+        }
+        Ok(())
+    }
+    
+    /// Settles a tournament table without its game, see `Tournament::forfeit_table`.
+    pub async fn forfeit_tournament_table(&self, id: Uuid, game_id: Uuid, forfeits: &[String]) -> std::io::Result<()> {
+        let _guard = self.lock_tournaments().await;
+        
+        let Some(mut tournament) = self.load_tournament(id).await else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("Tournament {} not found", id)));
+        };
+        tournament.forfeit_table(game_id, forfeits)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        self.save_tournament(&tournament).await
+    }
+    
+    fn tournament_path(&self, id: Uuid) -> PathBuf {
+        self.tournaments_dir.join(format!("{}.json", id))
+    }
+}
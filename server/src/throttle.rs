@@ -0,0 +1,38 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Allows each IP address at most `limit` requests within a sliding `window`,
+/// for endpoints that anyone can call but that cost the server something.
+pub struct IpThrottle {
+    limit: usize,
+    window: Duration,
+    requests: Mutex<HashMap<IpAddr, VecDeque<Instant>>>,
+}
+
+impl IpThrottle {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self { limit, window, requests: Mutex::new(HashMap::new()) }
+    }
+    
+    pub fn try_acquire(&self, ip: IpAddr, now: Instant) -> bool {
+        let mut requests = self.requests.lock().unwrap();
+        
+        // Addresses that went quiet are forgotten, so the map stays small
+        requests.retain(|_, sent| {
+            while sent.front().is_some_and(|sent| now.saturating_duration_since(*sent) >= self.window) {
+                sent.pop_front();
+            }
+            !sent.is_empty()
+        });
+        
+        let sent = requests.entry(ip).or_default();
+        if sent.len() >= self.limit {
+            return false;
+        }
+        
+        sent.push_back(now);
+        true
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use verplant::{City, GameRecord, Replay};
+use verplant_server::accounts::Accounts;
+
+#[tokio::test]
+async fn registering_checks_the_name_and_password() {
+    let accounts = Accounts::open(&common::temp_dir()).unwrap();
+    
+    let (account, _) = accounts.register(" Ann ", "correct horse").await.unwrap();
+    assert_eq!(account.username, "Ann");
+    
+    assert!(accounts.register("ann", "another password").await.is_err());
+    assert!(accounts.register("Bob", "short").await.is_err());
+    assert!(accounts.register("Bob!", "correct horse").await.is_err());
+    assert_eq!(accounts.find("ANN").await.unwrap().id, account.id);
+}
+
+#[tokio::test]
+async fn logging_in_needs_the_right_password() {
+    let accounts = Accounts::open(&common::temp_dir()).unwrap();
+    accounts.register("Ann", "correct horse").await.unwrap();
+    
+    let (account, token) = accounts.login("ann", "correct horse").await.unwrap();
+    assert_eq!(account.username, "Ann");
+    assert_eq!(accounts.authenticate(token).await.unwrap().id, account.id);
+    
+    // An unknown name fails the same way as a wrong password
+    let wrong_password = accounts.login("Ann", "battery staple").await.unwrap_err();
+    let unknown_name = accounts.login("Bob", "correct horse").await.unwrap_err();
+    assert_eq!(wrong_password, unknown_name);
+}
+
+#[tokio::test]
+async fn tokens_are_stored_hashed() {
+    let dir = common::temp_dir();
+    let accounts = Accounts::open(&dir).unwrap();
+    let (account, token) = accounts.register("Ann", "correct horse").await.unwrap();
+    
+    let file = std::fs::read_to_string(dir.join("accounts.json")).unwrap();
+    assert!(!file.contains(&token.to_string()));
+    assert!(!file.contains("correct horse"));
+    
+    // Tokens still work once the accounts are loaded again
+    let accounts = Accounts::open(&dir).unwrap();
+    assert_eq!(accounts.authenticate(token).await.unwrap().id, account.id);
+    assert!(accounts.authenticate(Uuid::new_v4()).await.is_none());
+}
+
+#[tokio::test]
+async fn finished_games_add_to_the_stats() {
+    let accounts = Accounts::open(&common::temp_dir()).unwrap();
+    let (account, _) = accounts.register("Ann", "correct horse").await.unwrap();
+    let (player, other) = (Uuid::new_v4(), Uuid::new_v4());
+    
+    let record = GameRecord {
+        game_id: Uuid::new_v4(),
+        city: City::Amsterdam,
+        players: vec![player, other],
+        roster: Vec::new(),
+        scores: HashMap::from([(player, 12), (other, 7)]),
+        finished_at: 0,
+        replay: Replay::default(),
+        accounts: HashMap::from([(player, account.id)]),
+        completed_lines: HashMap::new(),
+    };
+    accounts.record_game(&record).await.unwrap();
+    
+    let stats = accounts.find("Ann").await.unwrap().stats.stats();
+    assert_eq!((stats.games_played, stats.wins), (1, 1));
+    assert_eq!(stats.best_scores[&City::Amsterdam], 12);
+    assert_eq!(accounts.find("Ann").await.unwrap().game_ids, vec![record.game_id]);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+
+use axum::Router;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use verplant::{City, GameRecord, Replay};
+use verplant_server::accounts::Accounts;
+use verplant_server::http::router;
+use verplant_server::storage::Storage;
+
+/// The server's routes, without a client to serve.
+fn app(args: &[&str]) -> Router {
+    router(common::server(args), &common::temp_dir())
+}
+
+/// Sends a request from a fixed address and returns the status and JSON body.
+async fn call(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+        .unwrap();
+    request.extensions_mut().insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 4000))));
+    
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn rooms_only_report_scores_once_finished() {
+    let app = app(&[]);
+    let (status, room) = call(&app, "POST", "/api/rooms", Some(json!({ "city": "Amsterdam" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    
+    let (status, details) = call(&app, "GET", &format!("/api/rooms/{}", room["id"].as_str().unwrap()), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(details["status"], room["status"]);
+    assert_eq!(details["scores"], Value::Null);
+}
+
+#[tokio::test]
+async fn account_games_come_in_pages_newest_first() {
+    let dir = common::temp_dir();
+    let storage = Storage::open(dir.clone()).unwrap();
+    let accounts = Accounts::open(&dir).unwrap();
+    let (account, _) = accounts.register("Ann", "correct horse").await.unwrap();
+    
+    let mut game_ids = Vec::new();
+    for finished_at in 0..3 {
+        let player = Uuid::new_v4();
+        let record = GameRecord {
+            game_id: Uuid::new_v4(),
+            city: City::Amsterdam,
+            players: vec![player],
+            roster: Vec::new(),
+            scores: HashMap::from([(player, 10)]),
+            finished_at,
+            replay: Replay::default(),
+            accounts: HashMap::from([(player, account.id)]),
+            completed_lines: HashMap::new(),
+        };
+        storage.save_game(&record).await.unwrap();
+        accounts.record_game(&record).await.unwrap();
+        game_ids.push(record.game_id.to_string());
+    }
+    
+    let app = router(common::server_in(&dir, &[]), &common::temp_dir());
+    let (status, games) = call(&app, "GET", "/api/accounts/Ann/games?offset=1&limit=1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(games.as_array().unwrap().len(), 1);
+    assert_eq!(games[0]["game_id"], game_ids[1]);
+    
+    let (status, games) = call(&app, "GET", "/api/accounts/Ann/games", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(games[0]["game_id"], game_ids[2]);
+    
+    let (status, _) = call(&app, "GET", "/api/accounts/Ann/games?limit=1000", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
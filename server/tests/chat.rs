@@ -0,0 +1,75 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+use verplant::{Emote, GameMessage, LineId, ReactionTarget};
+use verplant_server::chat::{CHAT_HISTORY_LENGTH, MAX_CHAT_LENGTH, RateLimiter, clean_chat_text};
+
+use common::{UNTIMED, join, received, session};
+
+#[test]
+fn chat_text_is_trimmed_and_limited() {
+    assert_eq!(clean_chat_text("  hello there \n"), Ok("hello there".to_string()));
+    assert!(clean_chat_text("").is_err());
+    assert!(clean_chat_text(" \t ").is_err());
+    
+    // The limit counts characters, not bytes
+    assert!(clean_chat_text(&"ü".repeat(MAX_CHAT_LENGTH)).is_ok());
+    assert!(clean_chat_text(&"a".repeat(MAX_CHAT_LENGTH + 1)).is_err());
+}
+
+#[test]
+fn the_rate_limit_window_slides() {
+    let mut limiter = RateLimiter::default();
+    let start = Instant::now();
+    
+    for i in 0..5 {
+        assert!(limiter.try_acquire(start + Duration::from_secs(i)));
+    }
+    assert!(!limiter.try_acquire(start + Duration::from_secs(5)));
+    assert!(limiter.try_acquire(start + Duration::from_secs(10)));
+    assert!(!limiter.try_acquire(start + Duration::from_secs(10)));
+}
+
+#[tokio::test]
+async fn only_the_latest_messages_are_kept() {
+    let mut session = session(UNTIMED);
+    let (ann, _) = join(&mut session, "Ann").await;
+    
+    for i in 0..CHAT_HISTORY_LENGTH + 10 {
+        session.post_chat(ann, &format!("Message {}", i)).await.unwrap();
+    }
+    
+    let GameMessage::ChatHistory(history) = session.chat_history() else {
+        panic!("Expected the chat history");
+    };
+    assert_eq!(history.len(), CHAT_HISTORY_LENGTH);
+    assert_eq!(history[0].text, "Message 10");
+    assert_eq!(history.last().unwrap().text, format!("Message {}", CHAT_HISTORY_LENGTH + 9));
+}
+
+#[tokio::test]
+async fn reactions_must_point_at_something_real() {
+    let mut session = session(UNTIMED);
+    let (ann, mut frames) = join(&mut session, "Ann").await;
+    let (bob, _) = join(&mut session, "Bob").await;
+    let line_id = session.game_state.players[&bob].train_cars.keys().next().unwrap().clone();
+    
+    assert!(session.react(ann, Emote::Clap, None).await.is_ok());
+    assert!(session.react(ann, Emote::Clap, Some(ReactionTarget::Player(bob))).await.is_ok());
+    assert!(session.react(ann, Emote::Clap, Some(ReactionTarget::Player(Uuid::new_v4()))).await.is_err());
+    
+    let completed = ReactionTarget::LineCompleted { player_id: bob, line_id: line_id.clone() };
+    assert!(session.react(ann, Emote::Wow, Some(completed.clone())).await.is_err());
+    session.game_state.players.get_mut(&bob).unwrap().completed_lines.push(line_id);
+    assert!(session.react(ann, Emote::Wow, Some(completed)).await.is_ok());
+    
+    let unknown_line = ReactionTarget::LineCompleted { player_id: bob, line_id: LineId("nowhere".to_string()) };
+    assert!(session.react(ann, Emote::Wow, Some(unknown_line)).await.is_err());
+    
+    let reactions = received(&mut frames).into_iter()
+        .filter(|message| matches!(message, GameMessage::Reaction { .. }))
+        .count();
+    assert_eq!(reactions, 3);
+}
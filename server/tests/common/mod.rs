@@ -0,0 +1,76 @@
+//! Sessions and connections for the server tests, without any sockets.
+#![allow(dead_code)] // Not every test file uses every helper
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::extract::ws::Message;
+use tokio::sync::{Mutex, mpsc};
+use uuid::Uuid;
+
+use verplant::{City, GameMessage, RuleSet, SubwayMap};
+use verplant_server::accounts::Accounts;
+use verplant_server::config::Config;
+use verplant_server::storage::Storage;
+use verplant_server::{GameServer, GameSession, PlayerConnection, RoundTimer, WebSocketSender};
+
+pub const UNTIMED: RoundTimer = RoundTimer { round_timeout: None, time_bank: None };
+
+/// A fresh directory below the system's temp dir.
+pub fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("verplant-test-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// An Amsterdam room with the default rules, storing into a temp dir.
+pub fn session(timer: RoundTimer) -> GameSession {
+    let dir = temp_dir();
+    GameSession::new(
+        SubwayMap::builtin(&City::Amsterdam),
+        RuleSet::default(),
+        4,
+        timer,
+        true,
+        Arc::new(Storage::open(dir.clone()).unwrap()),
+        Arc::new(Accounts::open(&dir).unwrap()),
+    )
+}
+
+/// A server with the built-in maps, configured by `args` as if they were
+/// command line flags and storing into a temp dir.
+pub fn server(args: &[&str]) -> GameServer {
+    server_in(&temp_dir(), args)
+}
+
+/// The same, storing into `dir`, for tests that put data there first.
+pub fn server_in(dir: &Path, args: &[&str]) -> GameServer {
+    let config = Config::load_from(["verplant_server"].iter().chain(args).copied()).unwrap();
+    GameServer::new(config, HashMap::new(), Storage::open(dir.to_path_buf()).unwrap(), Accounts::open(dir).unwrap())
+}
+
+/// The server's end of a connection and the frames the client receives.
+pub fn connection() -> (Arc<Mutex<WebSocketSender>>, mpsc::UnboundedReceiver<Message>) {
+    let (frames, received) = mpsc::unbounded_channel();
+    (Arc::new(Mutex::new(WebSocketSender::new(frames))), received)
+}
+
+pub async fn join(session: &mut GameSession, name: &str) -> (Uuid, mpsc::UnboundedReceiver<Message>) {
+    let (sender, received) = connection();
+    let player = PlayerConnection { id: Uuid::new_v4(), sender, rejoin_token: Uuid::new_v4() };
+    let player_id = player.id;
+    session.add_player(player, name.to_string(), None).await.unwrap();
+    (player_id, received)
+}
+
+/// The messages that arrived since the last call.
+pub fn received(frames: &mut mpsc::UnboundedReceiver<Message>) -> Vec<GameMessage> {
+    let mut messages = Vec::new();
+    while let Ok(frame) = frames.try_recv() {
+        if let Message::Text(text) = frame {
+            messages.push(serde_json::from_str(&text).unwrap());
+        }
+    }
+    messages
+}
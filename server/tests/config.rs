@@ -0,0 +1,88 @@
+mod common;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use verplant::RulePreset;
+use verplant_server::config::Config;
+
+fn config_file(contents: &str) -> PathBuf {
+    let path = common::temp_dir().join("verplant.toml");
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn load(args: &[&str]) -> Result<Config, String> {
+    Config::load_from(std::iter::once("verplant_server").chain(args.iter().copied()))
+}
+
+// The environment is shared by every test in this file, so only this one
+// sets variables, and every other test passes the settings it checks as flags.
+#[test]
+fn flags_win_over_the_environment_which_wins_over_the_file() {
+    let config = load(&[]).unwrap();
+    assert_eq!(config.bind_address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.max_players, 6);
+    assert_eq!(config.round_timeout, Some(Duration::from_secs(60)));
+    assert_eq!(config.time_bank, None);
+    assert_eq!(config.rules, RulePreset::Classic);
+    
+    let file = config_file("port = 1000\nmax_players = 3\nround_timeout_secs = 5\nrules = \"family\"\n");
+    let file = file.to_str().unwrap();
+    let config = load(&["--config", file]).unwrap();
+    assert_eq!((config.port, config.max_players), (1000, 3));
+    
+    // SAFETY: No other test in this binary reads or writes these variables
+    unsafe {
+        std::env::set_var("VERPLANT_PORT", "2000");
+        std::env::set_var("VERPLANT_MAX_PLAYERS", "4");
+    }
+    let config = load(&["--config", file, "--port", "3000"]).unwrap();
+    assert_eq!(config.port, 3000);
+    assert_eq!(config.max_players, 4);
+    assert_eq!(config.round_timeout, Some(Duration::from_secs(5)));
+    assert_eq!(config.rules, RulePreset::Family);
+    assert_eq!(config.match_wait, Duration::from_secs(30));
+    
+    unsafe {
+        std::env::set_var("VERPLANT_MAX_PLAYERS", "lots");
+    }
+    assert!(load(&["--config", file]).is_err());
+    unsafe {
+        std::env::remove_var("VERPLANT_PORT");
+        std::env::remove_var("VERPLANT_MAX_PLAYERS");
+    }
+    
+    // A file value is checked like any other
+    let invalid = config_file("max_players = 0\n");
+    assert!(load(&["--config", invalid.to_str().unwrap()]).is_err());
+}
+
+#[test]
+fn zero_disables_the_timers() {
+    let config = load(&["--round-timeout-secs", "0", "--time-bank-secs", "45"]).unwrap();
+    assert_eq!(config.round_timeout, None);
+    assert_eq!(config.time_bank, Some(Duration::from_secs(45)));
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    assert!(load(&["--max-players", "0"]).is_err());
+    assert!(load(&["--max-players", "33"]).is_err());
+    assert!(load(&["--max-players", "4", "--min-match-players", "5"]).is_err());
+    assert!(load(&["--port", "eighty"]).is_err());
+    assert!(load(&["--bind-address", "localhost"]).is_err());
+    assert!(load(&["--rules", "chaos"]).is_err());
+    assert!(load(&["--log-level", "loud"]).is_err());
+    assert!(load(&["--maps-dir", "/does/not/exist"]).is_err());
+    assert!(load(&["--config", "/does/not/exist.toml"]).is_err());
+    
+    let unknown = config_file("prot = 8080\n");
+    assert!(load(&["--config", unknown.to_str().unwrap()]).is_err());
+    let mistyped = config_file("port = \"8080\"\n");
+    assert!(load(&["--config", mistyped.to_str().unwrap()]).is_err());
+    let unknown_rules = config_file("rules = \"chaos\"\n");
+    assert!(load(&["--config", unknown_rules.to_str().unwrap()]).is_err());
+}
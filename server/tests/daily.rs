@@ -0,0 +1,52 @@
+mod common;
+
+use uuid::Uuid;
+
+use verplant::{DailyEntry, Replay};
+use verplant_server::storage::Storage;
+
+const DATE: &str = "2025-06-01";
+
+fn entry(player_name: &str, score: i32) -> DailyEntry {
+    sent_by(player_name, player_name, score)
+}
+
+fn sent_by(submitter: &str, player_name: &str, score: i32) -> DailyEntry {
+    DailyEntry {
+        id: Uuid::new_v4(),
+        player_name: player_name.to_string(),
+        score,
+        submitted_at: 0,
+        replay: Replay::default(),
+        submitter: submitter.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn entries_are_ranked_best_first() {
+    let storage = Storage::open(common::temp_dir()).unwrap();
+    
+    assert_eq!(storage.add_daily_entry(DATE, entry("Ann", 10), 3).await.unwrap(), Some(1));
+    assert_eq!(storage.add_daily_entry(DATE, entry("Bob", 20), 3).await.unwrap(), Some(1));
+    // Equal scores keep the order they came in
+    assert_eq!(storage.add_daily_entry(DATE, entry("Cem", 10), 3).await.unwrap(), Some(3));
+    
+    let names: Vec<String> = storage.load_daily(DATE).await.into_iter().map(|entry| entry.player_name).collect();
+    assert_eq!(names, ["Bob", "Ann", "Cem"]);
+    assert!(storage.load_daily("2025-06-02").await.is_empty());
+}
+
+#[tokio::test]
+async fn submitters_have_a_limited_number_of_entries_per_day() {
+    let storage = Storage::open(common::temp_dir()).unwrap();
+    
+    assert!(storage.add_daily_entry(DATE, sent_by("account:1", "Ann", 10), 2).await.unwrap().is_some());
+    assert!(storage.add_daily_entry(DATE, sent_by("account:1", "Bob", 12), 2).await.unwrap().is_some());
+    assert_eq!(storage.add_daily_entry(DATE, sent_by("account:1", "Cem", 30), 2).await.unwrap(), None);
+    
+    // Scores under someone else's name don't use up their entries
+    assert!(storage.add_daily_entry(DATE, sent_by("address:10.0.0.2", "Ann", 5), 2).await.unwrap().is_some());
+    assert!(storage.add_daily_entry(DATE, sent_by("address:10.0.0.3", "Ann", 6), 2).await.unwrap().is_some());
+    
+    assert_eq!(storage.load_daily(DATE).await.len(), 4);
+}
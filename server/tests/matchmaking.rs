@@ -0,0 +1,179 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use verplant::{City, DEFAULT_RATING, MatchSettings};
+use verplant_server::accounts::{Account, Accounts};
+use verplant_server::matchmaking::{Matchmaker, SeatSlot, Ticket};
+
+fn settings() -> MatchSettings {
+    MatchSettings {
+        min_players: 2,
+        max_players: 4,
+        max_wait: Duration::from_secs(30),
+        rating_window: 100.0,
+        window_growth: 10.0,
+    }
+}
+
+fn ticket(name: &str, city: City, account: Option<Account>, queued_at: Instant) -> Ticket {
+    Ticket {
+        id: Uuid::new_v4(),
+        player_name: name.to_string(),
+        city,
+        account,
+        sender: common::connection().0,
+        seat: Default::default(),
+        queued_at,
+    }
+}
+
+#[test]
+fn tickets_wait_per_city_until_they_leave() {
+    let now = Instant::now();
+    let mut matchmaker = Matchmaker::default();
+    let ann = ticket("Ann", City::Amsterdam, None, now);
+    let ann_id = ann.id;
+    matchmaker.join(ann);
+    matchmaker.join(ticket("Bob", City::Berlin, None, now));
+    
+    assert_eq!(matchmaker.waiting(&City::Amsterdam), 1);
+    assert_eq!(matchmaker.waiting(&City::Paris), 0);
+    
+    assert_eq!(matchmaker.leave(ann_id).unwrap().player_name, "Ann");
+    assert!(matchmaker.leave(ann_id).is_none());
+    assert_eq!(matchmaker.waiting(&City::Amsterdam), 0);
+}
+
+#[test]
+fn matched_tickets_leave_the_queue() {
+    let now = Instant::now();
+    let mut matchmaker = Matchmaker::default();
+    matchmaker.join(ticket("Ann", City::Amsterdam, None, now));
+    matchmaker.join(ticket("Bob", City::Berlin, None, now));
+    assert!(matchmaker.take_matches(now, &settings()).is_empty());
+    
+    matchmaker.join(ticket("Cat", City::Amsterdam, None, now));
+    let groups = matchmaker.take_matches(now, &settings());
+    let names: Vec<Vec<&str>> = groups.iter()
+        .map(|group| group.iter().map(|ticket| ticket.player_name.as_str()).collect())
+        .collect();
+    assert_eq!(names, vec![vec!["Ann", "Cat"]]);
+    assert_eq!(matchmaker.waiting(&City::Amsterdam), 0);
+    assert_eq!(matchmaker.waiting(&City::Berlin), 1);
+    
+    // Bob gets a room alone after waiting long enough
+    let later = now + settings().max_wait;
+    assert_eq!(matchmaker.take_matches(later, &settings()).len(), 1);
+    assert_eq!(matchmaker.waiting(&City::Berlin), 0);
+}
+
+#[tokio::test]
+async fn a_connection_cannot_queue_again_before_picking_up_its_room() {
+    let server = common::server(&["--min-match-players", "1"]);
+    let (sender, _frames) = common::connection();
+    let connection_id = Uuid::new_v4();
+    let seat: SeatSlot = Default::default();
+    let join = || Ticket {
+        id: connection_id,
+        player_name: "Ann".to_string(),
+        city: City::Amsterdam,
+        account: None,
+        sender: sender.clone(),
+        seat: seat.clone(),
+        queued_at: Instant::now(),
+    };
+    
+    // Matched at once, so the ticket is out of the queue but the seat not picked up
+    server.queue_player(join()).await.unwrap();
+    assert!(seat.lock().unwrap().taken.is_some());
+    assert!(server.queue_player(join()).await.is_err());
+    assert_eq!(server.session_count().await, 1);
+    
+    // Once the connection has its room it is no longer waiting
+    assert!(seat.lock().unwrap().pick_up().is_some());
+    assert!(!seat.lock().unwrap().waiting);
+}
+
+fn queued(name: &str, seat: &SeatSlot) -> Ticket {
+    Ticket {
+        seat: seat.clone(),
+        ..ticket(name, City::Amsterdam, None, Instant::now())
+    }
+}
+
+#[tokio::test]
+async fn groups_wait_in_the_queue_while_the_server_is_full() {
+    let server = common::server(&["--min-match-players", "1", "--max-rooms", "1"]);
+    let (ann, bob): (SeatSlot, SeatSlot) = Default::default();
+    
+    server.queue_player(queued("Ann", &ann)).await.unwrap();
+    assert!(ann.lock().unwrap().taken.is_some());
+    
+    server.queue_player(queued("Bob", &bob)).await.unwrap();
+    assert!(bob.lock().unwrap().taken.is_none());
+    assert!(bob.lock().unwrap().waiting);
+    assert_eq!(server.session_count().await, 1);
+}
+
+#[tokio::test]
+async fn connections_that_left_the_queue_get_no_room() {
+    let server = common::server(&["--min-match-players", "1"]);
+    let seat: SeatSlot = Default::default();
+    seat.lock().unwrap().closed = true;
+    
+    server.queue_player(queued("Ann", &seat)).await.unwrap();
+    assert!(seat.lock().unwrap().taken.is_none());
+    assert_eq!(server.session_count().await, 0);
+}
+
+#[tokio::test]
+async fn tickets_are_matched_by_account_rating() {
+    let accounts = Accounts::open(&common::temp_dir()).unwrap();
+    let (mut strong, _) = accounts.register("Ann", "correct horse").await.unwrap();
+    strong.rating = DEFAULT_RATING + 400.0;
+    
+    let now = Instant::now();
+    let mut matchmaker = Matchmaker::default();
+    matchmaker.join(ticket("Ann", City::Paris, Some(strong), now));
+    matchmaker.join(ticket("Bob", City::Paris, None, now));
+    assert!(matchmaker.take_matches(now, &settings()).is_empty());
+    
+    matchmaker.join(ticket("Cat", City::Paris, None, now));
+    let groups = matchmaker.take_matches(now, &settings());
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].iter().map(|ticket| ticket.player_name.as_str()).collect::<Vec<_>>(), ["Bob", "Cat"]);
+    assert_eq!(matchmaker.waiting(&City::Paris), 1);
+}
+
+#[tokio::test]
+async fn results_move_the_ratings_of_accounts() {
+    let dir = common::temp_dir();
+    let accounts = Accounts::open(&dir).unwrap();
+    let (ann, _) = accounts.register("Ann", "correct horse").await.unwrap();
+    let (bob, _) = accounts.register("Bob", "correct horse").await.unwrap();
+    
+    accounts.record_results(&[(ann.id, 30), (bob.id, 20), (Uuid::new_v4(), 40)]).await.unwrap();
+    let ann = accounts.find("Ann").await.unwrap();
+    let bob = accounts.find("Bob").await.unwrap();
+    assert!(ann.rating > DEFAULT_RATING);
+    assert!((ann.rating + bob.rating - 2.0 * DEFAULT_RATING).abs() < 1e-9);
+    assert_eq!((ann.rated_games, bob.rated_games), (1, 1));
+    
+    // Ratings are kept across restarts
+    let reopened = Accounts::open(&dir).unwrap();
+    assert_eq!(reopened.find("Ann").await.unwrap().rating, ann.rating);
+}
+
+#[tokio::test]
+async fn games_with_one_account_are_not_rated() {
+    let accounts = Accounts::open(&common::temp_dir()).unwrap();
+    let (ann, _) = accounts.register("Ann", "correct horse").await.unwrap();
+    
+    accounts.record_results(&[(ann.id, 30), (Uuid::new_v4(), 20)]).await.unwrap();
+    let ann = accounts.find("Ann").await.unwrap();
+    assert_eq!(ann.rating, DEFAULT_RATING);
+    assert_eq!(ann.rated_games, 0);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use uuid::Uuid;
+use verplant::{GameMessage, LineId, PlayerAction};
+use verplant_server::{PlayerConnection, play_action};
+
+use common::{UNTIMED, join, received, session};
+
+#[tokio::test]
+async fn a_resent_request_is_played_once() {
+    let mut session = session(UNTIMED);
+    let (ann, mut frames) = join(&mut session, "Ann").await;
+    join(&mut session, "Bob").await;
+    session.start_game().await.unwrap();
+    received(&mut frames);
+    
+    let action = session.game_state.default_action(ann).unwrap();
+    session.handle_player_action(ann, Some(7), action.clone()).await;
+    let first = received(&mut frames);
+    let checksum = session.game_state.checksum();
+    assert!(first.iter().any(|message| matches!(message, GameMessage::PlayerActionResult { request_id: Some(7), success: true, .. })));
+    
+    // As if the reply got lost and the client sent the move again
+    session.handle_player_action(ann, Some(7), action.clone()).await;
+    let second = received(&mut frames);
+    assert_eq!(session.game_state.checksum(), checksum);
+    let replies: Vec<&GameMessage> = first.iter()
+        .filter(|message| !matches!(message, GameMessage::StateDelta { .. }))
+        .collect();
+    assert_eq!(serde_json::to_string(&second).unwrap(), serde_json::to_string(&replies).unwrap());
+    
+    // The same move under a new id is a new request, and it fails
+    session.handle_player_action(ann, Some(8), action).await;
+    assert!(received(&mut frames).iter().any(|message| matches!(message, GameMessage::Error { request_id: Some(8), .. })));
+    assert_eq!(session.game_state.checksum(), checksum);
+}
+
+#[tokio::test]
+async fn failed_requests_can_be_retried() {
+    let mut session = session(UNTIMED);
+    let (ann, mut frames) = join(&mut session, "Ann").await;
+    
+    // Too early, there is no card yet
+    session.handle_player_action(ann, Some(3), PlayerAction::ChooseLine { line_id: LineId("blue".to_string()), car_window_index: 0 }).await;
+    assert!(received(&mut frames).iter().any(|message| matches!(message, GameMessage::Error { request_id: Some(3), .. })));
+    
+    session.start_game().await.unwrap();
+    let action = session.game_state.default_action(ann).unwrap();
+    session.handle_player_action(ann, Some(3), action).await;
+    assert!(received(&mut frames).iter().any(|message| matches!(message, GameMessage::PlayerActionResult { request_id: Some(3), success: true, .. })));
+}
+
+#[tokio::test]
+async fn a_move_resent_after_rejoining_is_played_once() {
+    let mut session = session(UNTIMED);
+    let (sender, _) = common::connection();
+    let rejoin_token = Uuid::new_v4();
+    let ann = Uuid::new_v4();
+    session.add_player(PlayerConnection { id: ann, sender, rejoin_token }, "Ann".to_string(), None).await.unwrap();
+    join(&mut session, "Bob").await;
+    session.start_game().await.unwrap();
+    
+    let action = session.game_state.default_action(ann).unwrap();
+    session.handle_player_action(ann, Some(5), action.clone()).await;
+    let checksum = session.game_state.checksum();
+    
+    let (sender, mut frames) = common::connection();
+    assert!(session.rejoin_player(ann, Uuid::new_v4(), sender.clone()).await.is_err());
+    session.rejoin_player(ann, rejoin_token, sender).await.unwrap();
+    
+    session.handle_player_action(ann, Some(5), action).await;
+    assert_eq!(session.game_state.checksum(), checksum);
+    assert!(received(&mut frames).iter().any(|message| matches!(message, GameMessage::PlayerActionResult { request_id: Some(5), success: true, .. })));
+}
+
+#[tokio::test]
+async fn a_move_without_a_game_is_answered() {
+    let (sender, mut frames) = common::connection();
+    let action = PlayerAction::ChooseLine { line_id: LineId("blue".to_string()), car_window_index: 0 };
+    
+    play_action(&sender, None, Some(4), action).await;
+    assert!(received(&mut frames).iter().any(|message| matches!(message, GameMessage::Error { request_id: Some(4), .. })));
+}
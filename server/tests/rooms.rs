@@ -0,0 +1,56 @@
+mod common;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+use verplant_server::throttle::IpThrottle;
+use verplant_server::{IDLE_SESSION_TIMEOUT, PlayerConnection};
+
+use common::{UNTIMED, join, session};
+
+#[test]
+fn the_throttle_counts_each_address_separately() {
+    let throttle = IpThrottle::new(2, Duration::from_secs(60));
+    let alice = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let bob = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+    let start = Instant::now();
+    
+    assert!(throttle.try_acquire(alice, start));
+    assert!(throttle.try_acquire(alice, start + Duration::from_secs(1)));
+    assert!(!throttle.try_acquire(alice, start + Duration::from_secs(2)));
+    assert!(throttle.try_acquire(bob, start + Duration::from_secs(2)));
+    
+    // The window slides, so the first request stops counting after a minute
+    assert!(throttle.try_acquire(alice, start + Duration::from_secs(60)));
+    assert!(!throttle.try_acquire(alice, start + Duration::from_secs(60)));
+}
+
+#[tokio::test]
+async fn empty_rooms_become_idle() {
+    let mut session = session(UNTIMED);
+    let start = Instant::now();
+    
+    assert_eq!(session.idle_time(start), Duration::ZERO);
+    assert!(session.idle_time(start + IDLE_SESSION_TIMEOUT) >= IDLE_SESSION_TIMEOUT);
+    
+    // Someone joining resets the clock
+    let (ann, _) = join(&mut session, "Ann").await;
+    assert_eq!(session.idle_time(start + IDLE_SESSION_TIMEOUT), Duration::ZERO);
+    
+    session.game_state.set_connected(&ann, false);
+    let later = start + 2 * IDLE_SESSION_TIMEOUT;
+    assert_eq!(session.idle_time(later), Duration::ZERO);
+    assert_eq!(session.idle_time(later + Duration::from_secs(60)), Duration::from_secs(60));
+}
+
+#[tokio::test]
+async fn names_are_unique_within_a_room() {
+    let mut session = session(UNTIMED);
+    join(&mut session, "Ann").await;
+    
+    let (sender, _) = common::connection();
+    let player = PlayerConnection { id: Uuid::new_v4(), sender, rejoin_token: Uuid::new_v4() };
+    assert!(session.add_player(player, "ANN".to_string(), None).await.is_err());
+    assert_eq!(session.game_state.roster.len(), 1);
+}
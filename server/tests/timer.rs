@@ -0,0 +1,75 @@
+mod common;
+
+use std::time::Duration;
+
+use verplant::GameMessage;
+use verplant_server::RoundTimer;
+
+use common::{join, received, session};
+
+const TIMED: RoundTimer = RoundTimer { round_timeout: Some(Duration::from_secs(60)), time_bank: None };
+
+#[tokio::test]
+async fn players_who_miss_the_deadline_get_a_default_move() {
+    let mut session = session(TIMED);
+    let (ann, mut ann_frames) = join(&mut session, "Ann").await;
+    let (bob, _) = join(&mut session, "Bob").await;
+    session.start_game().await.unwrap();
+    let started = session.round_started.unwrap();
+    let round = session.game_state.round;
+    
+    let action = session.game_state.default_action(ann).unwrap();
+    session.handle_player_action(ann, None, action).await;
+    
+    session.check_timeouts(started + Duration::from_secs(59)).await;
+    assert!(!session.game_state.has_acted(&bob));
+    assert_eq!(session.game_state.round, round);
+    
+    received(&mut ann_frames);
+    session.check_timeouts(started + Duration::from_secs(61)).await;
+    assert_eq!(session.game_state.round, round + 1);
+    assert!(received(&mut ann_frames).iter().any(|message| matches!(
+        message,
+        GameMessage::PlayerTimedOut { player_id, action: Some(_) } if *player_id == bob
+    )));
+}
+
+#[tokio::test]
+async fn untimed_rounds_wait_for_everyone() {
+    let mut session = session(common::UNTIMED);
+    let (ann, _) = join(&mut session, "Ann").await;
+    session.start_game().await.unwrap();
+    let started = session.round_started.unwrap();
+    
+    assert_eq!(session.player_deadline(&ann), None);
+    session.check_timeouts(started + Duration::from_secs(3600)).await;
+    assert!(!session.game_state.has_acted(&ann));
+}
+
+#[tokio::test]
+async fn time_banks_pay_for_overtime() {
+    let timer = RoundTimer { round_timeout: Some(Duration::from_secs(10)), time_bank: Some(Duration::from_secs(30)) };
+    let mut session = session(timer);
+    let (ann, _) = join(&mut session, "Ann").await;
+    let (bob, _) = join(&mut session, "Bob").await;
+    session.start_game().await.unwrap();
+    let started = session.round_started.unwrap();
+    assert_eq!(session.player_deadline(&bob), Some(started + Duration::from_secs(40)));
+    
+    // Ann took 25 seconds, 15 of them from her bank
+    session.charge_time_bank(ann, started + Duration::from_secs(25));
+    assert_eq!(session.player_deadline(&ann), Some(started + Duration::from_secs(25)));
+    let action = session.game_state.default_action(ann).unwrap();
+    session.handle_player_action(ann, None, action).await;
+    
+    // Bob is still within his bank, then runs out of it
+    let round = session.game_state.round;
+    session.check_timeouts(started + Duration::from_secs(39)).await;
+    assert_eq!(session.game_state.round, round);
+    session.check_timeouts(started + Duration::from_secs(41)).await;
+    assert_eq!(session.game_state.round, round + 1);
+    
+    let next_round = session.round_started.unwrap();
+    assert_eq!(session.player_deadline(&bob), Some(next_round + Duration::from_secs(10)));
+    assert_eq!(session.player_deadline(&ann), Some(next_round + Duration::from_secs(25)));
+}
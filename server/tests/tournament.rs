@@ -0,0 +1,63 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use verplant_server::{IDLE_SESSION_TIMEOUT, IDLE_TABLE_TIMEOUT, PlayerConnection};
+use verplant_server::accounts::{Account, Accounts};
+
+fn player() -> PlayerConnection {
+    PlayerConnection { id: Uuid::new_v4(), sender: common::connection().0, rejoin_token: Uuid::new_v4() }
+}
+
+async fn accounts(names: &[&str]) -> Vec<Account> {
+    let accounts = Accounts::open(&common::temp_dir()).unwrap();
+    let mut registered = Vec::new();
+    for name in names {
+        registered.push(accounts.register(name, "correct horse").await.unwrap().0);
+    }
+    registered
+}
+
+#[tokio::test]
+async fn tables_only_seat_their_accounts() {
+    let accounts = accounts(&["Ann", "Bob", "Cem"]).await;
+    let mut session = common::session(common::UNTIMED);
+    session.reserve_for_tournament(Uuid::new_v4(), vec![(accounts[0].id, "Ann".to_string()), (accounts[1].id, "Bob".to_string())]);
+    
+    // The name alone isn't enough
+    assert!(session.add_player(player(), "Ann".to_string(), None).await.is_err());
+    assert!(session.add_player(player(), "Ann".to_string(), Some(&accounts[2])).await.is_err());
+    
+    // Players sit down under their tournament name, whatever they typed
+    session.add_player(player(), "Annie".to_string(), Some(&accounts[0])).await.unwrap();
+    assert_eq!(session.game_state.roster[0].name, "Ann");
+    assert!(session.add_player(player(), "Ann".to_string(), Some(&accounts[0])).await.is_err());
+}
+
+#[tokio::test]
+async fn tables_start_once_everyone_joined() {
+    let accounts = accounts(&["Ann", "Bob"]).await;
+    let mut session = common::session(common::UNTIMED);
+    session.reserve_for_tournament(Uuid::new_v4(), vec![(accounts[0].id, "Ann".to_string()), (accounts[1].id, "Bob".to_string())]);
+    
+    session.add_player(player(), "Ann".to_string(), Some(&accounts[0])).await.unwrap();
+    assert!(session.start_game().await.is_err());
+    
+    session.add_player(player(), "Bob".to_string(), Some(&accounts[1])).await.unwrap();
+    session.start_game().await.unwrap();
+}
+
+#[tokio::test]
+async fn tables_wait_longer_for_their_players() {
+    let mut session = common::session(common::UNTIMED);
+    session.reserve_for_tournament(Uuid::new_v4(), vec![(Uuid::new_v4(), "Ann".to_string())]);
+    assert!(IDLE_TABLE_TIMEOUT > IDLE_SESSION_TIMEOUT);
+    assert_eq!(session.idle_timeout(), IDLE_TABLE_TIMEOUT);
+    
+    // Nobody sat down, so the clock runs like for any other room
+    let start = Instant::now();
+    assert_eq!(session.idle_time(start), Duration::ZERO);
+    assert!(session.idle_time(start + IDLE_TABLE_TIMEOUT) >= session.idle_timeout());
+}
@@ -0,0 +1,73 @@
+//! Compares JSON and MessagePack for the messages the server sends most:
+//! full snapshots on join and the deltas after every move.
+//!
+//! Run with `cargo bench -p verplant --bench wire`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use uuid::Uuid;
+use verplant::wire::{self, WireFormat};
+use verplant::{City, GameMessage, GameState, GameStateView, SubwayMap};
+
+const PLAYERS: usize = 6;
+const ROUNDS: usize = 6;
+
+/// A snapshot of a six player game a few rounds in, and the delta of its last round.
+fn typical_messages() -> Vec<(&'static str, GameMessage)> {
+    let subway_map = SubwayMap::builtin(&City::Amsterdam);
+    let player_ids: Vec<Uuid> = (0..PLAYERS).map(|_| Uuid::new_v4()).collect();
+    
+    let mut game_state = GameState::new(City::Amsterdam, player_ids[0]);
+    for (index, player_id) in player_ids.iter().enumerate() {
+        game_state.add_player(*player_id, format!("Player {}", index), &subway_map);
+    }
+    
+    let mut previous_view = game_state.public_view(&subway_map);
+    for _ in 0..ROUNDS {
+        previous_view = game_state.public_view(&subway_map);
+        if game_state.reveal_card().is_none() {
+            break;
+        }
+        for player_id in &player_ids {
+            let played = game_state.default_action(*player_id)
+                .is_some_and(|action| game_state.process_player_action(*player_id, action, &subway_map).is_ok());
+            if !played {
+                game_state.pass(*player_id);
+            }
+        }
+        game_state.next_round().unwrap();
+    }
+    
+    let view = GameStateView { viewer: Some(player_ids[0]), ..game_state.public_view(&subway_map) };
+    vec![
+        ("snapshot", GameMessage::StateSnapshot {
+            version: ROUNDS as u64,
+            checksum: view.checksum(),
+            state: view.clone(),
+        }),
+        ("delta", GameMessage::StateDelta {
+            version: ROUNDS as u64,
+            checksum: view.checksum(),
+            patches: previous_view.diff(&view),
+        }),
+    ]
+}
+
+fn wire_formats(c: &mut Criterion) {
+    for (name, message) in typical_messages() {
+        for format in [WireFormat::Json, WireFormat::MessagePack] {
+            let bytes = wire::encode(&message, format).unwrap();
+            println!("{} as {:?}: {} bytes", name, format, bytes.len());
+            
+            c.bench_function(&format!("encode {} {:?}", name, format), |b| {
+                b.iter(|| wire::encode(black_box(&message), format).unwrap())
+            });
+            c.bench_function(&format!("decode {} {:?}", name, format), |b| {
+                b.iter(|| wire::decode::<GameMessage>(black_box(&bytes), format).unwrap())
+            });
+        }
+    }
+}
+
+criterion_group!(benches, wire_formats);
+criterion_main!(benches);
@@ -0,0 +1,178 @@
+use crate::validation::check_markable;
+use crate::{ActionError, Card, ExpressRule, LineId, PlayerAction, PlayerSheet, RuleSet, StationMark, SubwayMap};
+
+/// What a card does when it is played. Every `Card` has one, see `Card::effect`,
+/// so a new card only needs a variant and an implementation of this trait.
+pub trait CardEffect {
+    /// Written into the train car window when the card is played on a line.
+    fn window_symbol(&self) -> String;
+    
+    /// How often each player uses the card before they are done for the round.
+    fn plays_per_round(&self) -> u8 {
+        1
+    }
+    
+    /// Marks stations once the card went into a window of `line_id`, returns
+    /// the stations it marked.
+    fn mark_line(&self, _sheet: &mut PlayerSheet, _line_id: &LineId, _rules: &RuleSet, _subway_map: &SubwayMap) -> Vec<String> {
+        Vec::new()
+    }
+    
+    /// Checks an action on a single station, like `MarkTransferStation`. Cards
+    /// refuse these unless they override this.
+    fn validate_station_action(&self, action: &PlayerAction, _player: &PlayerSheet, _subway_map: &SubwayMap) -> Result<(), ActionError> {
+        Err(wrong_card(action))
+    }
+}
+
+impl Card {
+    pub fn effect(&self) -> Box<dyn CardEffect> {
+        match self {
+            Card::Number(value) => Box::new(Numbered { value: *value, express: false }),
+            Card::Six => Box::new(Numbered { value: 6, express: false }),
+            Card::Express(value) => Box::new(Numbered { value: *value, express: true }),
+            Card::Transfer => Box::new(Transfer),
+            Card::FreeRide => Box::new(FreeRide),
+            Card::Delay => Box::new(Delay),
+            Card::Double(value) => Box::new(Double(*value)),
+            Card::Closure => Box::new(Closure),
+        }
+    }
+}
+
+/// Marks `value` stations from the start of the line. Express cards may skip
+/// stations that are already marked, depending on the rules.
+struct Numbered {
+    value: u8,
+    express: bool,
+}
+
+impl CardEffect for Numbered {
+    fn window_symbol(&self) -> String {
+        self.value.to_string()
+    }
+    
+    fn mark_line(&self, sheet: &mut PlayerSheet, line_id: &LineId, rules: &RuleSet, subway_map: &SubwayMap) -> Vec<String> {
+        let skip_marked = self.express && rules.express == ExpressRule::SkipMarked;
+        mark_crosses(sheet, line_id, self.value, skip_marked, subway_map)
+    }
+}
+
+/// On a line it marks the next empty station with its number of lines,
+/// otherwise any transfer station.
+struct Transfer;
+
+impl CardEffect for Transfer {
+    fn window_symbol(&self) -> String {
+        "+".to_string()
+    }
+    
+    fn mark_line(&self, sheet: &mut PlayerSheet, line_id: &LineId, _rules: &RuleSet, subway_map: &SubwayMap) -> Vec<String> {
+        let Some(station) = sheet.find_next_empty_station(line_id, subway_map).ok().flatten()
+            .and_then(|station_id| subway_map.stations.get(&station_id))
+        else {
+            return Vec::new();
+        };
+        
+        sheet.marked_stations.insert(station.id.clone(), StationMark::TransferNumber(station.lines.len() as u8));
+        vec![station.id.clone()]
+    }
+    
+    fn validate_station_action(&self, action: &PlayerAction, player: &PlayerSheet, subway_map: &SubwayMap) -> Result<(), ActionError> {
+        let PlayerAction::MarkTransferStation { station_id } = action else {
+            return Err(wrong_card(action));
+        };
+        check_markable(player, station_id, subway_map)?;
+        
+        if !subway_map.stations[station_id].is_transfer_station() {
+            return Err(ActionError::NotATransferStation(station_id.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Marks any one station. Played on a line it marks nothing.
+struct FreeRide;
+
+impl CardEffect for FreeRide {
+    fn window_symbol(&self) -> String {
+        "0".to_string()
+    }
+    
+    fn validate_station_action(&self, action: &PlayerAction, player: &PlayerSheet, subway_map: &SubwayMap) -> Result<(), ActionError> {
+        let PlayerAction::MarkFreeRideStation { station_id } = action else {
+            return Err(wrong_card(action));
+        };
+        check_markable(player, station_id, subway_map)
+    }
+}
+
+/// Uses up a window without marking anything.
+struct Delay;
+
+impl CardEffect for Delay {
+    fn window_symbol(&self) -> String {
+        "-".to_string()
+    }
+}
+
+/// A number card that every player plays twice.
+struct Double(u8);
+
+impl CardEffect for Double {
+    fn window_symbol(&self) -> String {
+        self.0.to_string()
+    }
+    
+    fn plays_per_round(&self) -> u8 {
+        2
+    }
+    
+    fn mark_line(&self, sheet: &mut PlayerSheet, line_id: &LineId, _rules: &RuleSet, subway_map: &SubwayMap) -> Vec<String> {
+        mark_crosses(sheet, line_id, self.0, false, subway_map)
+    }
+}
+
+/// Closes a station on every sheet that hasn't marked it by the end of the
+/// round. Played on a line it marks nothing.
+struct Closure;
+
+impl CardEffect for Closure {
+    fn window_symbol(&self) -> String {
+        "X".to_string()
+    }
+    
+    fn validate_station_action(&self, action: &PlayerAction, player: &PlayerSheet, subway_map: &SubwayMap) -> Result<(), ActionError> {
+        let PlayerAction::CloseStation { station_id } = action else {
+            return Err(wrong_card(action));
+        };
+        if !subway_map.stations.contains_key(station_id) {
+            return Err(ActionError::UnknownStation(station_id.clone()));
+        }
+        if player.marked_stations.contains_key(station_id) {
+            return Err(ActionError::StationAlreadyMarked(station_id.clone()));
+        }
+        if player.closed_stations.contains(station_id) {
+            return Err(ActionError::StationClosed(station_id.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// The card a station action needs.
+fn wrong_card(action: &PlayerAction) -> ActionError {
+    let required = match action {
+        PlayerAction::MarkTransferStation { .. } => Card::Transfer,
+        PlayerAction::CloseStation { .. } => Card::Closure,
+        _ => Card::FreeRide,
+    };
+    ActionError::WrongCard { required }
+}
+
+fn mark_crosses(sheet: &mut PlayerSheet, line_id: &LineId, value: u8, skip_marked: bool, subway_map: &SubwayMap) -> Vec<String> {
+    let stations_to_mark = sheet.find_stations_to_mark(line_id, value, skip_marked, subway_map).unwrap_or_default();
+    for station_id in &stations_to_mark {
+        sheet.marked_stations.insert(station_id.clone(), StationMark::Cross);
+    }
+    stations_to_mark
+}
@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::{
+    Card, City, CompletionStatus, DeckConfig, Expansion, ExpressRule, GameEndRule, GamePhase, GameState, GameStateView, LineId,
+    PendingCompletion, PlayerInfo, PlayerSheet, RuleSet, StationMark,
+};
+
+/// 64-bit FNV-1a. Written out instead of using `DefaultHasher` so that the
+/// server and the wasm client always agree, whatever their pointer width or
+/// Rust version.
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn new() -> Self {
+        StateHasher(0xcbf2_9ce4_8422_2325)
+    }
+    
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Feeds a value into a `StateHasher`. Maps and sets are fed in key order so
+/// the result does not depend on `HashMap` iteration order.
+trait Checksum {
+    fn feed(&self, hasher: &mut StateHasher);
+}
+
+impl Checksum for u64 {
+    fn feed(&self, hasher: &mut StateHasher) {
+        hasher.write(&self.to_le_bytes());
+    }
+}
+
+impl Checksum for usize {
+    fn feed(&self, hasher: &mut StateHasher) {
+        (*self as u64).feed(hasher);
+    }
+}
+
+impl Checksum for u8 {
+    fn feed(&self, hasher: &mut StateHasher) {
+        hasher.write(&[*self]);
+    }
+}
+
+impl Checksum for u32 {
+    fn feed(&self, hasher: &mut StateHasher) {
+        hasher.write(&self.to_le_bytes());
+    }
+}
+
+impl Checksum for i32 {
+    fn feed(&self, hasher: &mut StateHasher) {
+        hasher.write(&self.to_le_bytes());
+    }
+}
+
+impl Checksum for bool {
+    fn feed(&self, hasher: &mut StateHasher) {
+        hasher.write(&[*self as u8]);
+    }
+}
+
+impl Checksum for str {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.len().feed(hasher);
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl Checksum for String {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.as_str().feed(hasher);
+    }
+}
+
+impl Checksum for Uuid {
+    fn feed(&self, hasher: &mut StateHasher) {
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl<T: Checksum> Checksum for Option<T> {
+    fn feed(&self, hasher: &mut StateHasher) {
+        match self {
+            Some(value) => {
+                hasher.write(&[1]);
+                value.feed(hasher);
+            },
+            None => hasher.write(&[0]),
+        }
+    }
+}
+
+impl<T: Checksum> Checksum for [T] {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.len().feed(hasher);
+        for value in self {
+            value.feed(hasher);
+        }
+    }
+}
+
+impl<T: Checksum> Checksum for Vec<T> {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.as_slice().feed(hasher);
+    }
+}
+
+impl<K: Checksum + Ord, V: Checksum> Checksum for HashMap<K, V> {
+    fn feed(&self, hasher: &mut StateHasher) {
+        let mut entries: Vec<(&K, &V)> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        
+        entries.len().feed(hasher);
+        for (key, value) in entries {
+            key.feed(hasher);
+            value.feed(hasher);
+        }
+    }
+}
+
+impl<T: Checksum + Ord> Checksum for HashSet<T> {
+    fn feed(&self, hasher: &mut StateHasher) {
+        let mut values: Vec<&T> = self.iter().collect();
+        values.sort();
+        
+        values.len().feed(hasher);
+        for value in values {
+            value.feed(hasher);
+        }
+    }
+}
+
+impl Checksum for LineId {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.0.feed(hasher);
+    }
+}
+
+impl Checksum for City {
+    fn feed(&self, hasher: &mut StateHasher) {
+        let index = City::ALL.iter().position(|city| city == self).unwrap_or(usize::MAX);
+        index.feed(hasher);
+    }
+}
+
+impl Checksum for Card {
+    fn feed(&self, hasher: &mut StateHasher) {
+        let (tag, value) = match self {
+            Card::Number(value) => (0, *value),
+            Card::Six => (1, 0),
+            Card::Express(value) => (2, *value),
+            Card::Transfer => (3, 0),
+            Card::FreeRide => (4, 0),
+            Card::Delay => (5, 0),
+            Card::Double(value) => (6, *value),
+            Card::Closure => (7, 0),
+        };
+        hasher.write(&[tag, value]);
+    }
+}
+
+impl Checksum for GamePhase {
+    fn feed(&self, hasher: &mut StateHasher) {
+        let tag = match self {
+            GamePhase::Lobby => 0,
+            GamePhase::WaitingForActions => 1,
+            GamePhase::RoundOver => 2,
+            GamePhase::Finished => 3,
+            GamePhase::ResolvingCardSix => 4,
+        };
+        hasher.write(&[tag]);
+    }
+}
+
+impl Checksum for StationMark {
+    fn feed(&self, hasher: &mut StateHasher) {
+        match self {
+            StationMark::Cross => hasher.write(&[0, 0]),
+            StationMark::TransferNumber(lines) => hasher.write(&[1, *lines]),
+        }
+    }
+}
+
+impl Checksum for CompletionStatus {
+    fn feed(&self, hasher: &mut StateHasher) {
+        match self {
+            CompletionStatus::FirstToComplete(points) => hasher.write(&[0, *points]),
+            CompletionStatus::LaterCompletion(points) => hasher.write(&[1, *points]),
+            CompletionStatus::NotCompleted => hasher.write(&[2, 0]),
+        }
+    }
+}
+
+impl Checksum for PlayerSheet {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.player_id.feed(hasher);
+        self.city.feed(hasher);
+        self.train_cars.feed(hasher);
+        self.marked_stations.feed(hasher);
+        self.completed_lines.feed(hasher);
+        self.line_completion_status.feed(hasher);
+        self.closed_stations.feed(hasher);
+    }
+}
+
+impl Checksum for PlayerInfo {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.id.feed(hasher);
+        self.name.feed(hasher);
+        self.color.feed(hasher);
+        self.connected.feed(hasher);
+        self.is_bot.feed(hasher);
+        self.is_conductor.feed(hasher);
+    }
+}
+
+impl Checksum for DeckConfig {
+    fn feed(&self, hasher: &mut StateHasher) {
+        hasher.write(&[self.number_copies, self.sixes, self.transfers, self.free_rides]);
+        self.express.feed(hasher);
+    }
+}
+
+impl Checksum for RuleSet {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.deck.feed(hasher);
+        match self.express {
+            ExpressRule::SkipMarked => hasher.write(&[0]),
+            ExpressRule::StopAtMarked => hasher.write(&[1]),
+        }
+        self.empty_station_penalty_divisor.feed(hasher);
+        self.double_transfers.feed(hasher);
+        match self.game_end {
+            GameEndRule::AllSheetsFull => hasher.write(&[0]),
+            GameEndRule::FirstSheetFull => hasher.write(&[1]),
+            GameEndRule::Rounds(rounds) => {
+                hasher.write(&[2]);
+                rounds.feed(hasher);
+            },
+        }
+        self.expansions.len().feed(hasher);
+        for expansion in &self.expansions {
+            match expansion {
+                Expansion::Delay => hasher.write(&[0]),
+                Expansion::Double => hasher.write(&[1]),
+                Expansion::Closure => hasher.write(&[2]),
+            }
+        }
+    }
+}
+
+impl Checksum for PendingCompletion {
+    fn feed(&self, hasher: &mut StateHasher) {
+        self.player_id.feed(hasher);
+        self.line_id.feed(hasher);
+        hasher.write(&[self.points.0, self.points.1]);
+    }
+}
+
+impl GameState {
+    /// A hash of the whole state, including the order of the deck.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        
+        self.id.feed(&mut hasher);
+        self.city.feed(&mut hasher);
+        self.players.feed(&mut hasher);
+        self.current_card.feed(&mut hasher);
+        self.deck.feed(&mut hasher);
+        self.discard_pile.feed(&mut hasher);
+        self.round.feed(&mut hasher);
+        self.phase.feed(&mut hasher);
+        self.conductor.feed(&mut hasher);
+        self.acted_this_round.feed(&mut hasher);
+        self.plays_this_round.feed(&mut hasher);
+        self.roster.feed(&mut hasher);
+        self.pending_completions.feed(&mut hasher);
+        self.pending_closures.feed(&mut hasher);
+        self.rng_state.feed(&mut hasher);
+        self.rules.feed(&mut hasher);
+        
+        hasher.0
+    }
+}
+
+impl GameStateView {
+    /// A hash of everything in the view except the viewer, so every client of a
+    /// game should arrive at the same value for the same version.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        
+        self.id.feed(&mut hasher);
+        self.city.feed(&mut hasher);
+        self.players.feed(&mut hasher);
+        self.scores.feed(&mut hasher);
+        self.roster.feed(&mut hasher);
+        self.current_card.feed(&mut hasher);
+        self.deck_count.feed(&mut hasher);
+        self.discard_pile.feed(&mut hasher);
+        self.round.feed(&mut hasher);
+        self.phase.feed(&mut hasher);
+        self.conductor.feed(&mut hasher);
+        self.acted_this_round.feed(&mut hasher);
+        
+        hasher.0
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{City, Replay};
+
+/// The solo game everyone plays on a given day: the same map and the same seed,
+/// so the same cards in the same order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DailyChallenge {
+    pub date: String, // YYYY-MM-DD, in UTC
+    pub city: City,
+    pub seed: u64,
+}
+
+impl DailyChallenge {
+    /// The challenge of the day `day` days after 1970-01-01.
+    pub fn for_day(day: u64) -> DailyChallenge {
+        // SplitMix64, so neighbouring days get unrelated seeds
+        let mut seed = day.wrapping_add(0x9e3779b97f4a7c15);
+        seed = (seed ^ (seed >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        seed = (seed ^ (seed >> 27)).wrapping_mul(0x94d049bb133111eb);
+        seed ^= seed >> 31;
+        
+        DailyChallenge {
+            date: format_date(day),
+            city: City::ALL[(seed % City::ALL.len() as u64) as usize].clone(),
+            seed,
+        }
+    }
+    
+    pub fn for_date(date: &str) -> Result<DailyChallenge, String> {
+        parse_date(date).map(Self::for_day)
+    }
+    
+    pub fn today() -> DailyChallenge {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap();
+        Self::for_day(now.as_secs() / 86_400)
+    }
+    
+    /// Whether scores are taken at `now`, in Unix seconds: all through the
+    /// challenge's day and `grace_secs` after it, for games started before midnight.
+    pub fn accepts_scores_at(&self, now: u64, grace_secs: u64) -> bool {
+        let Ok(day) = parse_date(&self.date) else {
+            return false;
+        };
+        let start = day * 86_400;
+        (start..start + 86_400 + grace_secs).contains(&now)
+    }
+}
+
+/// A finished daily challenge sent in for the leaderboard. The score is checked
+/// by playing the replay again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DailySubmission {
+    pub player_name: String,
+    pub score: i32,
+    pub replay: Replay,
+}
+
+/// A verified score on a daily leaderboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DailyEntry {
+    pub id: Uuid,
+    pub player_name: String,
+    pub score: i32,
+    pub submitted_at: u64, // Unix time in milliseconds
+    pub replay: Replay,
+    #[serde(default)]
+    pub submitter: String, // Account, or address when logged out, that the entries per day are counted for
+}
+
+// Civil date conversions after Howard Hinnant's `days_from_civil`, for the
+// proleptic Gregorian calendar from 1970 on.
+
+fn format_date(day: u64) -> String {
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153; // March is 0
+    let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+fn parse_date(date: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid date {}, expected YYYY-MM-DD", date);
+    
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return Err(invalid());
+    }
+    let year: u64 = year.parse().map_err(|_| invalid())?;
+    let month: u64 = month.parse().map_err(|_| invalid())?;
+    let day: u64 = day.parse().map_err(|_| invalid())?;
+    if year < 1970 || !(1..=12).contains(&month) || day == 0 {
+        return Err(invalid());
+    }
+    
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).checked_sub(719_468).ok_or_else(invalid)?;
+    
+    // Catches days past the end of the month, like 2025-02-30
+    if format_date(days) != date {
+        return Err(invalid());
+    }
+    Ok(days)
+}
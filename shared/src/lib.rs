@@ -1,9 +1,36 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{DefaultHasher, Hash, Hasher};
 use uuid::Uuid;
 
+mod cards;
+mod checksum;
+mod daily;
+mod maps;
+mod matchmaking;
+mod rating;
+mod rules;
+mod solo;
+mod stats;
+mod tournament;
+mod validation;
+mod view;
+pub mod wire;
+
+pub use cards::CardEffect;
+pub use daily::{DailyChallenge, DailyEntry, DailySubmission};
+pub use matchmaking::{MatchSettings, QueuedPlayer, find_matches};
+pub use rating::{DEFAULT_RATING, RatedPlayer, update_ratings};
+pub use rules::{DeckConfig, Expansion, ExpressRule, GameEndRule, RulePreset, RuleSet};
+pub use solo::{Medal, SoloGame, SoloTargets};
+pub use stats::{LineCount, PlayerStats, StatsTally};
+pub use tournament::{RoundSeating, SeatingRule, Standing, TableResult, Tournament, TournamentRound, TournamentSettings, TournamentTable};
+pub use validation::ActionError;
+pub use view::{GameStateView, StatePatch};
+pub use wire::WireFormat;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum City {
     Amsterdam,
     Berlin,
@@ -11,19 +38,29 @@ pub enum City {
     Madrid,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl City {
+    pub const ALL: [City; 4] = [City::Amsterdam, City::Berlin, City::Paris, City::Madrid];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Card {
     Number(u8),         // Cards 1-5
     Six,                // Special card 6 (reshuffles deck)
     Express(u8),        // Express cards 2, 3, 4
     Transfer,           // Transfer opportunity card
     FreeRide,           // Free ride card
+    Delay,              // Expansion: fills a window without marking anything
+    Double(u8),         // Expansion: every player plays it twice
+    Closure,            // Expansion: closes a station for everyone
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LineId(pub String);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Station {
     pub id: String,
     pub x: f32,
@@ -33,6 +70,7 @@ pub struct Station {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SubwayLine {
     pub id: LineId,
     pub color: String,
@@ -42,6 +80,7 @@ pub struct SubwayLine {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SubwayMap {
     pub city: City,
     pub stations: HashMap<String, Station>,
@@ -49,7 +88,8 @@ pub struct SubwayMap {
     pub special_stations: Vec<String>, // Paris/Madrid special stations
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PlayerSheet {
     pub player_id: Uuid,
     pub city: City,
@@ -57,22 +97,108 @@ pub struct PlayerSheet {
     pub marked_stations: HashMap<String, StationMark>,
     pub completed_lines: Vec<LineId>,
     pub line_completion_status: HashMap<LineId, CompletionStatus>,
+    #[serde(default)]
+    pub closed_stations: HashSet<String>, // Closed by someone's closure card, can't be marked any more
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Public information about a player in a room, shared with everyone in it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlayerInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub color: String,
+    pub connected: bool,
+    pub is_bot: bool,
+    pub is_conductor: bool,
+}
+
+pub const MAX_PLAYER_NAME_LENGTH: usize = 24;
+
+const PLAYER_COLORS: [&str; 8] = [
+    "#E6194B", "#3CB44B", "#4363D8", "#F58231", "#911EB4", "#42D4F4", "#F032E6", "#9A6324",
+];
+
+/// Trims a display name and checks that it is non-empty, not too long and
+/// only made of letters, digits, spaces, `-` and `_`.
+pub fn validate_player_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    
+    if name.is_empty() {
+        return Err("Name must not be empty".to_string());
+    }
+    if name.chars().count() > MAX_PLAYER_NAME_LENGTH {
+        return Err(format!("Name must be at most {} characters", MAX_PLAYER_NAME_LENGTH));
+    }
+    if !name.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_') {
+        return Err("Name may only contain letters, digits, spaces, '-' and '_'".to_string());
+    }
+    
+    Ok(name.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum StationMark {
     Cross,
     TransferNumber(u8), // Number of connecting lines (doubled for scoring)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CompletionStatus {
     FirstToComplete(u8), // Points for being first
     LaterCompletion(u8), // Points for completing after others
     NotCompleted,
 }
 
+/// A line completed during the current round. It is scored when the round
+/// ends, see `GameState::next_round`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PendingCompletion {
+    pub player_id: Uuid,
+    pub line_id: LineId,
+    pub points: (u8, u8), // First and later completion points of the line
+}
+
+impl PendingCompletion {
+    fn new(player_id: Uuid, line_id: LineId, subway_map: &SubwayMap) -> Self {
+        let points = subway_map.lines.get(&line_id)
+            .map(|line| line.completion_points)
+            .unwrap_or_default();
+        PendingCompletion { player_id, line_id, points }
+    }
+}
+
+/// Where a game is in its lifecycle. Only the transitions allowed by
+/// [`GamePhase::can_become`] are ever taken.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum GamePhase {
+    #[default]
+    Lobby,             // Players are joining, no card revealed yet
+    WaitingForActions, // A card is revealed and players are choosing their moves
+    RoundOver,         // Every move is in, the next card has not been revealed
+    ResolvingCardSix,  // Like RoundOver, but the 6 took the discard pile back into the deck
+    Finished,
+}
+
+impl GamePhase {
+    pub fn can_become(self, next: GamePhase) -> bool {
+        use GamePhase::*;
+        
+        matches!(
+            (self, next),
+            (Lobby | RoundOver | ResolvingCardSix, WaitingForActions)
+                | (WaitingForActions, RoundOver | ResolvingCardSix)
+                | (WaitingForActions | RoundOver | ResolvingCardSix, Finished)
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GameState {
     pub id: Uuid,
     pub city: City,
@@ -81,69 +207,231 @@ pub struct GameState {
     pub deck: Vec<Card>,
     pub discard_pile: Vec<Card>,
     pub round: u32,
-    pub game_ended: bool,
+    #[serde(default)]
+    pub phase: GamePhase,
     pub conductor: Uuid, // Player who shuffles cards
+    #[serde(default)]
+    pub acted_this_round: HashSet<Uuid>, // Players who already used the current card
+    #[serde(default)]
+    pub plays_this_round: HashMap<Uuid, u8>, // How often each player used the current card
+    #[serde(default)]
+    pub roster: Vec<PlayerInfo>, // In joining order
+    #[serde(default)]
+    pub pending_completions: Vec<PendingCompletion>,
+    #[serde(default)]
+    pub pending_closures: Vec<String>, // Stations closed this round, applied when it ends
+    #[serde(default)]
+    pub rng_state: u64, // Seeds every shuffle, so the same seed deals the same game
+    #[serde(default)]
+    pub rules: RuleSet,
+}
+
+/// Everything needed to look at a finished game again: who played, how they
+/// scored and every card and move in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GameRecord {
+    pub game_id: Uuid,
+    pub city: City,
+    pub players: Vec<Uuid>,
+    #[serde(default)]
+    pub roster: Vec<PlayerInfo>,
+    pub scores: HashMap<Uuid, i32>,
+    pub finished_at: u64, // Unix time in milliseconds
+    pub replay: Replay,
+    #[serde(default)]
+    pub accounts: HashMap<Uuid, Uuid>, // Account of each player who was logged in
+    #[serde(default)]
+    pub completed_lines: HashMap<Uuid, Vec<LineId>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Replay {
+    pub rounds: Vec<ReplayRound>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReplayRound {
+    pub card: Card,
+    pub actions: Vec<ReplayAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReplayAction {
+    pub player_id: Uuid,
+    pub action: Option<PlayerAction>, // None if the player passed
+}
+
+impl Replay {
+    pub fn start_round(&mut self, card: Card) {
+        self.rounds.push(ReplayRound { card, actions: Vec::new() });
+    }
+    
+    pub fn record(&mut self, player_id: Uuid, action: Option<PlayerAction>) {
+        if let Some(round) = self.rounds.last_mut() {
+            round.actions.push(ReplayAction { player_id, action });
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum PlayerAction {
     ChooseLine { line_id: LineId, car_window_index: usize },
     MarkTransferStation { station_id: String },
     MarkFreeRideStation { station_id: String },
+    CloseStation { station_id: String },
     CompleteLineAnnouncement { line_id: LineId },
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Emote {
+    Clap,
+    Wow,
+    Laugh,
+    Sad,
+    Train,
+}
+
+/// What a reaction refers to, so clients can show it next to the event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ReactionTarget {
+    LineCompleted { player_id: Uuid, line_id: LineId },
+    Player(Uuid),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ChatEntry {
+    pub sender: Uuid,
+    pub text: String,
+    pub sent_at: u64, // Unix time in milliseconds
+    pub from_spectator: bool,
+}
+
+/// Why the discard pile went back into the deck.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ReshuffleReason {
+    CardSix,   // Everyone has played the 6, which is shuffled back in as well
+    DeckEmpty,
+}
+
+/// Version of the `GameMessage` protocol. Bump it whenever a message changes
+/// shape, and update the golden files in `shared/tests/golden`.
+pub const PROTOCOL_VERSION: u32 = 11;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ClientKind {
+    Web,
+    Bot,
+    Other,
+}
+
+/// Chosen by the client, unique per connection or seat. The server echoes it in
+/// the `PlayerActionResult` or `Error` caused by the request.
+pub type RequestId = u32;
+
+/// The envelope every client to server message travels in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClientRequest {
+    #[serde(default)]
+    pub request_id: Option<RequestId>,
+    pub message: GameMessage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum GameMessage {
     // Client to Server
-    JoinGame { player_name: String, city: City },
+    Hello { // Must be the first message on a connection, always sent as JSON
+        protocol_version: u32,
+        client_kind: ClientKind,
+        #[serde(default)]
+        wire_formats: Vec<WireFormat>, // In order of preference, JSON if none is supported
+    },
+    JoinGame {
+        player_name: String,
+        city: City,
+        #[serde(default)]
+        game_id: Option<Uuid>, // Join this room instead of any open room for the city
+    },
+    Rejoin { game_id: Uuid, player_id: Uuid, token: Uuid },
+    Authenticate { token: Uuid }, // From `/api/login`, counts the games joined afterwards towards the account
     PlayerAction(PlayerAction),
     StartGame,
+    RequestSnapshot,
+    Spectate { game_id: Uuid },
+    SendChat { text: String },
+    React { emote: Emote, target: Option<ReactionTarget> },
     
     // Server to Client
-    GameJoined { player_id: Uuid, game_id: Uuid },
-    GameState(GameState),
-    CardRevealed(Card),
-    PlayerActionResult { success: bool, message: String },
+    Welcome {
+        protocol_version: u32,
+        supported_versions: Vec<u32>,
+        capabilities: Vec<String>, // Optional features this server offers, e.g. "spectators"
+        #[serde(default)]
+        wire_format: WireFormat, // Used for every later message, starting after this one
+    },
+    HelloRejected { reason: String, supported_versions: Vec<u32> }, // The server closes the connection afterwards
+    Authenticated { username: String },
+    Queued { waiting: usize }, // Waiting for a room, `GameJoined` follows once there is one
+    GameJoined {
+        player_id: Uuid,
+        game_id: Uuid,
+        subway_map: SubwayMap,
+        roster: Vec<PlayerInfo>,
+        rejoin_token: Uuid, // Send back in `Rejoin` to take over the seat after a disconnect
+    },
+    RosterUpdated(Vec<PlayerInfo>),
+    Spectating { game_id: Uuid, subway_map: SubwayMap },
+    SpectatorsChanged { count: usize },
+    Chat(ChatEntry),
+    ChatHistory(Vec<ChatEntry>),
+    Reaction { sender: Uuid, emote: Emote, target: Option<ReactionTarget> },
+    StateSnapshot { version: u64, checksum: u64, state: GameStateView },
+    StateDelta {
+        version: u64, // Applies to the state of `version - 1`
+        checksum: u64, // `GameStateView::checksum` after applying the patches
+        patches: Vec<StatePatch>,
+    },
+    CardRevealed {
+        card: Card,
+        deadline: Option<u64>,           // Unix time in milliseconds, None if the round is untimed
+        time_banks: HashMap<Uuid, u64>,  // Remaining time bank per player in milliseconds
+    },
+    PlayerTimedOut { player_id: Uuid, action: Option<PlayerAction> },
+    PlayerActionResult { request_id: Option<RequestId>, success: bool, message: String },
     LineCompleted { player_id: Uuid, line_id: LineId },
+    DeckReshuffled { reason: ReshuffleReason, deck_count: usize, discard_count: usize }, // Sent when the round ends
     GameEnded { scores: HashMap<Uuid, i32> },
-    Error(String),
+    Error { request_id: Option<RequestId>, message: String },
 }
 
 impl Card {
-    pub fn create_deck() -> Vec<Card> {
-        let mut deck = Vec::new();
-        
-        // Number cards 1-5 (2 of each)
-        for num in 1..=5 {
-            deck.push(Card::Number(num));
-            deck.push(Card::Number(num));
-        }
-        
-        // Special card 6 (1 card)
-        deck.push(Card::Six);
-        
-        // Express cards (1 of each)
-        deck.push(Card::Express(2));
-        deck.push(Card::Express(3));
-        deck.push(Card::Express(4));
-        
-        // Transfer and Free ride (1 of each)
-        deck.push(Card::Transfer);
-        deck.push(Card::FreeRide);
-        
-        deck
-    }
-    
     pub fn get_value(&self) -> Option<u8> {
         match self {
-            Card::Number(n) | Card::Express(n) => Some(*n),
+            Card::Number(n) | Card::Express(n) | Card::Double(n) => Some(*n),
             Card::Six => Some(6),
-            Card::Transfer | Card::FreeRide => None,
+            Card::Transfer | Card::FreeRide | Card::Delay | Card::Closure => None,
         }
     }
 }
 
+impl Station {
+    /// Whether a transfer card may mark it: a hub, or any station where lines meet.
+    pub fn is_transfer_station(&self) -> bool {
+        self.is_transfer_hub || self.lines.len() >= 2
+    }
+}
+
 impl PlayerSheet {
     pub fn new(player_id: Uuid, city: City, subway_map: &SubwayMap) -> Self {
         let mut train_cars = HashMap::new();
@@ -165,9 +453,25 @@ impl PlayerSheet {
             marked_stations: HashMap::new(),
             completed_lines: Vec::new(),
             line_completion_status,
+            closed_stations: HashSet::new(),
         }
     }
     
+    /// Whether the station can still be marked, i.e. it is neither marked nor closed.
+    pub fn is_open(&self, station_id: &str) -> bool {
+        !self.marked_stations.contains_key(station_id) && !self.closed_stations.contains(station_id)
+    }
+    
+    /// Whether any line still has an empty window for a card.
+    pub fn has_empty_window(&self) -> bool {
+        self.train_cars.keys().any(|line_id| self.can_use_line(line_id))
+    }
+    
+    /// Index of the window the next card on this line goes into.
+    pub fn next_empty_window(&self, line_id: &LineId) -> Option<usize> {
+        self.train_cars.get(line_id)?.iter().position(|window| window.is_none())
+    }
+    
     pub fn can_use_line(&self, line_id: &LineId) -> bool {
         if let Some(windows) = self.train_cars.get(line_id) {
             windows.iter().any(|w| w.is_none())
@@ -184,10 +488,7 @@ impl PlayerSheet {
         if let Some(windows) = self.train_cars.get_mut(line_id) {
             for window in windows.iter_mut() {
                 if window.is_none() {
-                    *window = Some(match card {
-                        Card::Transfer => "+".to_string(),
-                        _ => card.get_value().map(|v| v.to_string()).unwrap_or("0".to_string()),
-                    });
+                    *window = Some(card.effect().window_symbol());
                     return Ok(());
                 }
             }
@@ -196,45 +497,12 @@ impl PlayerSheet {
         Err("Could not add card to line".to_string())
     }
     
-    pub fn mark_stations_from_line(&mut self, line_id: &LineId, card: &Card, subway_map: &SubwayMap) -> Result<Vec<String>, String> {
-        let _line = subway_map.lines.get(line_id)
-            .ok_or("Line not found")?;
-            
-        let mut marked_stations = Vec::new();
-        
-        match card {
-            Card::FreeRide => {
-                // Free ride: player can mark any empty station
-                // This requires UI interaction, so we'll handle it differently
-                Ok(marked_stations)
-            },
-            Card::Transfer => {
-                // Find first empty station from train car and mark as transfer
-                if let Some(station_id) = self.find_next_empty_station(line_id, subway_map)? {
-                    let station = subway_map.stations.get(&station_id)
-                        .ok_or("Station not found")?;
-                    
-                    let connection_count = station.lines.len() as u8;
-                    self.marked_stations.insert(station_id.clone(), StationMark::TransferNumber(connection_count));
-                    marked_stations.push(station_id);
-                }
-                Ok(marked_stations)
-            },
-            _ => {
-                // Regular number or express card
-                let value = card.get_value().unwrap_or(0);
-                let is_express = matches!(card, Card::Express(_));
-                
-                let stations_to_mark = self.find_stations_to_mark(line_id, value, is_express, subway_map)?;
-                
-                for station_id in stations_to_mark.iter() {
-                    self.marked_stations.insert(station_id.clone(), StationMark::Cross);
-                    marked_stations.push(station_id.clone());
-                }
-                
-                Ok(marked_stations)
-            }
+    pub fn mark_stations_from_line(&mut self, line_id: &LineId, card: &Card, rules: &RuleSet, subway_map: &SubwayMap) -> Result<Vec<String>, String> {
+        if !subway_map.lines.contains_key(line_id) {
+            return Err("Line not found".to_string());
         }
+        
+        Ok(card.effect().mark_line(self, line_id, rules, subway_map))
     }
     
     fn find_next_empty_station(&self, line_id: &LineId, subway_map: &SubwayMap) -> Result<Option<String>, String> {
@@ -243,7 +511,7 @@ impl PlayerSheet {
         
         // Start from the train car (beginning of line) and find first empty station
         for station_id in &line.stations {
-            if !self.marked_stations.contains_key(station_id) {
+            if self.is_open(station_id) {
                 return Ok(Some(station_id.clone()));
             }
         }
@@ -251,7 +519,7 @@ impl PlayerSheet {
         Ok(None)
     }
     
-    fn find_stations_to_mark(&self, line_id: &LineId, value: u8, is_express: bool, subway_map: &SubwayMap) -> Result<Vec<String>, String> {
+    fn find_stations_to_mark(&self, line_id: &LineId, value: u8, skip_marked: bool, subway_map: &SubwayMap) -> Result<Vec<String>, String> {
         let line = subway_map.lines.get(line_id)
             .ok_or("Line not found")?;
         
@@ -263,12 +531,12 @@ impl PlayerSheet {
                 break;
             }
             
-            if self.marked_stations.contains_key(station_id) {
-                if is_express {
-                    // Express card: skip already marked stations
+            if !self.is_open(station_id) {
+                if skip_marked {
+                    // Express card: skip already marked or closed stations
                     continue;
                 } else {
-                    // Regular card: stop at already marked station
+                    // Regular card: stop at already marked or closed station
                     break;
                 }
             }
@@ -294,7 +562,7 @@ impl PlayerSheet {
         false
     }
     
-    pub fn calculate_score(&self, subway_map: &SubwayMap) -> i32 {
+    pub fn calculate_score(&self, rules: &RuleSet, subway_map: &SubwayMap) -> i32 {
         let mut score = 0i32;
         
         // Points for completed lines
@@ -308,39 +576,44 @@ impl PlayerSheet {
             }
         }
         
-        // Double points for transfer stations
+        // Points for transfer stations, doubled unless the rules say otherwise
+        let transfer_factor = if rules.double_transfers { 2 } else { 1 };
         for mark in self.marked_stations.values() {
             if let StationMark::TransferNumber(connections) = mark {
-                score += (*connections as i32) * 2;
+                score += (*connections as i32) * transfer_factor;
             }
         }
         
-        // Penalty for empty stations (half the count, rounded down)
+        // Penalty for empty stations (rounded down), none if the divisor is 0
         let empty_stations = self.count_empty_stations(subway_map);
-        score -= (empty_stations / 2) as i32;
+        if let Some(penalty) = empty_stations.checked_div(rules.empty_station_penalty_divisor) {
+            score -= penalty as i32;
+        }
         
         score
     }
     
+    /// Closed stations are not empty, the player could not have marked them.
     fn count_empty_stations(&self, subway_map: &SubwayMap) -> u32 {
         let mut total_stations = 0;
         let marked_stations = self.marked_stations.len() as u32;
+        let closed_stations = self.closed_stations.len() as u32;
         
         for line in subway_map.lines.values() {
             total_stations += line.stations.len() as u32;
         }
         
-        total_stations - marked_stations
+        total_stations.saturating_sub(marked_stations + closed_stations)
     }
 }
 
 impl GameState {
     pub fn new(city: City, conductor: Uuid) -> Self {
-        let mut deck = Card::create_deck();
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        // Simple shuffle using system time as seed
+        Self::with_rules(city, conductor, RuleSet::default())
+    }
+    
+    pub fn with_rules(city: City, conductor: Uuid, rules: RuleSet) -> Self {
+        // Seed from the system time unless a caller needs a reproducible game
         let mut hasher = DefaultHasher::new();
         std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -348,89 +621,138 @@ impl GameState {
             .as_nanos()
             .hash(&mut hasher);
         
-        let seed = hasher.finish();
-        Self::shuffle_deck(&mut deck, seed);
-        
-        Self {
+        Self::with_seed(city, conductor, rules, hasher.finish())
+    }
+    
+    /// A game whose deck order, and every reshuffle after, follows from `seed`.
+    pub fn with_seed(city: City, conductor: Uuid, rules: RuleSet, seed: u64) -> Self {
+        let mut game_state = Self {
             id: Uuid::new_v4(),
             city,
             players: HashMap::new(),
             current_card: None,
-            deck,
+            deck: rules.create_deck(),
             discard_pile: Vec::new(),
             round: 0,
-            game_ended: false,
+            phase: GamePhase::Lobby,
             conductor,
-        }
+            acted_this_round: HashSet::new(),
+            plays_this_round: HashMap::new(),
+            roster: Vec::new(),
+            pending_completions: Vec::new(),
+            pending_closures: Vec::new(),
+            rng_state: seed,
+            rules,
+        };
+        game_state.shuffle_deck();
+        game_state
     }
     
-    fn shuffle_deck(deck: &mut [Card], seed: u64) {
+    fn shuffle_deck(&mut self) {
         // Simple Fisher-Yates shuffle
-        let mut rng_state = seed;
-        
-        for i in (1..deck.len()).rev() {
-            rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
-            let j = (rng_state as usize) % (i + 1);
-            deck.swap(i, j);
+        for i in (1..self.deck.len()).rev() {
+            self.rng_state = self.rng_state.wrapping_mul(1103515245).wrapping_add(12345);
+            let j = (self.rng_state as usize) % (i + 1);
+            self.deck.swap(i, j);
         }
     }
     
-    pub fn add_player(&mut self, player_id: Uuid, subway_map: &SubwayMap) {
+    pub fn add_player(&mut self, player_id: Uuid, name: String, subway_map: &SubwayMap) {
         let player_sheet = PlayerSheet::new(player_id, self.city.clone(), subway_map);
         self.players.insert(player_id, player_sheet);
+        
+        let color = PLAYER_COLORS.iter()
+            .find(|color| !self.roster.iter().any(|info| info.color == **color))
+            .unwrap_or(&PLAYER_COLORS[self.roster.len() % PLAYER_COLORS.len()]);
+        
+        self.roster.push(PlayerInfo {
+            id: player_id,
+            name,
+            color: color.to_string(),
+            connected: true,
+            is_bot: false,
+            is_conductor: player_id == self.conductor,
+        });
     }
     
+    pub fn player_info(&self, player_id: &Uuid) -> Option<&PlayerInfo> {
+        self.roster.iter().find(|info| info.id == *player_id)
+    }
+    
+    pub fn is_name_taken(&self, name: &str) -> bool {
+        self.roster.iter().any(|info| info.name.eq_ignore_ascii_case(name))
+    }
+    
+    pub fn set_connected(&mut self, player_id: &Uuid, connected: bool) {
+        if let Some(info) = self.roster.iter_mut().find(|info| info.id == *player_id) {
+            info.connected = connected;
+        }
+    }
+    
+    /// Takes the top card. The deck is refilled at the end of a round, never
+    /// while drawing, see `next_round`.
     pub fn draw_card(&mut self) -> Option<Card> {
-        if let Some(card) = self.deck.pop() {
-            Some(card)
-        } else if !self.discard_pile.is_empty() {
-            // Reshuffle discard pile into deck
-            self.deck.append(&mut self.discard_pile);
-            let mut hasher = DefaultHasher::new();
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos()
-                .hash(&mut hasher);
-            Self::shuffle_deck(&mut self.deck, hasher.finish());
-            self.deck.pop()
-        } else {
-            None
+        self.deck.pop()
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.phase == GamePhase::Finished
+    }
+    
+    fn transition(&mut self, next: GamePhase) -> Result<(), String> {
+        if !self.phase.can_become(next) {
+            return Err(format!("Cannot go from {:?} to {:?}", self.phase, next));
         }
+        
+        self.phase = next;
+        Ok(())
+    }
+    
+    /// Reveals the first card. A game can only be started once.
+    pub fn start_game(&mut self) -> Result<Card, String> {
+        if self.phase != GamePhase::Lobby {
+            return Err("The game has already started".to_string());
+        }
+        
+        self.reveal_card().ok_or_else(|| "The deck is empty".to_string())
     }
     
+    /// Reveals the next card, or returns `None` when no round can start right
+    /// now. Running out of cards finishes the game.
     pub fn reveal_card(&mut self) -> Option<Card> {
-        if let Some(card) = self.draw_card() {
-            self.current_card = Some(card.clone());
-            
-            // Check if it's card 6 - reshuffle at end of round
-            if matches!(card, Card::Six) {
-                // This will be handled after all players make their moves
-            }
-            
-            Some(card)
-        } else {
-            None
+        if !self.phase.can_become(GamePhase::WaitingForActions) {
+            return None;
+        }
+        
+        match self.draw_card() {
+            Some(card) => {
+                self.current_card = Some(card.clone());
+                self.phase = GamePhase::WaitingForActions;
+                Some(card)
+            },
+            None => {
+                let _ = self.transition(GamePhase::Finished);
+                None
+            },
         }
     }
     
-    pub fn handle_card_six(&mut self) {
-        // Move all discard pile cards back to deck and shuffle
+    /// Shuffles the discard pile back into the deck and tells everyone about it.
+    fn reshuffle(&mut self, reason: ReshuffleReason) -> GameMessage {
         self.deck.append(&mut self.discard_pile);
-        if let Some(current) = self.current_card.take() {
-            self.deck.push(current);
-        }
+        self.shuffle_deck();
         
-        let mut hasher = DefaultHasher::new();
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos()
-            .hash(&mut hasher);
-        Self::shuffle_deck(&mut self.deck, hasher.finish());
+        GameMessage::DeckReshuffled {
+            reason,
+            deck_count: self.deck.len(),
+            discard_count: self.discard_pile.len(),
+        }
     }
     
     pub fn process_player_action(&mut self, player_id: Uuid, action: PlayerAction, subway_map: &SubwayMap) -> Result<Vec<GameMessage>, String> {
+        self.validate_action(player_id, &action, subway_map)
+            .map_err(|e| e.to_string())?;
+        
         let mut messages = Vec::new();
         
         let current_card = self.current_card.as_ref()
@@ -438,11 +760,6 @@ impl GameState {
         
         match action {
             PlayerAction::ChooseLine { line_id, car_window_index: _ } => {
-                // Check if others have completed this line first
-                let others_completed = self.players.values()
-                    .filter(|p| p.player_id != player_id)
-                    .any(|p| p.completed_lines.contains(&line_id));
-                
                 let player = self.players.get_mut(&player_id)
                     .ok_or("Player not found")?;
                 
@@ -450,42 +767,25 @@ impl GameState {
                 player.add_card_to_line(&line_id, current_card)?;
                 
                 // Mark stations based on card
-                let marked_stations = player.mark_stations_from_line(&line_id, current_card, subway_map)?;
+                let marked_stations = player.mark_stations_from_line(&line_id, current_card, &self.rules, subway_map)?;
                 
-                // Check for line completion
-                let line_completed = player.check_line_completion(&line_id, subway_map);
-                if line_completed {
-                    messages.push(GameMessage::LineCompleted { player_id, line_id: line_id.clone() });
-                    
-                    // Update completion status
-                    if let Some(line) = subway_map.lines.get(&line_id) {
-                        if !others_completed {
-                            // First to complete
-                            player.line_completion_status.insert(
-                                line_id.clone(), 
-                                CompletionStatus::FirstToComplete(line.completion_points.0)
-                            );
-                        } else {
-                            // Later completion
-                            player.line_completion_status.insert(
-                                line_id.clone(), 
-                                CompletionStatus::LaterCompletion(line.completion_points.1)
-                            );
-                        }
-                    }
+                // Completions are scored once the round is over
+                if player.check_line_completion(&line_id, subway_map) {
+                    self.pending_completions.push(PendingCompletion::new(player_id, line_id, subway_map));
                 }
                 
-                messages.push(GameMessage::PlayerActionResult { 
+                let mut message = format!("Marked {} stations", marked_stations.len());
+                if !self.record_play(player_id) {
+                    message.push_str(", play the card once more");
+                }
+                messages.push(GameMessage::PlayerActionResult {
+                    request_id: None,
                     success: true, 
-                    message: format!("Marked {} stations", marked_stations.len()) 
+                    message,
                 });
             },
             
             PlayerAction::MarkTransferStation { station_id } => {
-                if !matches!(current_card, Card::Transfer) {
-                    return Err("Can only mark transfer station with transfer card".to_string());
-                }
-                
                 let station = subway_map.stations.get(&station_id)
                     .ok_or("Station not found")?;
                 
@@ -496,70 +796,200 @@ impl GameState {
                 
                 player.marked_stations.insert(station_id, StationMark::TransferNumber(connection_count));
                 
-                messages.push(GameMessage::PlayerActionResult { 
+                messages.push(GameMessage::PlayerActionResult {
+                    request_id: None,
                     success: true, 
                     message: format!("Marked transfer station with {} connections", connection_count) 
                 });
+                self.record_play(player_id);
             },
             
             PlayerAction::MarkFreeRideStation { station_id } => {
-                if !matches!(current_card, Card::FreeRide) {
-                    return Err("Can only mark free ride station with free ride card".to_string());
-                }
-                
                 let player = self.players.get_mut(&player_id)
                     .ok_or("Player not found")?;
                 
-                if player.marked_stations.contains_key(&station_id) {
-                    return Err("Station already marked".to_string());
-                }
-                
                 player.marked_stations.insert(station_id, StationMark::Cross);
                 
-                messages.push(GameMessage::PlayerActionResult { 
+                messages.push(GameMessage::PlayerActionResult {
+                    request_id: None,
                     success: true, 
                     message: "Marked free ride station".to_string() 
                 });
+                self.record_play(player_id);
+            },
+            
+            PlayerAction::CloseStation { station_id } => {
+                // Takes effect when the round ends, so everyone plays this card on the same sheets
+                messages.push(GameMessage::PlayerActionResult {
+                    request_id: None,
+                    success: true,
+                    message: format!("Station {} closes at the end of the round", station_id),
+                });
+                self.pending_closures.push(station_id);
+                self.record_play(player_id);
             },
             
             PlayerAction::CompleteLineAnnouncement { line_id } => {
-                messages.push(GameMessage::LineCompleted { player_id, line_id });
+                // Lines finished through transfer or free ride marks are only
+                // registered once the player announces them
+                let player = self.players.get_mut(&player_id)
+                    .ok_or("Player not found")?;
+                
+                if player.check_line_completion(&line_id, subway_map) {
+                    messages.push(GameMessage::PlayerActionResult {
+                        request_id: None,
+                        success: true,
+                        message: format!("Line {} will be scored at the end of the round", line_id.0),
+                    });
+                    self.pending_completions.push(PendingCompletion::new(player_id, line_id, subway_map));
+                }
             },
         }
         
         Ok(messages)
     }
     
+    /// Counts one use of the current card and returns whether the player is
+    /// done for this round. A player without an empty window left is done even
+    /// if the card could be played again.
+    fn record_play(&mut self, player_id: Uuid) -> bool {
+        let plays_per_round = self.current_card.as_ref().map_or(1, |card| card.effect().plays_per_round());
+        let can_play_again = self.players.get(&player_id).is_some_and(PlayerSheet::has_empty_window);
+        let plays = self.plays_this_round.entry(player_id).or_default();
+        *plays += 1;
+        
+        if *plays >= plays_per_round || !can_play_again {
+            self.acted_this_round.insert(player_id);
+        }
+        self.has_acted(&player_id)
+    }
+    
+    pub fn has_acted(&self, player_id: &Uuid) -> bool {
+        self.acted_this_round.contains(player_id)
+    }
+    
+    pub fn all_players_acted(&self) -> bool {
+        self.players.keys().all(|player_id| self.acted_this_round.contains(player_id))
+    }
+    
+    /// Picks a legal move for a player who ran out of time: the current card goes
+    /// onto the first line (by id) that still has an empty window. Returns `None`
+    /// when the player has no usable line left and can only pass.
+    pub fn default_action(&self, player_id: Uuid) -> Option<PlayerAction> {
+        self.current_card.as_ref()?;
+        let player = self.players.get(&player_id)?;
+        
+        let mut line_ids: Vec<&LineId> = player.train_cars.keys()
+            .filter(|line_id| player.can_use_line(line_id))
+            .collect();
+        line_ids.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        line_ids.first().and_then(|line_id| Some(PlayerAction::ChooseLine {
+            line_id: (*line_id).clone(),
+            car_window_index: player.next_empty_window(line_id)?,
+        }))
+    }
+    
+    /// Skips the current card for a player without touching their sheet.
+    pub fn pass(&mut self, player_id: Uuid) {
+        self.acted_this_round.insert(player_id);
+    }
+    
     pub fn check_game_end(&self) -> bool {
-        // Game ends when all train car windows are filled
-        self.players.values().all(|player| {
-            player.train_cars.values().all(|windows| {
-                windows.iter().all(|window| window.is_some())
-            })
-        })
+        let sheet_full = |player: &PlayerSheet| {
+            player.train_cars.values().all(|windows| windows.iter().all(|window| window.is_some()))
+        };
+        
+        match self.rules.game_end {
+            GameEndRule::AllSheetsFull => self.players.values().all(sheet_full),
+            GameEndRule::FirstSheetFull => self.players.values().any(sheet_full),
+            GameEndRule::Rounds(rounds) => self.round >= rounds || self.players.values().all(sheet_full),
+        }
     }
     
     pub fn calculate_final_scores(&self, subway_map: &SubwayMap) -> HashMap<Uuid, i32> {
         self.players.iter()
-            .map(|(player_id, player)| (*player_id, player.calculate_score(subway_map)))
+            .map(|(player_id, player)| (*player_id, player.calculate_score(&self.rules, subway_map)))
             .collect()
     }
     
-    pub fn next_round(&mut self) {
+    /// Closes the current round once every move is in. Returns the
+    /// `LineCompleted` messages of the lines completed during it, followed by a
+    /// `DeckReshuffled` if the deck was refilled.
+    pub fn next_round(&mut self) -> Result<Vec<GameMessage>, String> {
+        if self.phase != GamePhase::WaitingForActions {
+            return Err("No round in progress".to_string());
+        }
+        
+        let mut messages = self.resolve_completions();
+        self.apply_closures();
         self.round += 1;
+        self.acted_this_round.clear();
+        self.plays_this_round.clear();
         
-        // Move current card to discard pile
+        // Everyone has used the card now, so a 6 can take the discard pile with it
+        let mut round_over = GamePhase::RoundOver;
         if let Some(card) = self.current_card.take() {
-            if matches!(card, Card::Six) {
-                self.handle_card_six();
-            } else {
-                self.discard_pile.push(card);
+            let is_six = matches!(card, Card::Six);
+            self.discard_pile.push(card);
+            if is_six {
+                messages.push(self.reshuffle(ReshuffleReason::CardSix));
+                round_over = GamePhase::ResolvingCardSix;
             }
         }
+        if self.deck.is_empty() && !self.discard_pile.is_empty() {
+            messages.push(self.reshuffle(ReshuffleReason::DeckEmpty));
+        }
         
-        // Check if game should end
         if self.check_game_end() {
-            self.game_ended = true;
+            self.transition(GamePhase::Finished)?;
+        } else {
+            self.transition(round_over)?;
+        }
+        
+        Ok(messages)
+    }
+    
+    /// Closes the stations of this round's closure cards on every sheet that
+    /// hasn't marked them by now.
+    fn apply_closures(&mut self) {
+        for station_id in self.pending_closures.drain(..) {
+            for player in self.players.values_mut() {
+                if !player.marked_stations.contains_key(&station_id) {
+                    player.closed_stations.insert(station_id.clone());
+                }
+            }
+        }
+    }
+    
+    /// Scores the lines completed this round. Everyone who completes a line in
+    /// the round it is first completed gets the first completion points, no
+    /// matter whose move arrived first.
+    fn resolve_completions(&mut self) -> Vec<GameMessage> {
+        let pending = std::mem::take(&mut self.pending_completions);
+        
+        let statuses: Vec<CompletionStatus> = pending.iter()
+            .map(|completion| {
+                let completed_earlier = self.players.values().any(|player| matches!(
+                    player.line_completion_status.get(&completion.line_id),
+                    Some(CompletionStatus::FirstToComplete(_) | CompletionStatus::LaterCompletion(_))
+                ));
+                if completed_earlier {
+                    CompletionStatus::LaterCompletion(completion.points.1)
+                } else {
+                    CompletionStatus::FirstToComplete(completion.points.0)
+                }
+            })
+            .collect();
+        
+        let mut messages = Vec::new();
+        for (completion, status) in pending.into_iter().zip(statuses) {
+            if let Some(player) = self.players.get_mut(&completion.player_id) {
+                player.line_completion_status.insert(completion.line_id.clone(), status);
+                messages.push(GameMessage::LineCompleted { player_id: completion.player_id, line_id: completion.line_id });
+            }
         }
+        
+        messages
     }
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::{City, LineId, Station, SubwayLine, SubwayMap};
+
+impl SubwayMap {
+    /// The map that ships with the game for a city. Servers can replace these
+    /// with maps loaded from disk.
+    pub fn builtin(city: &City) -> SubwayMap {
+        // For now, create a simple Amsterdam map
+        // This will be expanded with real subway data
+        let mut stations = HashMap::new();
+        let mut lines = HashMap::new();
+        
+        match city {
+            City::Amsterdam => {
+                // Create a simple Amsterdam map for testing
+                stations.insert("central".to_string(), Station {
+                    id: "central".to_string(),
+                    x: 100.0,
+                    y: 100.0,
+                    lines: vec![LineId("red".to_string()), LineId("blue".to_string())],
+                    is_transfer_hub: true,
+                });
+                
+                stations.insert("dam".to_string(), Station {
+                    id: "dam".to_string(),
+                    x: 150.0,
+                    y: 100.0, 
+                    lines: vec![LineId("red".to_string())],
+                    is_transfer_hub: false,
+                });
+                
+                stations.insert("museum".to_string(), Station {
+                    id: "museum".to_string(),
+                    x: 200.0,
+                    y: 100.0,
+                    lines: vec![LineId("red".to_string())],
+                    is_transfer_hub: false,
+                });
+                
+                lines.insert(LineId("red".to_string()), SubwayLine {
+                    id: LineId("red".to_string()),
+                    color: "#FF0000".to_string(),
+                    stations: vec!["central".to_string(), "dam".to_string(), "museum".to_string()],
+                    is_ring: false,
+                    completion_points: (6, 3),
+                });
+                
+                lines.insert(LineId("blue".to_string()), SubwayLine {
+                    id: LineId("blue".to_string()),
+                    color: "#0000FF".to_string(),
+                    stations: vec!["central".to_string()],
+                    is_ring: false,
+                    completion_points: (4, 2),
+                });
+            },
+            _ => {
+                // Placeholder for other cities
+                stations.insert("test".to_string(), Station {
+                    id: "test".to_string(),
+                    x: 100.0,
+                    y: 100.0,
+                    lines: vec![LineId("test".to_string())],
+                    is_transfer_hub: false,
+                });
+                
+                lines.insert(LineId("test".to_string()), SubwayLine {
+                    id: LineId("test".to_string()),
+                    color: "#000000".to_string(),
+                    stations: vec!["test".to_string()],
+                    is_ring: false,
+                    completion_points: (1, 1),
+                });
+            }
+        }
+        
+        SubwayMap {
+            city: city.clone(),
+            stations,
+            lines,
+            special_stations: Vec::new(),
+        }
+    }
+    
+    /// Checks that every line only refers to stations that exist on the map and
+    /// every station only refers to lines that exist.
+    pub fn validate(&self) -> Result<(), String> {
+        for line in self.lines.values() {
+            if line.stations.is_empty() {
+                return Err(format!("Line {} has no stations", line.id.0));
+            }
+            
+            for station_id in &line.stations {
+                if !self.stations.contains_key(station_id) {
+                    return Err(format!("Line {} refers to unknown station {}", line.id.0, station_id));
+                }
+            }
+        }
+        
+        for station in self.stations.values() {
+            for line_id in &station.lines {
+                if !self.lines.contains_key(line_id) {
+                    return Err(format!("Station {} refers to unknown line {}", station.id, line_id.0));
+                }
+            }
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::City;
+
+/// Someone waiting in the matchmaking queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedPlayer {
+    pub id: Uuid,
+    pub city: City,
+    pub rating: f64,
+    pub waited: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MatchSettings {
+    pub min_players: usize,
+    pub max_players: usize,
+    pub max_wait: Duration,      // After this a player gets a room with whoever is there, even alone
+    pub rating_window: f64,      // How far apart ratings may be at first
+    pub window_growth: f64,      // How much wider the window gets per second of waiting
+}
+
+/// Groups queued players into rooms. Whoever waited longest goes first and is
+/// joined by the closest ratings for the same city within their window. A
+/// group only forms once it has `min_players`, unless its first player has
+/// waited `max_wait`, in which case ratings no longer matter.
+pub fn find_matches(queue: &[QueuedPlayer], settings: &MatchSettings) -> Vec<Vec<Uuid>> {
+    let mut by_wait: Vec<&QueuedPlayer> = queue.iter().collect();
+    by_wait.sort_by_key(|player| std::cmp::Reverse(player.waited));
+    
+    let mut matched: Vec<Uuid> = Vec::new();
+    let mut matches = Vec::new();
+    
+    for anchor in &by_wait {
+        if matched.contains(&anchor.id) {
+            continue;
+        }
+        
+        let timed_out = anchor.waited >= settings.max_wait;
+        let window = settings.rating_window + settings.window_growth * anchor.waited.as_secs_f64();
+        
+        let mut candidates: Vec<&QueuedPlayer> = by_wait.iter()
+            .filter(|other| other.id != anchor.id && other.city == anchor.city && !matched.contains(&other.id))
+            .filter(|other| timed_out || (other.rating - anchor.rating).abs() <= window)
+            .copied()
+            .collect();
+        // Stable, so equally close players keep the longest waiting first
+        candidates.sort_by(|a, b| (a.rating - anchor.rating).abs().total_cmp(&(b.rating - anchor.rating).abs()));
+        
+        let group: Vec<Uuid> = std::iter::once(anchor.id)
+            .chain(candidates.iter().map(|other| other.id))
+            .take(settings.max_players.max(1))
+            .collect();
+        
+        if group.len() >= settings.min_players || timed_out {
+            matched.extend(&group);
+            matches.push(group);
+        }
+    }
+    
+    matches
+}
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+/// What a new account starts with.
+pub const DEFAULT_RATING: f64 = 1500.0;
+
+/// The most a rating moves in one game.
+const K_FACTOR: f64 = 32.0;
+
+/// A rated player's result in a finished game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatedPlayer {
+    pub id: Uuid,
+    pub rating: f64,
+    pub score: i32,
+}
+
+/// The ratings after a game. Every pair of players counts as one Elo game,
+/// scaled down so a game against many opponents moves a rating no more than
+/// a game against one.
+pub fn update_ratings(players: &[RatedPlayer]) -> HashMap<Uuid, f64> {
+    let opponents = players.len().saturating_sub(1).max(1) as f64;
+    
+    players.iter()
+        .map(|player| {
+            let change: f64 = players.iter()
+                .filter(|other| other.id != player.id)
+                .map(|other| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((other.rating - player.rating) / 400.0));
+                    let actual = match player.score.cmp(&other.score) {
+                        std::cmp::Ordering::Greater => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Less => 0.0,
+                    };
+                    actual - expected
+                })
+                .sum();
+            (player.id, player.rating + K_FACTOR * change / opponents)
+        })
+        .collect()
+}
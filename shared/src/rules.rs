@@ -0,0 +1,198 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Card;
+
+/// How many of each card go into the deck.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeckConfig {
+    pub number_copies: u8, // Copies of each number card 1-5
+    pub sixes: u8,
+    pub express: Vec<u8>, // One express card per value
+    pub transfers: u8,
+    pub free_rides: u8,
+}
+
+impl Default for DeckConfig {
+    fn default() -> Self {
+        DeckConfig {
+            number_copies: 2,
+            sixes: 1,
+            express: vec![2, 3, 4],
+            transfers: 1,
+            free_rides: 1,
+        }
+    }
+}
+
+impl DeckConfig {
+    /// The unshuffled deck.
+    pub fn create_deck(&self) -> Vec<Card> {
+        let mut deck = Vec::new();
+        
+        for num in 1..=5 {
+            for _ in 0..self.number_copies {
+                deck.push(Card::Number(num));
+            }
+        }
+        for _ in 0..self.sixes {
+            deck.push(Card::Six);
+        }
+        for value in &self.express {
+            deck.push(Card::Express(*value));
+        }
+        for _ in 0..self.transfers {
+            deck.push(Card::Transfer);
+        }
+        for _ in 0..self.free_rides {
+            deck.push(Card::FreeRide);
+        }
+        
+        deck
+    }
+}
+
+/// Optional cards a room can add on top of its deck.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Expansion {
+    Delay,   // Two cards that fill a window without marking anything
+    Double,  // A 2 and a 3 that every player plays twice
+    Closure, // One card that closes a station for everyone
+}
+
+impl Expansion {
+    pub const ALL: [Expansion; 3] = [Expansion::Delay, Expansion::Double, Expansion::Closure];
+    
+    pub fn cards(self) -> Vec<Card> {
+        match self {
+            Expansion::Delay => vec![Card::Delay, Card::Delay],
+            Expansion::Double => vec![Card::Double(2), Card::Double(3)],
+            Expansion::Closure => vec![Card::Closure],
+        }
+    }
+}
+
+/// What an express card does when it reaches a station that is already marked.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ExpressRule {
+    #[default]
+    SkipMarked, // Keeps going to the next empty station
+    StopAtMarked, // Stops like a number card
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum GameEndRule {
+    #[default]
+    AllSheetsFull, // Every window of every player is filled
+    FirstSheetFull, // Some player has filled all their windows
+    Rounds(u32),
+}
+
+/// The house rules of a game, fixed when its room is created.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RuleSet {
+    pub deck: DeckConfig,
+    pub express: ExpressRule,
+    pub empty_station_penalty_divisor: u32, // One point lost per this many empty stations, 0 for none
+    pub double_transfers: bool, // Transfer marks score twice their number of lines
+    pub game_end: GameEndRule,
+    #[serde(default)]
+    pub expansions: Vec<Expansion>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RulePreset::Classic.rules()
+    }
+}
+
+impl RuleSet {
+    /// The unshuffled deck including the cards of every expansion.
+    pub fn create_deck(&self) -> Vec<Card> {
+        let mut deck = self.deck.create_deck();
+        for expansion in &self.expansions {
+            deck.extend(expansion.cards());
+        }
+        deck
+    }
+    
+    pub fn validate(&self) -> Result<(), String> {
+        if self.create_deck().is_empty() {
+            return Err("The deck must contain at least one card".to_string());
+        }
+        if let Some(value) = self.deck.express.iter().find(|value| !(1..=6).contains(*value)) {
+            return Err(format!("Express cards must be between 1 and 6, not {}", value));
+        }
+        if let Some(expansion) = self.expansions.iter().enumerate()
+            .find_map(|(index, expansion)| self.expansions[..index].contains(expansion).then_some(expansion))
+        {
+            return Err(format!("Expansion {:?} is listed twice", expansion));
+        }
+        if self.game_end == GameEndRule::Rounds(0) {
+            return Err("A game must last at least one round".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Named rule sets to pick from when creating a room.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum RulePreset {
+    #[default]
+    Classic,
+    Family, // No penalty for empty stations and an extra free ride
+    Quick, // Ends as soon as one sheet is full
+    Strict, // Express cards stop at marked stations, every empty station costs a point
+}
+
+impl RulePreset {
+    pub const ALL: [RulePreset; 4] = [RulePreset::Classic, RulePreset::Family, RulePreset::Quick, RulePreset::Strict];
+    
+    pub fn rules(self) -> RuleSet {
+        let classic = RuleSet {
+            deck: DeckConfig::default(),
+            express: ExpressRule::SkipMarked,
+            empty_station_penalty_divisor: 2,
+            double_transfers: true,
+            game_end: GameEndRule::AllSheetsFull,
+            expansions: Vec::new(),
+        };
+        
+        match self {
+            RulePreset::Classic => classic,
+            RulePreset::Family => RuleSet {
+                deck: DeckConfig { free_rides: 2, ..DeckConfig::default() },
+                empty_station_penalty_divisor: 0,
+                ..classic
+            },
+            RulePreset::Quick => RuleSet {
+                game_end: GameEndRule::FirstSheetFull,
+                ..classic
+            },
+            RulePreset::Strict => RuleSet {
+                express: ExpressRule::StopAtMarked,
+                empty_station_penalty_divisor: 1,
+                double_transfers: false,
+                ..classic
+            },
+        }
+    }
+}
+
+impl FromStr for RulePreset {
+    type Err = String;
+    
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        RulePreset::ALL.into_iter()
+            .find(|preset| format!("{:?}", preset).eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown rule preset {}", name))
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Card, GameMessage, GameState, GameStateView, PlayerAction, Replay, RuleSet, SubwayMap};
+
+/// A medal for reaching one of the targets of a solo game.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+/// The scores a solo player aims for on a map.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SoloTargets {
+    pub bronze: i32,
+    pub silver: i32,
+    pub gold: i32,
+}
+
+impl SoloTargets {
+    /// Targets as a share of what the map is worth: every line completed first
+    /// plus every transfer station marked with its lines. Derived rather than
+    /// listed per city, so maps loaded from disk get targets too.
+    pub fn for_map(subway_map: &SubwayMap, rules: &RuleSet) -> SoloTargets {
+        let transfer_factor = if rules.double_transfers { 2 } else { 1 };
+        let lines: i32 = subway_map.lines.values()
+            .map(|line| line.completion_points.0 as i32)
+            .sum();
+        let transfers: i32 = subway_map.stations.values()
+            .filter(|station| station.is_transfer_station())
+            .map(|station| station.lines.len() as i32 * transfer_factor)
+            .sum();
+        
+        // Rounded up, so even a tiny map needs at least a point
+        let share = |percent: i32| ((lines + transfers) * percent + 99) / 100;
+        SoloTargets {
+            bronze: share(40),
+            silver: share(65),
+            gold: share(90),
+        }
+    }
+    
+    pub fn medal(&self, score: i32) -> Option<Medal> {
+        if score >= self.gold {
+            Some(Medal::Gold)
+        } else if score >= self.silver {
+            Some(Medal::Silver)
+        } else if score >= self.bronze {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+}
+
+/// A single player game run entirely on the client. Rounds end as soon as the
+/// player is done with the card, so the flow the server drives with its timers
+/// happens right inside `play`.
+pub struct SoloGame {
+    pub state: GameState,
+    pub subway_map: SubwayMap,
+    pub player_id: Uuid,
+    pub seed: u64,
+    pub targets: SoloTargets,
+    pub replay: Replay, // Every move, including passes, so the game can be played again
+}
+
+impl SoloGame {
+    /// Starts a game on `subway_map` and reveals the first card. The same seed
+    /// deals the same cards.
+    pub fn new(subway_map: SubwayMap, rules: RuleSet, seed: u64) -> Result<SoloGame, String> {
+        let player_id = Uuid::new_v4();
+        let targets = SoloTargets::for_map(&subway_map, &rules);
+        
+        let mut state = GameState::with_seed(subway_map.city.clone(), player_id, rules, seed);
+        state.add_player(player_id, "You".to_string(), &subway_map);
+        let card = state.start_game()?;
+        
+        let mut replay = Replay::default();
+        replay.start_round(card);
+        
+        Ok(SoloGame { state, subway_map, player_id, seed, targets, replay })
+    }
+    
+    /// Plays `replay` again from `seed` and returns the final score. Fails if it
+    /// deals other cards, has a move the rules don't allow or stops before the
+    /// game is over.
+    pub fn verify(subway_map: SubwayMap, rules: RuleSet, seed: u64, replay: &Replay) -> Result<i32, String> {
+        let mut game = SoloGame::new(subway_map, rules, seed)?;
+        
+        for (round, recorded) in replay.rounds.iter().enumerate() {
+            if game.current_card() != Some(&recorded.card) {
+                return Err(format!("Round {} was not dealt {:?}", round + 1, recorded.card));
+            }
+            for recorded_action in &recorded.actions {
+                match &recorded_action.action {
+                    Some(action) => game.play(action.clone()),
+                    None => game.pass(),
+                }
+                .map_err(|e| format!("Round {}: {}", round + 1, e))?;
+            }
+        }
+        
+        if !game.is_finished() {
+            return Err("The replay stops before the end of the game".to_string());
+        }
+        Ok(game.score())
+    }
+    
+    pub fn current_card(&self) -> Option<&Card> {
+        self.state.current_card.as_ref()
+    }
+    
+    /// Plays the current card. Returns what the server would have sent: the
+    /// result, then once the round is over its announcements, and `GameEnded`
+    /// when it was the last one.
+    pub fn play(&mut self, action: PlayerAction) -> Result<Vec<GameMessage>, String> {
+        let mut messages = self.state.process_player_action(self.player_id, action.clone(), &self.subway_map)?;
+        self.replay.record(self.player_id, Some(action));
+        if self.state.has_acted(&self.player_id) {
+            messages.extend(self.finish_round()?);
+        }
+        Ok(messages)
+    }
+    
+    /// Skips the current card, for when it fits nowhere.
+    pub fn pass(&mut self) -> Result<Vec<GameMessage>, String> {
+        if self.state.is_finished() {
+            return Err("The game is over".to_string());
+        }
+        
+        self.state.pass(self.player_id);
+        self.replay.record(self.player_id, None);
+        self.finish_round()
+    }
+    
+    fn finish_round(&mut self) -> Result<Vec<GameMessage>, String> {
+        let mut messages = self.state.next_round()?;
+        
+        match self.state.reveal_card() {
+            Some(card) => self.replay.start_round(card),
+            None if self.state.is_finished() => {
+                let scores = self.state.calculate_final_scores(&self.subway_map);
+                messages.push(GameMessage::GameEnded { scores });
+            },
+            None => {},
+        }
+        Ok(messages)
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.state.is_finished()
+    }
+    
+    pub fn score(&self) -> i32 {
+        self.state.players[&self.player_id].calculate_score(&self.state.rules, &self.subway_map)
+    }
+    
+    /// The medal earned so far. Only final once the game is finished.
+    pub fn medal(&self) -> Option<Medal> {
+        self.targets.medal(self.score())
+    }
+    
+    pub fn view(&self) -> GameStateView {
+        GameStateView {
+            viewer: Some(self.player_id),
+            ..self.state.public_view(&self.subway_map)
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{City, GameRecord, LineId};
+
+/// How often an account completed a line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LineCount {
+    pub city: City,
+    pub line_id: LineId,
+    pub count: usize,
+}
+
+/// An account's record over every finished game it played.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlayerStats {
+    pub games_played: usize,
+    pub wins: usize,
+    pub average_score: f64, // 0 without games
+    pub best_scores: HashMap<City, i32>,
+    pub favourite_lines: Vec<LineCount>, // Most often completed first, at most `FAVOURITE_LINES`
+}
+
+impl PlayerStats {
+    pub const FAVOURITE_LINES: usize = 5;
+}
+
+/// The running totals behind `PlayerStats`. Accounts keep one and add every
+/// game they finish, so their stats never need all games read again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StatsTally {
+    pub games_played: usize,
+    pub wins: usize,
+    pub total_score: i64,
+    pub best_scores: HashMap<City, i32>,
+    pub line_counts: Vec<LineCount>, // Every line completed at least once, in no particular order
+}
+
+impl StatsTally {
+    /// Counts a finished game that `player_id` played in.
+    pub fn add_game(&mut self, record: &GameRecord, player_id: Uuid) {
+        let score = record.scores.get(&player_id).copied().unwrap_or(0);
+        self.games_played += 1;
+        self.total_score += score as i64;
+        if record.is_winner(player_id) {
+            self.wins += 1;
+        }
+        
+        let best = self.best_scores.entry(record.city.clone()).or_insert(score);
+        *best = (*best).max(score);
+        
+        for line_id in record.completed_lines.get(&player_id).into_iter().flatten() {
+            match self.line_counts.iter_mut().find(|line| line.city == record.city && line.line_id == *line_id) {
+                Some(line) => line.count += 1,
+                None => self.line_counts.push(LineCount { city: record.city.clone(), line_id: line_id.clone(), count: 1 }),
+            }
+        }
+    }
+    
+    pub fn stats(&self) -> PlayerStats {
+        let average_score = if self.games_played > 0 {
+            self.total_score as f64 / self.games_played as f64
+        } else {
+            0.0
+        };
+        
+        let mut favourite_lines = self.line_counts.clone();
+        favourite_lines.sort_by(|a, b| {
+            b.count.cmp(&a.count)
+                .then_with(|| format!("{:?}", a.city).cmp(&format!("{:?}", b.city)))
+                .then_with(|| a.line_id.0.cmp(&b.line_id.0))
+        });
+        favourite_lines.truncate(PlayerStats::FAVOURITE_LINES);
+        
+        PlayerStats {
+            games_played: self.games_played,
+            wins: self.wins,
+            average_score,
+            best_scores: self.best_scores.clone(),
+            favourite_lines,
+        }
+    }
+}
+
+impl GameRecord {
+    /// The player an account played as in this game, if it took part.
+    pub fn player_for(&self, account_id: Uuid) -> Option<Uuid> {
+        self.accounts.iter()
+            .find(|(_, account)| **account == account_id)
+            .map(|(player_id, _)| *player_id)
+    }
+    
+    /// Whether a player finished with the top score. Ties count for everyone on
+    /// top, a game played alone doesn't count at all.
+    pub fn is_winner(&self, player_id: Uuid) -> bool {
+        let Some(score) = self.scores.get(&player_id) else {
+            return false;
+        };
+        self.scores.len() > 1 && self.scores.values().all(|other| other <= score)
+    }
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{City, validate_player_name};
+
+/// How players are spread over the tables of a round.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum SeatingRule {
+    #[default]
+    Fixed,       // The same tables every round, in the order players were listed
+    Random,      // Shuffled anew every round
+    ByStandings, // Leaders play leaders, like a Swiss system
+}
+
+/// What a tournament is created from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TournamentSettings {
+    pub name: String,
+    pub players: Vec<String>,
+    pub cities: Vec<City>, // One round per city, played in order
+    pub table_size: usize, // Most players at one table
+    #[serde(default)]
+    pub seating: SeatingRule,
+}
+
+/// A player's result at a finished table.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TableResult {
+    pub player_name: String,
+    pub score: i32,
+    pub completed_lines: usize,
+    #[serde(default)]
+    pub forfeited: bool, // Didn't play, loses to everyone who did
+}
+
+/// One game of a round.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TournamentTable {
+    pub game_id: Uuid,
+    pub players: Vec<String>,
+    pub results: Option<Vec<TableResult>>, // Once the game is over
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TournamentRound {
+    pub city: City,
+    pub tables: Vec<TournamentTable>,
+    #[serde(default)]
+    pub bye: Option<String>, // Sat the round out for lack of an opponent
+}
+
+/// Who plays where in a round that is about to start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundSeating {
+    pub city: City,
+    pub tables: Vec<Vec<String>>,
+    pub bye: Option<String>,
+}
+
+/// A player's place in the tournament. Every game is worth a point per
+/// opponent beaten and half a point per opponent tied, a bye is worth
+/// `Tournament::BYE_POINTS`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Standing {
+    pub player_name: String,
+    pub points: f64,
+    pub total_score: i32, // Breaks ties on points
+    pub games_played: usize,
+    pub wins: usize,
+    pub completed_lines: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Tournament {
+    pub id: Uuid,
+    pub name: String,
+    pub players: Vec<String>,
+    pub cities: Vec<City>,
+    pub table_size: usize,
+    pub seating: SeatingRule,
+    pub seed: u64, // For `SeatingRule::Random`
+    pub created_at: u64, // Unix time in milliseconds
+    pub rounds: Vec<TournamentRound>, // Started so far
+    #[serde(default)]
+    pub organizer: Uuid, // Account that may start rounds and settle tables
+    #[serde(default)]
+    pub accounts: Vec<Uuid>, // Of every player, in the order of `players`
+}
+
+impl Tournament {
+    pub const MAX_NAME_LENGTH: usize = 64;
+    
+    /// As much as beating the one opponent a table of two would have had.
+    pub const BYE_POINTS: f64 = 1.0;
+    
+    /// `accounts` are the accounts the players take their seats with, in the
+    /// order of `settings.players`.
+    pub fn new(settings: TournamentSettings, organizer: Uuid, accounts: Vec<Uuid>, seed: u64, created_at: u64) -> Result<Tournament, String> {
+        let name = settings.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > Self::MAX_NAME_LENGTH {
+            return Err(format!("Tournament names must have 1 to {} characters", Self::MAX_NAME_LENGTH));
+        }
+        
+        let mut players: Vec<String> = Vec::new();
+        for player in &settings.players {
+            let player = validate_player_name(player)?;
+            if players.iter().any(|other| other.eq_ignore_ascii_case(&player)) {
+                return Err(format!("{} is listed twice", player));
+            }
+            players.push(player);
+        }
+        if players.len() < 2 {
+            return Err("A tournament needs at least 2 players".to_string());
+        }
+        if settings.cities.is_empty() {
+            return Err("A tournament needs at least one round".to_string());
+        }
+        if settings.table_size < 2 {
+            return Err("Tables must seat at least 2 players".to_string());
+        }
+        if accounts.len() != players.len() {
+            return Err("Every player needs an account".to_string());
+        }
+        
+        Ok(Tournament {
+            id: Uuid::new_v4(),
+            name,
+            players,
+            cities: settings.cities,
+            table_size: settings.table_size,
+            seating: settings.seating,
+            seed,
+            created_at,
+            rounds: Vec::new(),
+            organizer,
+            accounts,
+        })
+    }
+    
+    /// The account a player takes their seats with.
+    pub fn account_of(&self, player_name: &str) -> Option<Uuid> {
+        let index = self.players.iter().position(|player| player.eq_ignore_ascii_case(player_name))?;
+        self.accounts.get(index).copied()
+    }
+    
+    fn round_finished(round: &TournamentRound) -> bool {
+        round.tables.iter().all(|table| table.results.is_some())
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.rounds.len() == self.cities.len() && self.rounds.iter().all(Self::round_finished)
+    }
+    
+    /// The seating of the next round, if it can start: every game of the
+    /// current one must be over.
+    pub fn next_round(&self) -> Result<RoundSeating, String> {
+        let Some(city) = self.cities.get(self.rounds.len()) else {
+            return Err("Every round has been played".to_string());
+        };
+        if !self.rounds.last().is_none_or(Self::round_finished) {
+            return Err("The current round is still being played".to_string());
+        }
+        
+        let mut order = match self.seating {
+            SeatingRule::Fixed => self.players.clone(),
+            SeatingRule::Random => self.shuffled_players(self.seed.wrapping_add(self.rounds.len() as u64)),
+            SeatingRule::ByStandings => self.standings().into_iter().map(|standing| standing.player_name).collect(),
+        };
+        
+        // Only tables of two can leave a player without an opponent, an odd
+        // one out sits the round out instead
+        let bye = (order.len() < 2 * order.len().div_ceil(self.table_size)).then(|| self.bye_player(&order));
+        if let Some(bye) = &bye {
+            order.retain(|player| player != bye);
+        }
+        
+        // As even as possible, so no table is left with a single player
+        let table_count = order.len().div_ceil(self.table_size);
+        let mut remaining = order.into_iter();
+        let tables = (0..table_count)
+            .map(|i| {
+                let size = remaining.len() / (table_count - i);
+                remaining.by_ref().take(size).collect()
+            })
+            .collect();
+        
+        Ok(RoundSeating { city: city.clone(), tables, bye })
+    }
+    
+    /// The last player in `order` who hasn't had a bye yet, or the last one
+    /// if everybody has.
+    fn bye_player(&self, order: &[String]) -> String {
+        let had_bye = |player: &String| self.rounds.iter().any(|round| round.bye.as_ref() == Some(player));
+        order.iter().rev()
+            .find(|player| !had_bye(player))
+            .or(order.last())
+            .cloned()
+            .unwrap_or_default()
+    }
+    
+    fn shuffled_players(&self, seed: u64) -> Vec<String> {
+        let mut players = self.players.clone();
+        let mut state = seed;
+        for i in (1..players.len()).rev() {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let j = ((state >> 33) as usize) % (i + 1);
+            players.swap(i, j);
+        }
+        players
+    }
+    
+    /// The table playing a game, unless its results are in.
+    fn open_table(&mut self, game_id: Uuid) -> Option<&mut TournamentTable> {
+        self.rounds.iter_mut()
+            .flat_map(|round| round.tables.iter_mut())
+            .find(|table| table.game_id == game_id && table.results.is_none())
+    }
+    
+    /// Stores the results of a tournament game. Returns false if the game
+    /// isn't part of this tournament or was already recorded.
+    pub fn record_results(&mut self, game_id: Uuid, results: Vec<TableResult>) -> bool {
+        match self.open_table(game_id) {
+            Some(table) => {
+                table.results = Some(results);
+                true
+            },
+            None => false,
+        }
+    }
+    
+    /// Settles a table without its game, for when it can't be played to the
+    /// end. `forfeits` lose to everyone else at the table, who score nothing.
+    pub fn forfeit_table(&mut self, game_id: Uuid, forfeits: &[String]) -> Result<(), String> {
+        let table = self.open_table(game_id).ok_or("No table of this tournament is playing that game")?;
+        if let Some(stranger) = forfeits.iter().find(|name| !table.players.iter().any(|player| player.eq_ignore_ascii_case(name))) {
+            return Err(format!("{} isn't seated at this table", stranger));
+        }
+        
+        let results = table.players.iter()
+            .map(|player_name| TableResult {
+                player_name: player_name.clone(),
+                score: 0,
+                completed_lines: 0,
+                forfeited: forfeits.iter().any(|name| name.eq_ignore_ascii_case(player_name)),
+            })
+            .collect();
+        table.results = Some(results);
+        Ok(())
+    }
+    
+    /// Moves a table to a new game, for when its players have to sit down
+    /// again. Returns false if no table is playing `game_id`.
+    pub fn replace_game(&mut self, game_id: Uuid, new_game_id: Uuid) -> bool {
+        match self.open_table(game_id) {
+            Some(table) => {
+                table.game_id = new_game_id;
+                true
+            },
+            None => false,
+        }
+    }
+    
+    /// Every player, best first: by points, then by total score.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self.players.iter()
+            .map(|player_name| Standing {
+                player_name: player_name.clone(),
+                points: 0.0,
+                total_score: 0,
+                games_played: 0,
+                wins: 0,
+                completed_lines: 0,
+            })
+            .collect();
+        
+        let results = self.rounds.iter()
+            .flat_map(|round| &round.tables)
+            .filter_map(|table| table.results.as_ref());
+        for bye in self.rounds.iter().filter_map(|round| round.bye.as_ref()) {
+            if let Some(standing) = standings.iter_mut().find(|standing| standing.player_name == *bye) {
+                standing.points += Self::BYE_POINTS;
+            }
+        }
+        for table in results {
+            for result in table {
+                let Some(standing) = standings.iter_mut().find(|standing| standing.player_name.eq_ignore_ascii_case(&result.player_name)) else {
+                    continue;
+                };
+                let mut opponents = table.iter().filter(|other| other.player_name != result.player_name);
+                let points: f64 = opponents.clone()
+                    .map(|other| match (result.forfeited, other.forfeited) {
+                        (true, _) => 0.0,
+                        (false, true) => 1.0,
+                        (false, false) => match result.score.cmp(&other.score) {
+                            std::cmp::Ordering::Greater => 1.0,
+                            std::cmp::Ordering::Equal => 0.5,
+                            std::cmp::Ordering::Less => 0.0,
+                        },
+                    })
+                    .sum();
+                
+                standing.points += points;
+                if result.forfeited {
+                    continue;
+                }
+                standing.total_score += result.score;
+                standing.games_played += 1;
+                standing.completed_lines += result.completed_lines;
+                if table.len() > 1 && opponents.all(|other| other.forfeited || other.score <= result.score) {
+                    standing.wins += 1;
+                }
+            }
+        }
+        
+        // Stable, so equal standings keep the order players were listed in
+        standings.sort_by(|a, b| b.points.total_cmp(&a.points).then(b.total_score.cmp(&a.total_score)));
+        standings
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::{Card, GamePhase, GameState, LineId, PlayerAction, PlayerSheet, SubwayMap};
+
+/// Why a `PlayerAction` was refused. Checked before any state is changed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionError {
+    WrongPhase(GamePhase),
+    NoCardRevealed,
+    UnknownPlayer,
+    AlreadyPlayed,
+    WrongCard { required: Card },
+    UnknownLine(LineId),
+    LineFull(LineId),
+    WrongWindow { line_id: LineId, next_window: usize },
+    UnknownStation(String),
+    StationAlreadyMarked(String),
+    StationClosed(String),
+    NotATransferStation(String),
+    NoOpenLineAtStation(String), // Every line through the station is already complete for the player
+    LineNotComplete(LineId),
+    LineAlreadyCompleted(LineId),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::WrongPhase(GamePhase::Lobby) => write!(f, "The game has not started yet"),
+            ActionError::WrongPhase(GamePhase::Finished) => write!(f, "The game is over"),
+            ActionError::WrongPhase(_) => write!(f, "The round is over, wait for the next card"),
+            ActionError::NoCardRevealed => write!(f, "No card revealed"),
+            ActionError::UnknownPlayer => write!(f, "Player not found"),
+            ActionError::AlreadyPlayed => write!(f, "Already played the current card"),
+            ActionError::WrongCard { required: Card::Transfer } => write!(f, "Can only mark transfer station with transfer card"),
+            ActionError::WrongCard { required: Card::FreeRide } => write!(f, "Can only mark free ride station with free ride card"),
+            ActionError::WrongCard { required: Card::Closure } => write!(f, "Can only close a station with a closure card"),
+            ActionError::WrongCard { required } => write!(f, "This needs a {:?} card", required),
+            ActionError::UnknownLine(line_id) => write!(f, "Line {} not found", line_id.0),
+            ActionError::LineFull(line_id) => write!(f, "No empty windows available on line {}", line_id.0),
+            ActionError::WrongWindow { line_id, next_window } => {
+                write!(f, "The next free window on line {} is window {}", line_id.0, next_window)
+            },
+            ActionError::UnknownStation(station_id) => write!(f, "Station {} not found", station_id),
+            ActionError::StationAlreadyMarked(station_id) => write!(f, "Station {} is already marked", station_id),
+            ActionError::StationClosed(station_id) => write!(f, "Station {} is closed", station_id),
+            ActionError::NotATransferStation(station_id) => write!(f, "Station {} is not a transfer station", station_id),
+            ActionError::NoOpenLineAtStation(station_id) => {
+                write!(f, "Station {} is only on lines you already completed", station_id)
+            },
+            ActionError::LineNotComplete(line_id) => write!(f, "Line {} is not complete yet", line_id.0),
+            ActionError::LineAlreadyCompleted(line_id) => write!(f, "Line {} was already completed", line_id.0),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+impl GameState {
+    /// Checks an action against the rules, the current card and the map.
+    pub fn validate_action(&self, player_id: Uuid, action: &PlayerAction, subway_map: &SubwayMap) -> Result<(), ActionError> {
+        if self.phase != GamePhase::WaitingForActions {
+            return Err(ActionError::WrongPhase(self.phase));
+        }
+        let current_card = self.current_card.as_ref().ok_or(ActionError::NoCardRevealed)?;
+        let player = self.players.get(&player_id).ok_or(ActionError::UnknownPlayer)?;
+        
+        if !matches!(action, PlayerAction::CompleteLineAnnouncement { .. }) && self.has_acted(&player_id) {
+            return Err(ActionError::AlreadyPlayed);
+        }
+        
+        match action {
+            PlayerAction::ChooseLine { line_id, car_window_index } => {
+                if !subway_map.lines.contains_key(line_id) || !player.train_cars.contains_key(line_id) {
+                    return Err(ActionError::UnknownLine(line_id.clone()));
+                }
+                let next_window = player.next_empty_window(line_id)
+                    .ok_or_else(|| ActionError::LineFull(line_id.clone()))?;
+                if *car_window_index != next_window {
+                    return Err(ActionError::WrongWindow { line_id: line_id.clone(), next_window });
+                }
+            },
+            
+            PlayerAction::MarkTransferStation { .. } | PlayerAction::MarkFreeRideStation { .. } | PlayerAction::CloseStation { .. } => {
+                current_card.effect().validate_station_action(action, player, subway_map)?;
+            },
+            
+            PlayerAction::CompleteLineAnnouncement { line_id } => {
+                let line = subway_map.lines.get(line_id)
+                    .ok_or_else(|| ActionError::UnknownLine(line_id.clone()))?;
+                if player.completed_lines.contains(line_id) {
+                    return Err(ActionError::LineAlreadyCompleted(line_id.clone()));
+                }
+                if !line.stations.iter().all(|station_id| player.marked_stations.contains_key(station_id)) {
+                    return Err(ActionError::LineNotComplete(line_id.clone()));
+                }
+            },
+        }
+        
+        Ok(())
+    }
+}
+
+/// A station may be marked if it exists, is still empty and lies on a line
+/// the player has not completed yet.
+pub(crate) fn check_markable(player: &PlayerSheet, station_id: &str, subway_map: &SubwayMap) -> Result<(), ActionError> {
+    let station = subway_map.stations.get(station_id)
+        .ok_or_else(|| ActionError::UnknownStation(station_id.to_string()))?;
+    
+    if player.marked_stations.contains_key(station_id) {
+        return Err(ActionError::StationAlreadyMarked(station_id.to_string()));
+    }
+    if player.closed_stations.contains(station_id) {
+        return Err(ActionError::StationClosed(station_id.to_string()));
+    }
+    if station.lines.iter().all(|line_id| player.completed_lines.contains(line_id)) {
+        return Err(ActionError::NoOpenLineAtStation(station_id.to_string()));
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Card, City, CompletionStatus, GamePhase, GameState, LineId, PlayerInfo, PlayerSheet, StationMark, SubwayMap};
+
+/// What a client is allowed to know about a game. Unlike `GameState` it does not
+/// contain the order of the remaining deck, only how many cards are left.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GameStateView {
+    pub id: Uuid,
+    pub city: City,
+    pub viewer: Option<Uuid>, // The player this view was made for, None for spectators
+    pub players: HashMap<Uuid, PlayerSheet>,
+    pub scores: HashMap<Uuid, i32>,
+    pub roster: Vec<PlayerInfo>,
+    pub current_card: Option<Card>,
+    pub deck_count: usize,
+    pub discard_pile: Vec<Card>,
+    pub round: u32,
+    pub phase: GamePhase,
+    pub conductor: Uuid,
+    pub acted_this_round: HashSet<Uuid>,
+}
+
+/// One change to a `GameStateView`. The server sends these in `StateDelta`
+/// messages so clients don't need a full snapshot after every move.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum StatePatch {
+    CardRevealed(Option<Card>),
+    WindowFilled { player_id: Uuid, line_id: LineId, index: usize, value: Option<String> },
+    StationMarked { player_id: Uuid, station_id: String, mark: Option<StationMark> },
+    LineCompleted { player_id: Uuid, line_id: LineId },
+    LineStatusChanged { player_id: Uuid, line_id: LineId, status: CompletionStatus },
+    ScoreChanged { player_id: Uuid, score: i32 },
+    SheetReplaced(PlayerSheet), // New players and changes the smaller patches can't express
+    PlayerRemoved(Uuid),
+    RosterChanged(Vec<PlayerInfo>),
+    DeckChanged { deck_count: usize, discard_pile: Vec<Card> },
+    RoundChanged(u32),
+    PhaseChanged(GamePhase),
+    ConductorChanged(Uuid),
+    ActedChanged(Vec<Uuid>),
+}
+
+impl GameState {
+    /// The state as far as anyone may see it. Sheets are public, so players get
+    /// the same view, only with `viewer` set.
+    pub fn public_view(&self, subway_map: &SubwayMap) -> GameStateView {
+        GameStateView {
+            id: self.id,
+            city: self.city.clone(),
+            viewer: None,
+            players: self.players.clone(),
+            scores: self.calculate_final_scores(subway_map),
+            roster: self.roster.clone(),
+            current_card: self.current_card.clone(),
+            deck_count: self.deck.len(),
+            discard_pile: self.discard_pile.clone(),
+            round: self.round,
+            phase: self.phase,
+            conductor: self.conductor,
+            acted_this_round: self.acted_this_round.clone(),
+        }
+    }
+}
+
+impl GameStateView {
+    /// The patches that turn `self` into `new`, in a stable order. The viewer is
+    /// not part of the diff since it differs per client.
+    pub fn diff(&self, new: &GameStateView) -> Vec<StatePatch> {
+        let mut patches = Vec::new();
+        
+        if self.current_card != new.current_card {
+            patches.push(StatePatch::CardRevealed(new.current_card.clone()));
+        }
+        
+        let mut player_ids: Vec<&Uuid> = self.players.keys().chain(new.players.keys()).collect();
+        player_ids.sort();
+        player_ids.dedup();
+        
+        for player_id in player_ids {
+            match (self.players.get(player_id), new.players.get(player_id)) {
+                (Some(old_sheet), Some(new_sheet)) => {
+                    if old_sheet != new_sheet {
+                        patches.extend(diff_sheets(old_sheet, new_sheet));
+                    }
+                },
+                (None, Some(new_sheet)) => patches.push(StatePatch::SheetReplaced(new_sheet.clone())),
+                (Some(_), None) => patches.push(StatePatch::PlayerRemoved(*player_id)),
+                (None, None) => {},
+            }
+        }
+        
+        let mut score_changes: Vec<(&Uuid, &i32)> = new.scores.iter()
+            .filter(|(player_id, score)| self.scores.get(player_id) != Some(score))
+            .collect();
+        score_changes.sort();
+        for (player_id, score) in score_changes {
+            patches.push(StatePatch::ScoreChanged { player_id: *player_id, score: *score });
+        }
+        
+        if self.roster != new.roster {
+            patches.push(StatePatch::RosterChanged(new.roster.clone()));
+        }
+        if self.deck_count != new.deck_count || self.discard_pile != new.discard_pile {
+            patches.push(StatePatch::DeckChanged {
+                deck_count: new.deck_count,
+                discard_pile: new.discard_pile.clone(),
+            });
+        }
+        if self.round != new.round {
+            patches.push(StatePatch::RoundChanged(new.round));
+        }
+        if self.phase != new.phase {
+            patches.push(StatePatch::PhaseChanged(new.phase));
+        }
+        if self.conductor != new.conductor {
+            patches.push(StatePatch::ConductorChanged(new.conductor));
+        }
+        if self.acted_this_round != new.acted_this_round {
+            let mut acted: Vec<Uuid> = new.acted_this_round.iter().copied().collect();
+            acted.sort();
+            patches.push(StatePatch::ActedChanged(acted));
+        }
+        
+        patches
+    }
+    
+    pub fn apply(&mut self, patch: StatePatch) -> Result<(), String> {
+        match patch {
+            StatePatch::CardRevealed(card) => self.current_card = card,
+            StatePatch::WindowFilled { player_id, line_id, index, value } => {
+                let window = self.sheet_mut(&player_id)?
+                    .train_cars.get_mut(&line_id)
+                    .and_then(|windows| windows.get_mut(index))
+                    .ok_or_else(|| format!("Unknown window {} on line {}", index, line_id.0))?;
+                *window = value;
+            },
+            StatePatch::StationMarked { player_id, station_id, mark } => {
+                let sheet = self.sheet_mut(&player_id)?;
+                match mark {
+                    Some(mark) => { sheet.marked_stations.insert(station_id, mark); },
+                    None => { sheet.marked_stations.remove(&station_id); },
+                }
+            },
+            StatePatch::LineCompleted { player_id, line_id } => {
+                self.sheet_mut(&player_id)?.completed_lines.push(line_id);
+            },
+            StatePatch::LineStatusChanged { player_id, line_id, status } => {
+                self.sheet_mut(&player_id)?.line_completion_status.insert(line_id, status);
+            },
+            StatePatch::ScoreChanged { player_id, score } => {
+                self.scores.insert(player_id, score);
+            },
+            StatePatch::SheetReplaced(sheet) => {
+                self.players.insert(sheet.player_id, sheet);
+            },
+            StatePatch::PlayerRemoved(player_id) => {
+                self.players.remove(&player_id);
+                self.scores.remove(&player_id);
+            },
+            StatePatch::RosterChanged(roster) => self.roster = roster,
+            StatePatch::DeckChanged { deck_count, discard_pile } => {
+                self.deck_count = deck_count;
+                self.discard_pile = discard_pile;
+            },
+            StatePatch::RoundChanged(round) => self.round = round,
+            StatePatch::PhaseChanged(phase) => self.phase = phase,
+            StatePatch::ConductorChanged(conductor) => self.conductor = conductor,
+            StatePatch::ActedChanged(acted) => self.acted_this_round = acted.into_iter().collect(),
+        }
+        
+        Ok(())
+    }
+    
+    fn sheet_mut(&mut self, player_id: &Uuid) -> Result<&mut PlayerSheet, String> {
+        self.players.get_mut(player_id)
+            .ok_or_else(|| format!("Unknown player {}", player_id))
+    }
+}
+
+/// Falls back to replacing the whole sheet when a change is not a plain
+/// window, station or completion update, closures included.
+fn diff_sheets(old: &PlayerSheet, new: &PlayerSheet) -> Vec<StatePatch> {
+    let replace = || vec![StatePatch::SheetReplaced(new.clone())];
+    let player_id = new.player_id;
+    let mut patches = Vec::new();
+    
+    if old.city != new.city
+        || !new.completed_lines.starts_with(&old.completed_lines)
+        || old.line_completion_status.keys().any(|line_id| !new.line_completion_status.contains_key(line_id))
+        || old.train_cars.len() != new.train_cars.len()
+        || old.closed_stations != new.closed_stations
+    {
+        return replace();
+    }
+    
+    let mut line_ids: Vec<&LineId> = new.train_cars.keys().collect();
+    line_ids.sort_by(|a, b| a.0.cmp(&b.0));
+    for line_id in line_ids {
+        let new_windows = &new.train_cars[line_id];
+        let Some(old_windows) = old.train_cars.get(line_id) else {
+            return replace();
+        };
+        if old_windows.len() != new_windows.len() {
+            return replace();
+        }
+        
+        for (index, (old_value, new_value)) in old_windows.iter().zip(new_windows).enumerate() {
+            if old_value != new_value {
+                patches.push(StatePatch::WindowFilled {
+                    player_id,
+                    line_id: line_id.clone(),
+                    index,
+                    value: new_value.clone(),
+                });
+            }
+        }
+    }
+    
+    let mut station_ids: Vec<&String> = old.marked_stations.keys().chain(new.marked_stations.keys()).collect();
+    station_ids.sort();
+    station_ids.dedup();
+    for station_id in station_ids {
+        let new_mark = new.marked_stations.get(station_id);
+        if old.marked_stations.get(station_id) != new_mark {
+            patches.push(StatePatch::StationMarked {
+                player_id,
+                station_id: station_id.clone(),
+                mark: new_mark.cloned(),
+            });
+        }
+    }
+    
+    for line_id in &new.completed_lines[old.completed_lines.len()..] {
+        patches.push(StatePatch::LineCompleted { player_id, line_id: line_id.clone() });
+    }
+    
+    let mut status_changes: Vec<(&LineId, &CompletionStatus)> = new.line_completion_status.iter()
+        .filter(|(line_id, status)| old.line_completion_status.get(line_id) != Some(status))
+        .collect();
+    status_changes.sort_by(|a, b| a.0.0.cmp(&b.0.0));
+    for (line_id, status) in status_changes {
+        patches.push(StatePatch::LineStatusChanged {
+            player_id,
+            line_id: line_id.clone(),
+            status: status.clone(),
+        });
+    }
+    
+    patches
+}
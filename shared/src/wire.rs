@@ -0,0 +1,27 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// How messages are encoded on a connection. JSON is always understood and is
+/// used for the handshake; a client may ask for MessagePack in `Hello`, after
+/// which the server sends binary frames.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+pub fn encode<T: Serialize>(value: &T, format: WireFormat) -> Result<Vec<u8>, String> {
+    match format {
+        WireFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        WireFormat::MessagePack => rmp_serde::to_vec(value).map_err(|e| e.to_string()),
+    }
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8], format: WireFormat) -> Result<T, String> {
+    match format {
+        WireFormat::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        WireFormat::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+    }
+}
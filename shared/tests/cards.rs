@@ -0,0 +1,164 @@
+mod common;
+
+use uuid::Uuid;
+use verplant::{ActionError, Card, Expansion, PlayerAction, RuleSet, StationMark};
+
+use common::{Game, red};
+
+/// A game with every expansion and `card` revealed.
+fn game_with_card(card: Card) -> Game {
+    Game::with_rules(RuleSet { expansions: Expansion::ALL.to_vec(), ..RuleSet::default() }, card)
+}
+
+fn play(game: &mut Game, player: Uuid, action: PlayerAction) -> Result<(), String> {
+    game.state.process_player_action(player, action, &game.map).map(|_| ())
+}
+
+/// Plays the current card on the next window of the red line.
+fn play_red(game: &mut Game, player: Uuid) -> Result<(), String> {
+    let car_window_index = game.state.players[&player].next_empty_window(&red()).unwrap();
+    play(game, player, PlayerAction::ChooseLine { line_id: red(), car_window_index })
+}
+
+fn close(station_id: &str) -> PlayerAction {
+    PlayerAction::CloseStation { station_id: station_id.to_string() }
+}
+
+#[test]
+fn expansions_add_their_cards_to_the_deck() {
+    let classic = RuleSet::default();
+    let expanded = RuleSet { expansions: Expansion::ALL.to_vec(), ..RuleSet::default() };
+    
+    let deck = expanded.create_deck();
+    assert_eq!(deck.len(), classic.create_deck().len() + 5);
+    assert!(deck.contains(&Card::Closure));
+    assert!(!classic.create_deck().contains(&Card::Delay));
+    
+    let twice = RuleSet { expansions: vec![Expansion::Delay, Expansion::Delay], ..RuleSet::default() };
+    assert!(twice.validate().is_err());
+}
+
+#[test]
+fn delay_fills_a_window_without_marking() {
+    let mut game = game_with_card(Card::Delay);
+    let player = game.player;
+    play_red(&mut game, player).unwrap();
+    
+    let sheet = &game.state.players[&player];
+    assert_eq!(sheet.train_cars[&red()][0].as_deref(), Some("-"));
+    assert!(sheet.marked_stations.is_empty());
+    assert!(game.state.has_acted(&player));
+}
+
+#[test]
+fn double_is_played_twice() {
+    let mut game = game_with_card(Card::Double(2));
+    let player = game.player;
+    
+    play_red(&mut game, player).unwrap();
+    assert!(!game.state.has_acted(&player));
+    
+    play_red(&mut game, player).unwrap();
+    assert!(game.state.has_acted(&player));
+    
+    let sheet = &game.state.players[&player];
+    assert_eq!(sheet.train_cars[&red()][..2], [Some("2".to_string()), Some("2".to_string())]);
+    // Like any number card the second play stops at the stations the first one marked
+    assert_eq!(sheet.marked_stations.len(), 2);
+    
+    assert_eq!(play_red(&mut game, player), Err(ActionError::AlreadyPlayed.to_string()));
+}
+
+#[test]
+fn double_ends_early_without_a_second_window() {
+    let mut game = game_with_card(Card::Double(2));
+    let player = game.player;
+    // Only the red line's last window is left
+    for (line_id, windows) in game.state.players.get_mut(&player).unwrap().train_cars.iter_mut() {
+        let filled = if *line_id == red() { windows.len() - 1 } else { windows.len() };
+        windows[..filled].fill(Some("1".to_string()));
+    }
+    
+    play_red(&mut game, player).unwrap();
+    assert!(game.state.has_acted(&player));
+}
+
+#[test]
+fn closure_closes_a_station_for_everyone_when_the_round_ends() {
+    let mut game = game_with_card(Card::Closure);
+    let (player, other) = (game.player, game.other);
+    game.state.players.get_mut(&other).unwrap().marked_stations.insert("dam".to_string(), StationMark::Cross);
+    
+    play(&mut game, player, close("dam")).unwrap();
+    assert!(game.state.has_acted(&player));
+    assert!(game.state.players[&player].closed_stations.is_empty());
+    
+    assert_eq!(
+        play(&mut game, other, close("dam")),
+        Err(ActionError::StationAlreadyMarked("dam".to_string()).to_string()),
+    );
+    assert_eq!(
+        play(&mut game, other, close("nowhere")),
+        Err(ActionError::UnknownStation("nowhere".to_string()).to_string()),
+    );
+    
+    game.state.pass(other);
+    game.state.next_round().unwrap();
+    
+    let sheet = &game.state.players[&player];
+    assert!(sheet.closed_stations.contains("dam"));
+    assert!(!sheet.marked_stations.contains_key("dam"));
+    // Stations someone already marked stay theirs
+    assert!(game.state.players[&other].closed_stations.is_empty());
+    assert!(game.state.pending_closures.is_empty());
+}
+
+#[test]
+fn closed_stations_cannot_be_marked_or_complete_a_line() {
+    let mut game = game_with_card(Card::FreeRide);
+    let player = game.player;
+    game.state.players.get_mut(&player).unwrap().closed_stations.insert("dam".to_string());
+    
+    let action = PlayerAction::MarkFreeRideStation { station_id: "dam".to_string() };
+    assert_eq!(
+        play(&mut game, player, action),
+        Err(ActionError::StationClosed("dam".to_string()).to_string()),
+    );
+    
+    let sheet = game.state.players.get_mut(&player).unwrap();
+    for station_id in ["central", "museum"] {
+        sheet.marked_stations.insert(station_id.to_string(), StationMark::Cross);
+    }
+    assert!(!sheet.check_line_completion(&red(), &game.map));
+}
+
+#[test]
+fn closed_stations_are_not_empty() {
+    let mut game = game_with_card(Card::Number(1));
+    let player = game.player;
+    let rules = RuleSet { empty_station_penalty_divisor: 1, ..RuleSet::default() };
+    
+    let open = game.state.players[&player].calculate_score(&rules, &game.map);
+    game.state.players.get_mut(&player).unwrap().closed_stations.insert("dam".to_string());
+    let closed = game.state.players[&player].calculate_score(&rules, &game.map);
+    
+    assert_eq!(closed, open + 1);
+}
+
+#[test]
+fn station_actions_need_the_matching_card() {
+    let mut game = game_with_card(Card::Number(2));
+    let player = game.player;
+    assert_eq!(
+        play(&mut game, player, close("dam")),
+        Err(ActionError::WrongCard { required: Card::Closure }.to_string()),
+    );
+    
+    let mut game = game_with_card(Card::Closure);
+    let player = game.player;
+    let action = PlayerAction::MarkFreeRideStation { station_id: "dam".to_string() };
+    assert_eq!(
+        play(&mut game, player, action),
+        Err(ActionError::WrongCard { required: Card::FreeRide }.to_string()),
+    );
+}
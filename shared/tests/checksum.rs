@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+use verplant::{City, GameState, GameStateView, SubwayMap};
+
+fn new_game(player_ids: &[Uuid]) -> (GameState, SubwayMap) {
+    let subway_map = SubwayMap::builtin(&City::Amsterdam);
+    let mut game_state = GameState::new(City::Amsterdam, player_ids[0]);
+    for (index, player_id) in player_ids.iter().enumerate() {
+        game_state.add_player(*player_id, format!("Player {}", index), &subway_map);
+    }
+    (game_state, subway_map)
+}
+
+/// Plays the default move for every player, in id order, for a few rounds.
+fn play_rounds(game_state: &mut GameState, subway_map: &SubwayMap, rounds: usize) {
+    for _ in 0..rounds {
+        if game_state.reveal_card().is_none() {
+            break;
+        }
+        
+        let mut player_ids: Vec<Uuid> = game_state.players.keys().copied().collect();
+        player_ids.sort();
+        for player_id in player_ids {
+            match game_state.default_action(player_id) {
+                Some(action) => {
+                    if game_state.process_player_action(player_id, action, subway_map).is_err() {
+                        game_state.pass(player_id);
+                    }
+                },
+                None => game_state.pass(player_id),
+            }
+        }
+        
+        game_state.next_round().unwrap();
+    }
+}
+
+#[test]
+fn checksum_does_not_depend_on_map_order() {
+    let player_ids: Vec<Uuid> = (0..8).map(|_| Uuid::new_v4()).collect();
+    let (game_state, subway_map) = new_game(&player_ids);
+    
+    let mut reordered = game_state.clone();
+    let mut players = HashMap::with_capacity(64);
+    for player_id in player_ids.iter().rev() {
+        players.insert(*player_id, game_state.players[player_id].clone());
+    }
+    reordered.players = players;
+    
+    assert_eq!(game_state.checksum(), reordered.checksum());
+    assert_eq!(game_state.public_view(&subway_map).checksum(), reordered.public_view(&subway_map).checksum());
+}
+
+#[test]
+fn checksum_changes_when_a_player_acts() {
+    let player_ids = [Uuid::new_v4(), Uuid::new_v4()];
+    let (mut game_state, subway_map) = new_game(&player_ids);
+    game_state.reveal_card();
+    let before = game_state.checksum();
+    
+    let action = game_state.default_action(player_ids[0]).unwrap();
+    game_state.process_player_action(player_ids[0], action, &subway_map).unwrap();
+    
+    assert_ne!(before, game_state.checksum());
+}
+
+#[test]
+fn view_checksum_ignores_the_viewer() {
+    let player_ids = [Uuid::new_v4(), Uuid::new_v4()];
+    let (game_state, subway_map) = new_game(&player_ids);
+    
+    let public = game_state.public_view(&subway_map);
+    let first = GameStateView { viewer: Some(player_ids[0]), ..public.clone() };
+    let second = GameStateView { viewer: Some(player_ids[1]), ..public.clone() };
+    assert_eq!(first.checksum(), second.checksum());
+    assert_eq!(first.checksum(), public.checksum());
+}
+
+#[test]
+fn applying_a_diff_reproduces_the_checksum() {
+    let player_ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+    let (mut game_state, subway_map) = new_game(&player_ids);
+    let mut client_view = game_state.public_view(&subway_map);
+    
+    for _ in 0..5 {
+        play_rounds(&mut game_state, &subway_map, 1);
+        let server_view = game_state.public_view(&subway_map);
+        
+        for patch in client_view.diff(&server_view) {
+            client_view.apply(patch).unwrap();
+        }
+        assert_eq!(client_view.checksum(), server_view.checksum());
+        assert_eq!(client_view, server_view);
+    }
+}
+
+#[test]
+fn replaying_the_same_moves_gives_the_same_checksum() {
+    let player_ids = [Uuid::new_v4(), Uuid::new_v4()];
+    let (game_state, subway_map) = new_game(&player_ids);
+    
+    let mut first = game_state.clone();
+    let mut second = game_state;
+    play_rounds(&mut first, &subway_map, 8);
+    play_rounds(&mut second, &subway_map, 8);
+    
+    assert_eq!(first.checksum(), second.checksum());
+}
@@ -0,0 +1,39 @@
+//! Games on the built-in Amsterdam map for the rules tests. The red line runs
+//! central - dam - museum, the blue line only has central, a transfer hub.
+#![allow(dead_code)] // Not every test file uses every helper
+
+use uuid::Uuid;
+use verplant::{Card, City, GameState, LineId, RuleSet, SubwayMap};
+
+pub struct Game {
+    pub state: GameState,
+    pub map: SubwayMap,
+    pub player: Uuid,
+    pub other: Uuid,
+}
+
+impl Game {
+    /// A two player game dealt from a fixed seed, with `card` revealed.
+    pub fn with_rules(rules: RuleSet, card: Card) -> Game {
+        let map = SubwayMap::builtin(&City::Amsterdam);
+        let player = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        
+        let mut state = GameState::with_seed(City::Amsterdam, player, rules, 1);
+        state.add_player(player, "Ann".to_string(), &map);
+        state.add_player(other, "Bob".to_string(), &map);
+        state.deck.push(card);
+        state.start_game().unwrap();
+        
+        Game { state, map, player, other }
+    }
+}
+
+/// A two player game with the default rules and `card` revealed.
+pub fn game_with_card(card: Card) -> Game {
+    Game::with_rules(RuleSet::default(), card)
+}
+
+pub fn red() -> LineId {
+    LineId("red".to_string())
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use uuid::Uuid;
+use verplant::{Card, City, CompletionStatus, GameMessage, GameState, PlayerAction, RuleSet, StationMark, SubwayMap};
+
+use common::red;
+
+/// An Amsterdam game where every player has already marked the whole red line
+/// and only needs to announce it. The first card is a 1, so no 6 reshuffles
+/// the deck at the end of the round.
+fn new_game(players: usize) -> (GameState, SubwayMap, Vec<Uuid>) {
+    let subway_map = SubwayMap::builtin(&City::Amsterdam);
+    let mut player_ids: Vec<Uuid> = (0..players).map(|_| Uuid::new_v4()).collect();
+    player_ids.sort();
+    
+    let mut game_state = GameState::with_seed(City::Amsterdam, player_ids[0], RuleSet::default(), 1);
+    for (index, player_id) in player_ids.iter().enumerate() {
+        game_state.add_player(*player_id, format!("Player {}", index), &subway_map);
+        let sheet = game_state.players.get_mut(player_id).unwrap();
+        for station_id in ["central", "dam", "museum"] {
+            sheet.marked_stations.insert(station_id.to_string(), StationMark::Cross);
+        }
+    }
+    game_state.deck.push(Card::Number(1));
+    game_state.start_game().unwrap();
+    
+    (game_state, subway_map, player_ids)
+}
+
+fn announce(game_state: &mut GameState, subway_map: &SubwayMap, player_id: Uuid) {
+    let action = PlayerAction::CompleteLineAnnouncement { line_id: red() };
+    game_state.process_player_action(player_id, action, subway_map).unwrap();
+}
+
+fn status(game_state: &GameState, player_id: Uuid) -> &CompletionStatus {
+    &game_state.players[&player_id].line_completion_status[&red()]
+}
+
+#[test]
+fn completions_in_the_same_round_all_count_as_first() {
+    let (mut game_state, subway_map, player_ids) = new_game(2);
+    announce(&mut game_state, &subway_map, player_ids[1]);
+    announce(&mut game_state, &subway_map, player_ids[0]);
+    
+    assert_eq!(status(&game_state, player_ids[0]), &CompletionStatus::NotCompleted);
+    
+    let messages = game_state.next_round().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert!(messages.iter().all(|message| matches!(message, GameMessage::LineCompleted { line_id, .. } if *line_id == red())));
+    assert_eq!(status(&game_state, player_ids[0]), &CompletionStatus::FirstToComplete(6));
+    assert_eq!(status(&game_state, player_ids[1]), &CompletionStatus::FirstToComplete(6));
+    assert!(game_state.pending_completions.is_empty());
+}
+
+#[test]
+fn completions_in_a_later_round_get_the_later_points() {
+    let (mut game_state, subway_map, player_ids) = new_game(2);
+    announce(&mut game_state, &subway_map, player_ids[0]);
+    game_state.next_round().unwrap();
+    
+    game_state.reveal_card().unwrap();
+    announce(&mut game_state, &subway_map, player_ids[1]);
+    game_state.next_round().unwrap();
+    
+    assert_eq!(status(&game_state, player_ids[0]), &CompletionStatus::FirstToComplete(6));
+    assert_eq!(status(&game_state, player_ids[1]), &CompletionStatus::LaterCompletion(3));
+}
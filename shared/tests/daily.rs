@@ -0,0 +1,39 @@
+use verplant::DailyChallenge;
+
+#[test]
+fn days_have_dates() {
+    assert_eq!(DailyChallenge::for_day(0).date, "1970-01-01");
+    assert_eq!(DailyChallenge::for_day(11_016).date, "2000-02-29");
+    assert_eq!(DailyChallenge::for_day(20_088).date, "2024-12-31");
+    
+    for day in [0, 59, 365, 11_016, 20_088, 20_089, 40_000] {
+        let challenge = DailyChallenge::for_day(day);
+        assert_eq!(DailyChallenge::for_date(&challenge.date), Ok(challenge));
+    }
+}
+
+#[test]
+fn everyone_gets_the_same_challenge_on_a_day() {
+    assert_eq!(DailyChallenge::for_date("2025-06-01"), DailyChallenge::for_date("2025-06-01"));
+    assert_ne!(DailyChallenge::for_date("2025-06-01").unwrap().seed, DailyChallenge::for_date("2025-06-02").unwrap().seed);
+}
+
+#[test]
+fn invalid_dates_are_rejected() {
+    for date in ["", "2025-6-1", "2025-02-30", "2025-13-01", "1969-12-31", "../games/x", "2025-06-01.json"] {
+        assert!(DailyChallenge::for_date(date).is_err(), "{}", date);
+    }
+}
+
+#[test]
+fn scores_are_taken_until_shortly_after_the_day() {
+    let challenge = DailyChallenge::for_date("1970-01-02").unwrap();
+    let grace = 3600;
+    
+    assert!(!challenge.accepts_scores_at(86_399, grace));
+    assert!(challenge.accepts_scores_at(86_400, grace));
+    assert!(challenge.accepts_scores_at(2 * 86_400 - 1, grace));
+    // A game started before midnight can still be sent in for a while
+    assert!(challenge.accepts_scores_at(2 * 86_400 + grace - 1, grace));
+    assert!(!challenge.accepts_scores_at(2 * 86_400 + grace, grace));
+}
@@ -0,0 +1,96 @@
+use uuid::Uuid;
+use verplant::{Card, City, GameMessage, GamePhase, GameState, ReshuffleReason, RuleSet, SubwayMap};
+
+const SEED: u64 = 0x5eed;
+
+/// A seeded one player game. The player only ever passes, so the game never ends.
+fn new_game(seed: u64) -> (GameState, Uuid) {
+    let subway_map = SubwayMap::builtin(&City::Amsterdam);
+    let player_id = Uuid::new_v4();
+    let mut game_state = GameState::with_seed(City::Amsterdam, player_id, RuleSet::default(), seed);
+    game_state.add_player(player_id, "Ann".to_string(), &subway_map);
+    (game_state, player_id)
+}
+
+fn play_round(game_state: &mut GameState, player_id: Uuid) -> Vec<GameMessage> {
+    game_state.pass(player_id);
+    game_state.next_round().unwrap()
+}
+
+fn reshuffles(messages: &[GameMessage]) -> Vec<(ReshuffleReason, usize, usize)> {
+    messages.iter()
+        .filter_map(|message| match message {
+            GameMessage::DeckReshuffled { reason, deck_count, discard_count } => Some((*reason, *deck_count, *discard_count)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn the_same_seed_deals_the_same_cards() {
+    let (mut first, first_player) = new_game(SEED);
+    let (mut second, second_player) = new_game(SEED);
+    assert_eq!(first.deck, second.deck);
+    
+    // Long enough to go through several reshuffles
+    for _ in 0..60 {
+        let card = first.reveal_card();
+        assert_eq!(card, second.reveal_card());
+        assert!(card.is_some());
+        
+        assert_eq!(reshuffles(&play_round(&mut first, first_player)), reshuffles(&play_round(&mut second, second_player)));
+        assert_eq!(first.deck, second.deck);
+        assert_eq!(first.discard_pile, second.discard_pile);
+    }
+}
+
+#[test]
+fn different_seeds_deal_different_cards() {
+    assert_ne!(new_game(SEED).0.deck, new_game(SEED + 1).0.deck);
+}
+
+#[test]
+fn card_six_is_reshuffled_after_the_round() {
+    let (mut game_state, player_id) = new_game(SEED);
+    game_state.deck = vec![Card::Number(1), Card::Number(2), Card::Six];
+    game_state.discard_pile = vec![Card::Number(3), Card::Number(4)];
+    
+    assert_eq!(game_state.start_game(), Ok(Card::Six));
+    assert_eq!(game_state.deck.len(), 2);
+    assert_eq!(game_state.discard_pile.len(), 2);
+    
+    let messages = play_round(&mut game_state, player_id);
+    assert_eq!(reshuffles(&messages), vec![(ReshuffleReason::CardSix, 5, 0)]);
+    assert!(game_state.deck.contains(&Card::Six));
+    assert!(game_state.discard_pile.is_empty());
+    assert_eq!(game_state.phase, GamePhase::ResolvingCardSix);
+    assert!(game_state.reveal_card().is_some());
+    assert_eq!(game_state.phase, GamePhase::WaitingForActions);
+}
+
+#[test]
+fn an_empty_deck_is_refilled_from_the_discard_pile() {
+    let (mut game_state, player_id) = new_game(SEED);
+    game_state.deck = vec![Card::Number(1)];
+    game_state.discard_pile = vec![Card::Number(2), Card::Number(3)];
+    
+    game_state.start_game().unwrap();
+    assert!(game_state.deck.is_empty());
+    
+    let messages = play_round(&mut game_state, player_id);
+    assert_eq!(reshuffles(&messages), vec![(ReshuffleReason::DeckEmpty, 3, 0)]);
+    assert!(game_state.reveal_card().is_some());
+}
+
+#[test]
+fn other_cards_go_to_the_discard_pile() {
+    let (mut game_state, player_id) = new_game(SEED);
+    game_state.deck = vec![Card::Number(1), Card::Number(2)];
+    
+    game_state.start_game().unwrap();
+    let messages = play_round(&mut game_state, player_id);
+    
+    assert!(reshuffles(&messages).is_empty());
+    assert_eq!(game_state.deck, vec![Card::Number(1)]);
+    assert_eq!(game_state.discard_pile, vec![Card::Number(2)]);
+}
@@ -0,0 +1,294 @@
+//! Pins the JSON shape of every `GameMessage` variant. If a change here is
+//! intended, bump `PROTOCOL_VERSION` and regenerate the files with
+//! `UPDATE_GOLDEN=1 cargo test -p verplant --test golden`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use uuid::Uuid;
+use verplant::{
+    Card, ChatEntry, City, ClientKind, ClientRequest, CompletionStatus, Emote, GameMessage, GamePhase, GameStateView, LineId, PROTOCOL_VERSION,
+    PlayerAction, PlayerInfo, PlayerSheet, ReactionTarget, ReshuffleReason, StatePatch, Station, StationMark, SubwayLine, SubwayMap,
+    WireFormat, wire,
+};
+
+const PLAYER: Uuid = Uuid::from_u128(1);
+const GAME: Uuid = Uuid::from_u128(2);
+const TOKEN: Uuid = Uuid::from_u128(3);
+
+/// The name of a message's variant. Deliberately exhaustive so that adding a
+/// variant does not compile until it has a golden file.
+fn variant_name(message: &GameMessage) -> &'static str {
+    match message {
+        GameMessage::Hello { .. } => "Hello",
+        GameMessage::JoinGame { .. } => "JoinGame",
+        GameMessage::Rejoin { .. } => "Rejoin",
+        GameMessage::Authenticate { .. } => "Authenticate",
+        GameMessage::PlayerAction(_) => "PlayerAction",
+        GameMessage::StartGame => "StartGame",
+        GameMessage::RequestSnapshot => "RequestSnapshot",
+        GameMessage::Spectate { .. } => "Spectate",
+        GameMessage::SendChat { .. } => "SendChat",
+        GameMessage::React { .. } => "React",
+        GameMessage::Welcome { .. } => "Welcome",
+        GameMessage::HelloRejected { .. } => "HelloRejected",
+        GameMessage::Authenticated { .. } => "Authenticated",
+        GameMessage::Queued { .. } => "Queued",
+        GameMessage::GameJoined { .. } => "GameJoined",
+        GameMessage::RosterUpdated(_) => "RosterUpdated",
+        GameMessage::Spectating { .. } => "Spectating",
+        GameMessage::SpectatorsChanged { .. } => "SpectatorsChanged",
+        GameMessage::Chat(_) => "Chat",
+        GameMessage::ChatHistory(_) => "ChatHistory",
+        GameMessage::Reaction { .. } => "Reaction",
+        GameMessage::StateSnapshot { .. } => "StateSnapshot",
+        GameMessage::StateDelta { .. } => "StateDelta",
+        GameMessage::CardRevealed { .. } => "CardRevealed",
+        GameMessage::PlayerTimedOut { .. } => "PlayerTimedOut",
+        GameMessage::PlayerActionResult { .. } => "PlayerActionResult",
+        GameMessage::LineCompleted { .. } => "LineCompleted",
+        GameMessage::DeckReshuffled { .. } => "DeckReshuffled",
+        GameMessage::GameEnded { .. } => "GameEnded",
+        GameMessage::Error { .. } => "Error",
+    }
+}
+
+fn line() -> LineId {
+    LineId("red".to_string())
+}
+
+fn subway_map() -> SubwayMap {
+    SubwayMap {
+        city: City::Amsterdam,
+        stations: HashMap::from([("central".to_string(), Station {
+            id: "central".to_string(),
+            x: 100.0,
+            y: 100.0,
+            lines: vec![line()],
+            is_transfer_hub: false,
+        })]),
+        lines: HashMap::from([(line(), SubwayLine {
+            id: line(),
+            color: "#FF0000".to_string(),
+            stations: vec!["central".to_string()],
+            is_ring: false,
+            completion_points: (10, 5),
+        })]),
+        special_stations: Vec::new(),
+    }
+}
+
+fn player_info() -> PlayerInfo {
+    PlayerInfo {
+        id: PLAYER,
+        name: "Ann".to_string(),
+        color: "#E6194B".to_string(),
+        connected: true,
+        is_bot: false,
+        is_conductor: true,
+    }
+}
+
+fn sheet() -> PlayerSheet {
+    PlayerSheet {
+        player_id: PLAYER,
+        city: City::Amsterdam,
+        train_cars: HashMap::from([(line(), vec![Some("3".to_string()), None])]),
+        marked_stations: HashMap::from([("central".to_string(), StationMark::Cross)]),
+        completed_lines: Vec::new(),
+        line_completion_status: HashMap::from([(line(), CompletionStatus::NotCompleted)]),
+        closed_stations: HashSet::from(["museum".to_string()]),
+    }
+}
+
+fn chat_entry() -> ChatEntry {
+    ChatEntry {
+        sender: PLAYER,
+        text: "Hi!".to_string(),
+        sent_at: 1_700_000_000_000,
+        from_spectator: false,
+    }
+}
+
+fn state_view() -> GameStateView {
+    GameStateView {
+        id: GAME,
+        city: City::Amsterdam,
+        viewer: Some(PLAYER),
+        players: HashMap::from([(PLAYER, sheet())]),
+        scores: HashMap::from([(PLAYER, 3)]),
+        roster: vec![player_info()],
+        current_card: Some(Card::Express(3)),
+        deck_count: 12,
+        discard_pile: vec![Card::Number(1), Card::Transfer],
+        round: 4,
+        phase: GamePhase::WaitingForActions,
+        conductor: PLAYER,
+        acted_this_round: HashSet::from([PLAYER]),
+    }
+}
+
+fn samples() -> Vec<(String, GameMessage)> {
+    let mut samples = vec![
+        GameMessage::Hello {
+            protocol_version: PROTOCOL_VERSION,
+            client_kind: ClientKind::Web,
+            wire_formats: vec![WireFormat::MessagePack, WireFormat::Json],
+        },
+        GameMessage::JoinGame { player_name: "Ann".to_string(), city: City::Amsterdam, game_id: Some(GAME) },
+        GameMessage::Rejoin { game_id: GAME, player_id: PLAYER, token: TOKEN },
+        GameMessage::Authenticate { token: TOKEN },
+        GameMessage::StartGame,
+        GameMessage::RequestSnapshot,
+        GameMessage::Spectate { game_id: GAME },
+        GameMessage::SendChat { text: "Hi!".to_string() },
+        GameMessage::React {
+            emote: Emote::Train,
+            target: Some(ReactionTarget::LineCompleted { player_id: PLAYER, line_id: line() }),
+        },
+        GameMessage::Welcome {
+            protocol_version: PROTOCOL_VERSION,
+            supported_versions: vec![PROTOCOL_VERSION],
+            capabilities: vec!["chat".to_string()],
+            wire_format: WireFormat::MessagePack,
+        },
+        GameMessage::HelloRejected { reason: "Too old".to_string(), supported_versions: vec![PROTOCOL_VERSION] },
+        GameMessage::Authenticated { username: "Ann".to_string() },
+        GameMessage::Queued { waiting: 3 },
+        GameMessage::GameJoined {
+            player_id: PLAYER,
+            game_id: GAME,
+            subway_map: subway_map(),
+            roster: vec![player_info()],
+            rejoin_token: TOKEN,
+        },
+        GameMessage::RosterUpdated(vec![player_info()]),
+        GameMessage::Spectating { game_id: GAME, subway_map: subway_map() },
+        GameMessage::SpectatorsChanged { count: 2 },
+        GameMessage::Chat(chat_entry()),
+        GameMessage::ChatHistory(vec![chat_entry()]),
+        GameMessage::Reaction { sender: PLAYER, emote: Emote::Clap, target: Some(ReactionTarget::Player(PLAYER)) },
+        GameMessage::StateSnapshot { version: 7, checksum: 42, state: state_view() },
+        GameMessage::StateDelta {
+            version: 8,
+            checksum: 43,
+            patches: vec![
+                StatePatch::CardRevealed(Some(Card::Six)),
+                StatePatch::WindowFilled { player_id: PLAYER, line_id: line(), index: 1, value: Some("6".to_string()) },
+                StatePatch::StationMarked { player_id: PLAYER, station_id: "central".to_string(), mark: Some(StationMark::TransferNumber(2)) },
+                StatePatch::LineCompleted { player_id: PLAYER, line_id: line() },
+                StatePatch::LineStatusChanged { player_id: PLAYER, line_id: line(), status: CompletionStatus::FirstToComplete(10) },
+                StatePatch::ScoreChanged { player_id: PLAYER, score: 13 },
+                StatePatch::SheetReplaced(sheet()),
+                StatePatch::PlayerRemoved(PLAYER),
+                StatePatch::RosterChanged(vec![player_info()]),
+                StatePatch::DeckChanged { deck_count: 11, discard_pile: vec![Card::FreeRide] },
+                StatePatch::RoundChanged(5),
+                StatePatch::PhaseChanged(GamePhase::Finished),
+                StatePatch::ConductorChanged(PLAYER),
+                StatePatch::ActedChanged(vec![PLAYER]),
+            ],
+        },
+        GameMessage::CardRevealed {
+            card: Card::Number(4),
+            deadline: Some(1_700_000_060_000),
+            time_banks: HashMap::from([(PLAYER, 30_000)]),
+        },
+        GameMessage::PlayerTimedOut {
+            player_id: PLAYER,
+            action: Some(PlayerAction::ChooseLine { line_id: line(), car_window_index: 0 }),
+        },
+        GameMessage::PlayerActionResult { request_id: Some(17), success: true, message: "Marked 3 stations".to_string() },
+        GameMessage::LineCompleted { player_id: PLAYER, line_id: line() },
+        GameMessage::DeckReshuffled { reason: ReshuffleReason::CardSix, deck_count: 9, discard_count: 0 },
+        GameMessage::GameEnded { scores: HashMap::from([(PLAYER, 13)]) },
+        GameMessage::Error { request_id: Some(18), message: "Game not found".to_string() },
+    ]
+    .into_iter()
+    .map(|message| (variant_name(&message).to_string(), message))
+    .collect::<Vec<_>>();
+    
+    let actions = [
+        ("ChooseLine", PlayerAction::ChooseLine { line_id: line(), car_window_index: 1 }),
+        ("MarkTransferStation", PlayerAction::MarkTransferStation { station_id: "central".to_string() }),
+        ("MarkFreeRideStation", PlayerAction::MarkFreeRideStation { station_id: "central".to_string() }),
+        ("CompleteLineAnnouncement", PlayerAction::CompleteLineAnnouncement { line_id: line() }),
+    ];
+    for (name, action) in actions {
+        samples.push((format!("PlayerAction.{}", name), GameMessage::PlayerAction(action)));
+    }
+    
+    samples
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.json", name))
+}
+
+/// Every sample as JSON, plus the envelope clients wrap their messages in.
+fn golden_values() -> Vec<(String, serde_json::Value)> {
+    let request = ClientRequest {
+        request_id: Some(16),
+        message: GameMessage::PlayerAction(PlayerAction::ChooseLine { line_id: line(), car_window_index: 2 }),
+    };
+    
+    // Going through `Value` sorts object keys, so `HashMap` order doesn't matter
+    let mut values = vec![("ClientRequest".to_string(), serde_json::to_value(&request).unwrap())];
+    for (name, message) in samples() {
+        values.push((name, serde_json::to_value(&message).unwrap()));
+    }
+    values
+}
+
+#[test]
+fn messages_match_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+    
+    for (name, actual) in golden_values() {
+        let path = golden_path(&name);
+        
+        if update {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+            continue;
+        }
+        
+        let expected: serde_json::Value = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap(),
+            Err(_) => {
+                mismatches.push(format!("{}: missing {}", name, path.display()));
+                continue;
+            }
+        };
+        if expected != actual {
+            mismatches.push(format!("{}: expected {}, got {}", name, expected, actual));
+        }
+    }
+    
+    assert!(mismatches.is_empty(), "JSON shape changed:\n{}", mismatches.join("\n"));
+}
+
+#[test]
+fn golden_files_still_parse() {
+    for (name, message) in samples() {
+        let Ok(contents) = std::fs::read_to_string(golden_path(&name)) else {
+            continue; // Reported by messages_match_golden_files
+        };
+        
+        let parsed: GameMessage = serde_json::from_str(&contents)
+            .unwrap_or_else(|e| panic!("{} no longer parses: {}", name, e));
+        assert_eq!(variant_name(&parsed), variant_name(&message), "{}", name);
+    }
+}
+
+#[test]
+fn messages_survive_message_pack() {
+    for (name, message) in samples() {
+        let bytes = wire::encode(&message, WireFormat::MessagePack).unwrap();
+        let decoded: GameMessage = wire::decode(&bytes, WireFormat::MessagePack)
+            .unwrap_or_else(|e| panic!("{} does not decode from MessagePack: {}", name, e));
+        
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&message).unwrap(), "{}", name);
+    }
+}
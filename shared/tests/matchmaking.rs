@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+use verplant::{City, MatchSettings, QueuedPlayer, find_matches};
+
+fn settings() -> MatchSettings {
+    MatchSettings {
+        min_players: 2,
+        max_players: 3,
+        max_wait: Duration::from_secs(30),
+        rating_window: 100.0,
+        window_growth: 10.0,
+    }
+}
+
+fn queued(city: City, rating: f64, waited_secs: u64) -> QueuedPlayer {
+    QueuedPlayer { id: Uuid::new_v4(), city, rating, waited: Duration::from_secs(waited_secs) }
+}
+
+#[test]
+fn players_of_similar_rating_and_city_are_matched() {
+    let queue = vec![
+        queued(City::Amsterdam, 1500.0, 5),
+        queued(City::Amsterdam, 1900.0, 4),
+        queued(City::Berlin, 1500.0, 3),
+        queued(City::Amsterdam, 1560.0, 2),
+    ];
+    
+    let matches = find_matches(&queue, &settings());
+    assert_eq!(matches, vec![vec![queue[0].id, queue[3].id]]);
+}
+
+#[test]
+fn groups_are_capped_at_max_players_closest_rating_first() {
+    let queue = vec![
+        queued(City::Paris, 1500.0, 10),
+        queued(City::Paris, 1580.0, 9),
+        queued(City::Paris, 1510.0, 8),
+        queued(City::Paris, 1450.0, 7),
+    ];
+    
+    let matches = find_matches(&queue, &settings());
+    assert_eq!(matches, vec![vec![queue[0].id, queue[2].id, queue[3].id]]);
+}
+
+#[test]
+fn the_window_widens_while_waiting() {
+    // 250 apart is too far at first, but not after 20 seconds
+    let fresh = vec![queued(City::Madrid, 1500.0, 0), queued(City::Madrid, 1750.0, 0)];
+    assert!(find_matches(&fresh, &settings()).is_empty());
+    
+    let waiting = vec![queued(City::Madrid, 1500.0, 20), queued(City::Madrid, 1750.0, 0)];
+    assert_eq!(find_matches(&waiting, &settings()).len(), 1);
+}
+
+#[test]
+fn timed_out_players_get_a_room_anyway() {
+    let alone = vec![queued(City::Berlin, 1500.0, 30)];
+    assert_eq!(find_matches(&alone, &settings()), vec![vec![alone[0].id]]);
+    
+    // Ratings no longer matter either
+    let far_apart = vec![queued(City::Berlin, 1000.0, 30), queued(City::Berlin, 2500.0, 1)];
+    assert_eq!(find_matches(&far_apart, &settings()), vec![vec![far_apart[0].id, far_apart[1].id]]);
+    
+    let too_early = vec![queued(City::Berlin, 1500.0, 29)];
+    assert!(find_matches(&too_early, &settings()).is_empty());
+}
@@ -0,0 +1,33 @@
+use uuid::Uuid;
+use verplant::{DEFAULT_RATING, RatedPlayer, update_ratings};
+
+fn rated(rating: f64, score: i32) -> RatedPlayer {
+    RatedPlayer { id: Uuid::new_v4(), rating, score }
+}
+
+#[test]
+fn winners_gain_what_losers_lose() {
+    let players = [rated(DEFAULT_RATING, 30), rated(DEFAULT_RATING, 20), rated(DEFAULT_RATING, 10)];
+    let ratings = update_ratings(&players);
+    
+    assert_eq!(ratings[&players[0].id], DEFAULT_RATING + 16.0);
+    assert_eq!(ratings[&players[1].id], DEFAULT_RATING);
+    assert_eq!(ratings[&players[2].id], DEFAULT_RATING - 16.0);
+    let total: f64 = ratings.values().sum();
+    assert!((total - 3.0 * DEFAULT_RATING).abs() < 1e-9);
+}
+
+#[test]
+fn upsets_move_ratings_more() {
+    let favourite = rated(1800.0, 10);
+    let underdog = rated(1400.0, 20);
+    let ratings = update_ratings(&[favourite, underdog]);
+    
+    let gained = ratings[&underdog.id] - underdog.rating;
+    assert!(gained > 16.0 && gained < 32.0);
+    assert!((ratings[&favourite.id] - favourite.rating + gained).abs() < 1e-9);
+    
+    // A tie between equals changes nothing
+    let ratings = update_ratings(&[rated(1500.0, 5), rated(1500.0, 5)]);
+    assert!(ratings.values().all(|rating| *rating == 1500.0));
+}
@@ -0,0 +1,52 @@
+use uuid::Uuid;
+use verplant::{City, GameState, MAX_PLAYER_NAME_LENGTH, SubwayMap, validate_player_name};
+
+#[test]
+fn names_are_trimmed() {
+    assert_eq!(validate_player_name("  Ann  "), Ok("Ann".to_string()));
+    assert_eq!(validate_player_name("Jean-Luc_2"), Ok("Jean-Luc_2".to_string()));
+    assert_eq!(validate_player_name("Zoë Ng"), Ok("Zoë Ng".to_string()));
+}
+
+#[test]
+fn invalid_names_are_rejected() {
+    assert!(validate_player_name("").is_err());
+    assert!(validate_player_name("   ").is_err());
+    assert!(validate_player_name("<script>").is_err());
+    assert!(validate_player_name("Ann!").is_err());
+    assert!(validate_player_name("Ann\nBob").is_err());
+    
+    // The limit counts characters, not bytes, and ignores surrounding spaces
+    assert!(validate_player_name(&"é".repeat(MAX_PLAYER_NAME_LENGTH)).is_ok());
+    assert!(validate_player_name(&format!(" {} ", "a".repeat(MAX_PLAYER_NAME_LENGTH))).is_ok());
+    assert!(validate_player_name(&"a".repeat(MAX_PLAYER_NAME_LENGTH + 1)).is_err());
+}
+
+#[test]
+fn names_are_unique_regardless_of_case() {
+    let subway_map = SubwayMap::builtin(&City::Amsterdam);
+    let ann = Uuid::new_v4();
+    let mut game_state = GameState::new(City::Amsterdam, ann);
+    game_state.add_player(ann, "Ann".to_string(), &subway_map);
+    
+    assert!(game_state.is_name_taken("Ann"));
+    assert!(game_state.is_name_taken("aNN"));
+    assert!(!game_state.is_name_taken("Anna"));
+}
+
+#[test]
+fn the_roster_gives_every_player_their_own_colour() {
+    let subway_map = SubwayMap::builtin(&City::Amsterdam);
+    let ann = Uuid::new_v4();
+    let bob = Uuid::new_v4();
+    let mut game_state = GameState::new(City::Amsterdam, ann);
+    game_state.add_player(ann, "Ann".to_string(), &subway_map);
+    game_state.add_player(bob, "Bob".to_string(), &subway_map);
+    
+    let ann_info = game_state.player_info(&ann).unwrap();
+    let bob_info = game_state.player_info(&bob).unwrap();
+    assert_eq!(ann_info.name, "Ann");
+    assert!(ann_info.is_conductor && !bob_info.is_conductor);
+    assert!(ann_info.connected);
+    assert_ne!(ann_info.color, bob_info.color);
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use uuid::Uuid;
+use verplant::{Card, City, DeckConfig, ExpressRule, GameEndRule, GameState, PlayerAction, RulePreset, RuleSet, StationMark};
+
+use common::{Game, red};
+
+fn marked(game_state: &GameState, player_id: Uuid) -> Vec<&str> {
+    let sheet = &game_state.players[&player_id];
+    let mut stations: Vec<&str> = sheet.marked_stations.keys().map(String::as_str).collect();
+    stations.sort();
+    stations
+}
+
+#[test]
+fn presets_are_valid() {
+    for preset in RulePreset::ALL {
+        assert_eq!(preset.rules().validate(), Ok(()), "{:?}", preset);
+        assert_eq!(format!("{:?}", preset).to_lowercase().parse::<RulePreset>(), Ok(preset));
+    }
+    assert_eq!(RuleSet::default(), RulePreset::Classic.rules());
+}
+
+#[test]
+fn deck_follows_the_config() {
+    let mut rules = RulePreset::Classic.rules();
+    assert_eq!(rules.deck.create_deck().len(), 16);
+    
+    rules.deck.number_copies = 3;
+    rules.deck.sixes = 2;
+    rules.deck.express = vec![5];
+    let game_state = GameState::with_seed(City::Amsterdam, Uuid::new_v4(), rules, 1);
+    
+    assert_eq!(game_state.deck.len(), 15 + 2 + 1 + 1 + 1);
+    assert_eq!(game_state.deck.iter().filter(|card| **card == Card::Six).count(), 2);
+    assert!(game_state.deck.contains(&Card::Express(5)));
+    assert!(!game_state.deck.contains(&Card::Express(2)));
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    let rules = RuleSet { game_end: GameEndRule::Rounds(0), ..RuleSet::default() };
+    assert!(rules.validate().is_err());
+    
+    let mut rules = RuleSet::default();
+    rules.deck.express = vec![9];
+    assert!(rules.validate().is_err());
+    
+    let deck = DeckConfig { number_copies: 0, sixes: 0, express: Vec::new(), transfers: 0, free_rides: 0 };
+    let rules = RuleSet { deck, ..RuleSet::default() };
+    assert!(rules.validate().is_err());
+}
+
+#[test]
+fn express_cards_skip_or_stop_at_marked_stations() {
+    for (express, expected) in [(ExpressRule::SkipMarked, vec!["central", "dam", "museum"]), (ExpressRule::StopAtMarked, vec!["central", "dam"])] {
+        let rules = RuleSet { express, ..RuleSet::default() };
+        let mut game = Game::with_rules(rules, Card::Express(2));
+        game.state.players.get_mut(&game.player).unwrap().marked_stations.insert("dam".to_string(), StationMark::Cross);
+        
+        let action = PlayerAction::ChooseLine { line_id: red(), car_window_index: 0 };
+        game.state.process_player_action(game.player, action, &game.map).unwrap();
+        
+        assert_eq!(marked(&game.state, game.player), expected, "{:?}", express);
+    }
+}
+
+#[test]
+fn scoring_follows_the_rules() {
+    let score = |rules: RuleSet| {
+        let mut game = Game::with_rules(rules, Card::Number(1));
+        // One transfer mark on central (2 lines), leaving 3 of the 4 line stops empty
+        game.state.players.get_mut(&game.player).unwrap().marked_stations.insert("central".to_string(), StationMark::TransferNumber(2));
+        game.state.calculate_final_scores(&game.map)[&game.player]
+    };
+    
+    assert_eq!(score(RulePreset::Classic.rules()), 4 - 1);
+    assert_eq!(score(RulePreset::Family.rules()), 4);
+    assert_eq!(score(RulePreset::Strict.rules()), 2 - 3);
+}
+
+#[test]
+fn game_can_end_after_a_number_of_rounds() {
+    let rules = RuleSet { game_end: GameEndRule::Rounds(2), ..RuleSet::default() };
+    let mut game = Game::with_rules(rules, Card::Number(1));
+    
+    for player_id in [game.player, game.other] {
+        game.state.pass(player_id);
+    }
+    game.state.next_round().unwrap();
+    assert!(!game.state.is_finished());
+    
+    game.state.reveal_card().unwrap();
+    for player_id in [game.player, game.other] {
+        game.state.pass(player_id);
+    }
+    game.state.next_round().unwrap();
+    assert!(game.state.is_finished());
+}
@@ -0,0 +1,95 @@
+use verplant::{Card, City, GameMessage, Medal, RuleSet, SoloGame, SoloTargets, SubwayMap};
+
+const SEED: u64 = 0x5eed;
+
+fn new_game(seed: u64) -> SoloGame {
+    SoloGame::new(SubwayMap::builtin(&City::Amsterdam), RuleSet::default(), seed).unwrap()
+}
+
+/// Plays the default move every round until the game ends.
+fn play_out(game: &mut SoloGame) -> Vec<GameMessage> {
+    let mut messages = Vec::new();
+    for _ in 0..200 {
+        if game.is_finished() {
+            break;
+        }
+        let played = match game.state.default_action(game.player_id) {
+            Some(action) => game.play(action),
+            None => game.pass(),
+        };
+        messages.extend(played.unwrap());
+    }
+    messages
+}
+
+#[test]
+fn targets_follow_from_the_map() {
+    // Amsterdam is worth 6 + 4 for its lines and 2 * 2 for the central transfer
+    let targets = SoloTargets::for_map(&SubwayMap::builtin(&City::Amsterdam), &RuleSet::default());
+    assert_eq!(targets, SoloTargets { bronze: 6, silver: 10, gold: 13 });
+    
+    assert_eq!(targets.medal(5), None);
+    assert_eq!(targets.medal(6), Some(Medal::Bronze));
+    assert_eq!(targets.medal(12), Some(Medal::Silver));
+    assert_eq!(targets.medal(20), Some(Medal::Gold));
+}
+
+#[test]
+fn the_same_seed_deals_the_same_game() {
+    let mut first = new_game(SEED);
+    let mut second = new_game(SEED);
+    assert_eq!(first.current_card(), second.current_card());
+    
+    play_out(&mut first);
+    play_out(&mut second);
+    assert_eq!(first.state.round, second.state.round);
+    assert_eq!(first.score(), second.score());
+}
+
+#[test]
+fn a_game_is_played_to_the_end_without_a_server() {
+    let mut game = new_game(SEED);
+    let messages = play_out(&mut game);
+    assert!(game.is_finished());
+    
+    let scores = messages.iter()
+        .find_map(|message| match message {
+            GameMessage::GameEnded { scores } => Some(scores.clone()),
+            _ => None,
+        })
+        .expect("The game should announce its end");
+    assert_eq!(scores[&game.player_id], game.score());
+    assert_eq!(game.medal(), game.targets.medal(game.score()));
+    
+    assert!(game.pass().is_err());
+}
+
+#[test]
+fn a_replay_scores_the_same_when_played_again() {
+    let mut game = new_game(SEED);
+    play_out(&mut game);
+    
+    let map = SubwayMap::builtin(&City::Amsterdam);
+    assert_eq!(SoloGame::verify(map, RuleSet::default(), SEED, &game.replay), Ok(game.score()));
+}
+
+#[test]
+fn a_tampered_replay_is_rejected() {
+    let map = SubwayMap::builtin(&City::Amsterdam);
+    let mut game = new_game(SEED);
+    play_out(&mut game);
+    
+    // Another seed deals other cards
+    assert!(SoloGame::verify(map.clone(), RuleSet::default(), SEED + 1, &game.replay).is_err());
+    
+    let mut dealt = game.replay.clone();
+    dealt.rounds[0].card = match dealt.rounds[0].card {
+        Card::Number(1) => Card::Number(2),
+        _ => Card::Number(1),
+    };
+    assert!(SoloGame::verify(map.clone(), RuleSet::default(), SEED, &dealt).is_err());
+    
+    let mut unfinished = game.replay.clone();
+    unfinished.rounds.pop();
+    assert!(SoloGame::verify(map, RuleSet::default(), SEED, &unfinished).is_err());
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+use verplant::{City, GameRecord, LineCount, LineId, PlayerStats, Replay, StatsTally};
+
+fn line(id: &str) -> LineId {
+    LineId(id.to_string())
+}
+
+/// A finished game where `account` played as the first of `scores`.
+fn record(account: Uuid, city: City, scores: &[i32], lines: &[&str]) -> GameRecord {
+    let players: Vec<Uuid> = scores.iter().map(|_| Uuid::new_v4()).collect();
+    
+    GameRecord {
+        game_id: Uuid::new_v4(),
+        city,
+        players: players.clone(),
+        roster: Vec::new(),
+        scores: players.iter().copied().zip(scores.iter().copied()).collect(),
+        finished_at: 0,
+        replay: Replay::default(),
+        accounts: HashMap::from([(players[0], account)]),
+        completed_lines: HashMap::from([(players[0], lines.iter().map(|id| line(id)).collect())]),
+    }
+}
+
+/// The stats of `account` after adding up `games` the way accounts do.
+fn stats(account: Uuid, games: &[GameRecord]) -> PlayerStats {
+    let mut tally = StatsTally::default();
+    for record in games {
+        if let Some(player_id) = record.player_for(account) {
+            tally.add_game(record, player_id);
+        }
+    }
+    tally.stats()
+}
+
+#[test]
+fn stats_add_up_the_games_of_an_account() {
+    let account = Uuid::new_v4();
+    let games = vec![
+        record(account, City::Amsterdam, &[10, 4], &["red", "blue"]),
+        record(account, City::Amsterdam, &[6, 8], &["red"]),
+        record(account, City::Berlin, &[5, 5, 1], &[]),
+        record(Uuid::new_v4(), City::Berlin, &[20], &["test"]),
+    ];
+    
+    let stats = stats(account, &games);
+    assert_eq!(stats.games_played, 3);
+    assert_eq!(stats.wins, 2); // A shared top score counts as a win
+    assert_eq!(stats.average_score, 7.0);
+    assert_eq!(stats.best_scores, HashMap::from([(City::Amsterdam, 10), (City::Berlin, 5)]));
+    assert_eq!(stats.favourite_lines, vec![
+        LineCount { city: City::Amsterdam, line_id: line("red"), count: 2 },
+        LineCount { city: City::Amsterdam, line_id: line("blue"), count: 1 },
+    ]);
+}
+
+#[test]
+fn an_account_without_games_has_empty_stats() {
+    let games = vec![record(Uuid::new_v4(), City::Paris, &[3, 2], &["test"])];
+    assert_eq!(stats(Uuid::new_v4(), &games), PlayerStats::default());
+}
+
+#[test]
+fn playing_alone_is_not_a_win() {
+    let account = Uuid::new_v4();
+    let game = record(account, City::Madrid, &[12], &[]);
+    
+    let player_id = game.player_for(account).unwrap();
+    assert!(!game.is_winner(player_id));
+    assert_eq!(stats(account, &[game]).wins, 0);
+}
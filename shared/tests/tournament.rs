@@ -0,0 +1,199 @@
+use uuid::Uuid;
+use verplant::{City, RoundSeating, SeatingRule, TableResult, Tournament, TournamentRound, TournamentSettings, TournamentTable};
+
+fn settings(players: &[&str], rounds: usize, seating: SeatingRule) -> TournamentSettings {
+    TournamentSettings {
+        name: "Office cup".to_string(),
+        players: players.iter().map(|name| name.to_string()).collect(),
+        cities: vec![City::Amsterdam; rounds],
+        table_size: 4,
+        seating,
+    }
+}
+
+/// A tournament where every player has an account of their own.
+fn create(settings: TournamentSettings, seed: u64) -> Result<Tournament, String> {
+    let accounts = settings.players.iter().map(|_| Uuid::new_v4()).collect();
+    Tournament::new(settings, Uuid::new_v4(), accounts, seed, 0)
+}
+
+/// Starts the next round and finishes every table with the given scores.
+fn play_round(tournament: &mut Tournament, scores: &[(&str, i32)]) {
+    let RoundSeating { city, tables: seating, bye } = tournament.next_round().unwrap();
+    let tables: Vec<TournamentTable> = seating.into_iter()
+        .map(|players| TournamentTable { game_id: Uuid::new_v4(), players, results: None })
+        .collect();
+    tournament.rounds.push(TournamentRound { city, tables: tables.clone(), bye });
+    
+    for table in tables {
+        let results = table.players.iter()
+            .map(|player_name| TableResult {
+                player_name: player_name.to_uppercase(), // Players may join under any case
+                score: scores.iter().find(|(name, _)| name == player_name).unwrap().1,
+                completed_lines: 1,
+                forfeited: false,
+            })
+            .collect();
+        assert!(tournament.record_results(table.game_id, results));
+    }
+}
+
+#[test]
+fn settings_are_checked() {
+    assert!(create(settings(&["Ann", "Bob"], 1, SeatingRule::Fixed), 0).is_ok());
+    assert!(create(settings(&["Ann"], 1, SeatingRule::Fixed), 0).is_err());
+    assert!(create(settings(&["Ann", "ann"], 1, SeatingRule::Fixed), 0).is_err());
+    assert!(create(settings(&["Ann", "Bob"], 0, SeatingRule::Fixed), 0).is_err());
+    assert!(create(TournamentSettings { table_size: 1, ..settings(&["Ann", "Bob"], 1, SeatingRule::Fixed) }, 0).is_err());
+    assert!(create(TournamentSettings { name: " ".to_string(), ..settings(&["Ann", "Bob"], 1, SeatingRule::Fixed) }, 0).is_err());
+    
+    // Every seat belongs to an account
+    let ann = Uuid::new_v4();
+    assert!(Tournament::new(settings(&["Ann", "Bob"], 1, SeatingRule::Fixed), Uuid::new_v4(), vec![ann], 0, 0).is_err());
+    let tournament = Tournament::new(settings(&["Ann", "Bob"], 1, SeatingRule::Fixed), Uuid::new_v4(), vec![ann, Uuid::new_v4()], 0, 0).unwrap();
+    assert_eq!(tournament.account_of("ANN"), Some(ann));
+    assert_eq!(tournament.account_of("Cem"), None);
+}
+
+#[test]
+fn tables_are_filled_evenly() {
+    let players = ["A", "B", "C", "D", "E", "F", "G", "H", "I"];
+    let tournament = create(settings(&players, 1, SeatingRule::Fixed), 0).unwrap();
+    
+    let RoundSeating { tables, bye, .. } = tournament.next_round().unwrap();
+    let sizes: Vec<usize> = tables.iter().map(Vec::len).collect();
+    assert_eq!(sizes, vec![3, 3, 3]);
+    assert_eq!(tables[0], vec!["A", "B", "C"]);
+    assert_eq!(bye, None);
+    
+    let five = create(settings(&players[..5], 1, SeatingRule::Fixed), 0).unwrap();
+    let RoundSeating { tables, bye, .. } = five.next_round().unwrap();
+    assert_eq!(tables, vec![vec!["A", "B"], vec!["C", "D", "E"]]);
+    assert_eq!(bye, None);
+    
+    let random = create(settings(&players, 1, SeatingRule::Random), 7).unwrap();
+    let RoundSeating { tables, .. } = random.next_round().unwrap();
+    let mut seated: Vec<String> = tables.concat();
+    seated.sort();
+    assert_eq!(seated, players);
+}
+
+#[test]
+fn rounds_start_one_after_another() {
+    let mut tournament = create(settings(&["Ann", "Bob"], 2, SeatingRule::Fixed), 0).unwrap();
+    let RoundSeating { city, tables, .. } = tournament.next_round().unwrap();
+    tournament.rounds.push(TournamentRound {
+        city,
+        tables: vec![TournamentTable { game_id: Uuid::new_v4(), players: tables[0].clone(), results: None }],
+        bye: None,
+    });
+    assert!(tournament.next_round().is_err());
+    assert!(!tournament.record_results(Uuid::new_v4(), Vec::new()));
+    
+    tournament.rounds.clear();
+    play_round(&mut tournament, &[("Ann", 5), ("Bob", 3)]);
+    assert!(!tournament.is_finished());
+    play_round(&mut tournament, &[("Ann", 5), ("Bob", 3)]);
+    assert!(tournament.is_finished());
+    assert!(tournament.next_round().is_err());
+}
+
+#[test]
+fn standings_add_up_every_game() {
+    let players = ["Ann", "Bob", "Cem", "Dee"];
+    let mut tournament = create(settings(&players, 2, SeatingRule::Fixed), 0).unwrap();
+    play_round(&mut tournament, &[("Ann", 10), ("Bob", 20), ("Cem", 20), ("Dee", 5)]);
+    play_round(&mut tournament, &[("Ann", 30), ("Bob", 10), ("Cem", 12), ("Dee", 12)]);
+    
+    let standings = tournament.standings();
+    let order: Vec<&str> = standings.iter().map(|standing| standing.player_name.as_str()).collect();
+    assert_eq!(order, vec!["Ann", "Cem", "Bob", "Dee"]);
+    
+    // Tied at 4 points, the higher total score comes first
+    assert_eq!((standings[0].points, standings[0].total_score), (4.0, 40));
+    assert_eq!((standings[1].points, standings[1].total_score), (4.0, 32));
+    assert_eq!(standings[1].wins, 1); // Shared top score
+    assert_eq!(standings[2].wins, 1);
+    assert_eq!(standings[3].points, 1.5);
+    assert_eq!(standings[3].games_played, 2);
+    assert_eq!(standings[3].completed_lines, 2);
+}
+
+#[test]
+fn seating_by_standings_puts_leaders_together() {
+    let players = ["A", "B", "C", "D"];
+    let mut tournament = create(TournamentSettings { table_size: 2, ..settings(&players, 2, SeatingRule::ByStandings) }, 0).unwrap();
+    play_round(&mut tournament, &[("A", 1), ("B", 9), ("C", 8), ("D", 2)]);
+    
+    let RoundSeating { tables, .. } = tournament.next_round().unwrap();
+    assert_eq!(tables, vec![vec!["B", "C"], vec!["D", "A"]]);
+}
+
+#[test]
+fn an_odd_player_out_gets_a_bye() {
+    let players = ["Ann", "Bob", "Cem"];
+    let mut tournament = create(TournamentSettings { table_size: 2, ..settings(&players, 3, SeatingRule::Fixed) }, 0).unwrap();
+    let RoundSeating { tables, bye, .. } = tournament.next_round().unwrap();
+    assert_eq!(tables, vec![vec!["Ann", "Bob"]]);
+    assert_eq!(bye.as_deref(), Some("Cem"));
+    
+    // Nobody sits out twice while someone else hasn't yet
+    play_round(&mut tournament, &[("Ann", 5), ("Bob", 3)]);
+    let RoundSeating { tables, bye, .. } = tournament.next_round().unwrap();
+    assert_eq!(tables, vec![vec!["Ann", "Cem"]]);
+    assert_eq!(bye.as_deref(), Some("Bob"));
+    
+    // The bye counts like a win, without a game
+    let standings = tournament.standings();
+    let cem = standings.iter().find(|standing| standing.player_name == "Cem").unwrap();
+    assert_eq!((cem.points, cem.games_played, cem.wins), (Tournament::BYE_POINTS, 0, 0));
+}
+
+/// Starts the next round without playing it, returning its game ids.
+fn start_round(tournament: &mut Tournament) -> Vec<Uuid> {
+    let RoundSeating { city, tables: seating, bye } = tournament.next_round().unwrap();
+    let tables: Vec<TournamentTable> = seating.into_iter()
+        .map(|players| TournamentTable { game_id: Uuid::new_v4(), players, results: None })
+        .collect();
+    let game_ids = tables.iter().map(|table| table.game_id).collect();
+    tournament.rounds.push(TournamentRound { city, tables, bye });
+    game_ids
+}
+
+#[test]
+fn forfeits_settle_a_table() {
+    let players = ["Ann", "Bob", "Cem"];
+    let mut tournament = create(settings(&players, 2, SeatingRule::Fixed), 0).unwrap();
+    let game_ids = start_round(&mut tournament);
+    
+    assert!(tournament.forfeit_table(game_ids[0], &["Dee".to_string()]).is_err());
+    assert!(tournament.forfeit_table(Uuid::new_v4(), &[]).is_err());
+    tournament.forfeit_table(game_ids[0], &["bob".to_string()]).unwrap();
+    assert!(tournament.forfeit_table(game_ids[0], &[]).is_err());
+    
+    // Ann and Cem each beat Bob and tie each other, without playing
+    let standings = tournament.standings();
+    let points: Vec<(&str, f64, usize)> = standings.iter()
+        .map(|standing| (standing.player_name.as_str(), standing.points, standing.games_played))
+        .collect();
+    assert_eq!(points, vec![("Ann", 1.5, 1), ("Cem", 1.5, 1), ("Bob", 0.0, 0)]);
+    assert_eq!(standings[2].wins, 0);
+    
+    // The next round can start
+    assert!(tournament.next_round().is_ok());
+}
+
+#[test]
+fn tables_can_move_to_a_new_game() {
+    let mut tournament = create(settings(&["Ann", "Bob"], 1, SeatingRule::Fixed), 0).unwrap();
+    let game_ids = start_round(&mut tournament);
+    let new_game_id = Uuid::new_v4();
+    
+    assert!(tournament.replace_game(game_ids[0], new_game_id));
+    assert!(!tournament.replace_game(game_ids[0], Uuid::new_v4()));
+    
+    // The old game no longer counts
+    assert!(!tournament.record_results(game_ids[0], Vec::new()));
+    assert!(tournament.record_results(new_game_id, Vec::new()));
+    assert!(!tournament.replace_game(new_game_id, Uuid::new_v4()));
+}
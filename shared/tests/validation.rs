@@ -0,0 +1,200 @@
+mod common;
+
+use uuid::Uuid;
+use verplant::{ActionError, Card, City, CompletionStatus, GameMessage, GamePhase, GameState, LineId, PlayerAction, StationMark, SubwayMap};
+
+use common::{Game, game_with_card, red};
+
+fn choose(line_id: LineId, car_window_index: usize) -> PlayerAction {
+    PlayerAction::ChooseLine { line_id, car_window_index }
+}
+
+fn free_ride(station_id: &str) -> PlayerAction {
+    PlayerAction::MarkFreeRideStation { station_id: station_id.to_string() }
+}
+
+fn transfer(station_id: &str) -> PlayerAction {
+    PlayerAction::MarkTransferStation { station_id: station_id.to_string() }
+}
+
+fn announce(line_id: LineId) -> PlayerAction {
+    PlayerAction::CompleteLineAnnouncement { line_id }
+}
+
+/// Asserts that the action is refused with `expected` and leaves the state alone.
+fn assert_rejected(game: &mut Game, action: PlayerAction, expected: ActionError) {
+    assert_eq!(game.state.validate_action(game.player, &action, &game.map), Err(expected.clone()));
+    
+    let checksum = game.state.checksum();
+    let result = game.state.process_player_action(game.player, action, &game.map);
+    assert_eq!(result.err(), Some(expected.to_string()));
+    assert_eq!(game.state.checksum(), checksum);
+}
+
+#[test]
+fn rejects_actions_before_a_card_is_revealed() {
+    let mut game = game_with_card(Card::Number(1));
+    game.state.current_card = None;
+    assert_rejected(&mut game, choose(red(), 0), ActionError::NoCardRevealed);
+}
+
+#[test]
+fn rejects_actions_outside_of_a_round() {
+    let mut game = game_with_card(Card::Number(1));
+    game.state.phase = GamePhase::Lobby;
+    assert_rejected(&mut game, choose(red(), 0), ActionError::WrongPhase(GamePhase::Lobby));
+    
+    game.state.phase = GamePhase::Finished;
+    assert_rejected(&mut game, choose(red(), 0), ActionError::WrongPhase(GamePhase::Finished));
+}
+
+#[test]
+fn game_can_only_be_started_once() {
+    let map = SubwayMap::builtin(&City::Amsterdam);
+    let player = Uuid::new_v4();
+    let mut state = GameState::new(City::Amsterdam, player);
+    state.add_player(player, "Ann".to_string(), &map);
+    
+    assert!(state.next_round().is_err());
+    assert!(state.start_game().is_ok());
+    assert_eq!(state.phase, GamePhase::WaitingForActions);
+    assert!(state.start_game().is_err());
+    assert_eq!(state.reveal_card(), None);
+    
+    state.next_round().unwrap();
+    assert_ne!(state.phase, GamePhase::WaitingForActions);
+    assert!(state.start_game().is_err());
+}
+
+#[test]
+fn rejects_unknown_players() {
+    let mut game = game_with_card(Card::Number(1));
+    game.player = Uuid::new_v4();
+    assert_rejected(&mut game, choose(red(), 0), ActionError::UnknownPlayer);
+}
+
+#[test]
+fn rejects_a_second_move_in_the_same_round() {
+    let mut game = game_with_card(Card::Number(1));
+    game.state.process_player_action(game.player, choose(red(), 0), &game.map).unwrap();
+    assert_rejected(&mut game, choose(red(), 1), ActionError::AlreadyPlayed);
+}
+
+#[test]
+fn rejects_lines_that_are_not_on_the_map() {
+    let mut game = game_with_card(Card::Number(1));
+    let green = LineId("green".to_string());
+    assert_rejected(&mut game, choose(green.clone(), 0), ActionError::UnknownLine(green));
+}
+
+#[test]
+fn rejects_full_lines() {
+    let mut game = game_with_card(Card::Number(1));
+    for window in game.state.players.get_mut(&game.player).unwrap().train_cars.get_mut(&red()).unwrap() {
+        *window = Some("1".to_string());
+    }
+    assert_rejected(&mut game, choose(red(), 0), ActionError::LineFull(red()));
+}
+
+#[test]
+fn rejects_windows_other_than_the_next_empty_one() {
+    let mut game = game_with_card(Card::Number(1));
+    assert_rejected(&mut game, choose(red(), 2), ActionError::WrongWindow { line_id: red(), next_window: 0 });
+}
+
+#[test]
+fn rejects_transfer_marks_without_a_transfer_card() {
+    let mut game = game_with_card(Card::Number(1));
+    assert_rejected(&mut game, transfer("central"), ActionError::WrongCard { required: Card::Transfer });
+}
+
+#[test]
+fn rejects_transfer_marks_on_unknown_stations() {
+    let mut game = game_with_card(Card::Transfer);
+    assert_rejected(&mut game, transfer("zoo"), ActionError::UnknownStation("zoo".to_string()));
+}
+
+#[test]
+fn rejects_transfer_marks_on_marked_stations() {
+    let mut game = game_with_card(Card::Transfer);
+    game.state.players.get_mut(&game.player).unwrap()
+        .marked_stations.insert("central".to_string(), StationMark::Cross);
+    assert_rejected(&mut game, transfer("central"), ActionError::StationAlreadyMarked("central".to_string()));
+}
+
+#[test]
+fn rejects_transfer_marks_on_ordinary_stations() {
+    let mut game = game_with_card(Card::Transfer);
+    assert_rejected(&mut game, transfer("dam"), ActionError::NotATransferStation("dam".to_string()));
+}
+
+#[test]
+fn rejects_free_rides_without_a_free_ride_card() {
+    let mut game = game_with_card(Card::Transfer);
+    assert_rejected(&mut game, free_ride("dam"), ActionError::WrongCard { required: Card::FreeRide });
+}
+
+#[test]
+fn rejects_free_rides_to_unknown_stations() {
+    let mut game = game_with_card(Card::FreeRide);
+    assert_rejected(&mut game, free_ride("zoo"), ActionError::UnknownStation("zoo".to_string()));
+}
+
+#[test]
+fn rejects_free_rides_to_marked_stations() {
+    let mut game = game_with_card(Card::FreeRide);
+    game.state.players.get_mut(&game.player).unwrap()
+        .marked_stations.insert("dam".to_string(), StationMark::Cross);
+    assert_rejected(&mut game, free_ride("dam"), ActionError::StationAlreadyMarked("dam".to_string()));
+}
+
+#[test]
+fn rejects_free_rides_to_stations_on_completed_lines_only() {
+    let mut game = game_with_card(Card::FreeRide);
+    game.state.players.get_mut(&game.player).unwrap().completed_lines.push(red());
+    assert_rejected(&mut game, free_ride("museum"), ActionError::NoOpenLineAtStation("museum".to_string()));
+}
+
+#[test]
+fn rejects_announcements_of_unknown_lines() {
+    let mut game = game_with_card(Card::Number(1));
+    let green = LineId("green".to_string());
+    assert_rejected(&mut game, announce(green.clone()), ActionError::UnknownLine(green));
+}
+
+#[test]
+fn rejects_announcements_of_incomplete_lines() {
+    let mut game = game_with_card(Card::Number(1));
+    assert_rejected(&mut game, announce(red()), ActionError::LineNotComplete(red()));
+}
+
+#[test]
+fn rejects_announcing_a_line_twice() {
+    let mut game = game_with_card(Card::Number(1));
+    let sheet = game.state.players.get_mut(&game.player).unwrap();
+    for station_id in ["central", "dam", "museum"] {
+        sheet.marked_stations.insert(station_id.to_string(), StationMark::Cross);
+    }
+    sheet.completed_lines.push(red());
+    assert_rejected(&mut game, announce(red()), ActionError::LineAlreadyCompleted(red()));
+}
+
+#[test]
+fn announcing_a_complete_line_registers_it() {
+    let mut game = game_with_card(Card::FreeRide);
+    let sheet = game.state.players.get_mut(&game.player).unwrap();
+    for station_id in ["central", "dam"] {
+        sheet.marked_stations.insert(station_id.to_string(), StationMark::Cross);
+    }
+    
+    game.state.process_player_action(game.player, free_ride("museum"), &game.map).unwrap();
+    game.state.process_player_action(game.player, announce(red()), &game.map).unwrap();
+    let sheet = &game.state.players[&game.player];
+    assert_eq!(sheet.completed_lines, vec![red()]);
+    assert_eq!(sheet.line_completion_status[&red()], CompletionStatus::NotCompleted);
+    
+    let messages = game.state.next_round().unwrap();
+    assert!(matches!(&messages[..], [GameMessage::LineCompleted { line_id, .. }] if *line_id == red()));
+    assert_eq!(game.state.players[&game.player].line_completion_status[&red()], CompletionStatus::FirstToComplete(6));
+    assert!(game.state.players[&game.other].completed_lines.is_empty());
+}
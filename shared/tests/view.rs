@@ -0,0 +1,152 @@
+use uuid::Uuid;
+use verplant::{Card, City, CompletionStatus, GamePhase, GameState, GameStateView, LineId, RuleSet, StatePatch, StationMark, SubwayMap};
+
+fn seeded_game(seed: u64) -> (GameState, SubwayMap) {
+    let subway_map = SubwayMap::builtin(&City::Amsterdam);
+    let player_id = Uuid::from_u128(1);
+    let mut game_state = GameState::with_seed(City::Amsterdam, player_id, RuleSet::default(), seed);
+    game_state.id = Uuid::from_u128(2);
+    game_state.add_player(player_id, "Ann".to_string(), &subway_map);
+    (game_state, subway_map)
+}
+
+#[test]
+fn views_hide_the_deck_order_and_the_seed() {
+    let (game_state, subway_map) = seeded_game(1);
+    let view = serde_json::to_value(game_state.public_view(&subway_map)).unwrap();
+    
+    let fields = view.as_object().unwrap();
+    assert!(!fields.contains_key("deck"));
+    assert!(!fields.contains_key("rng_state"));
+    assert_eq!(fields["deck_count"], game_state.deck.len());
+    
+    // Games dealt from different seeds look the same until a card is revealed
+    let (other_game, _) = seeded_game(2);
+    assert_ne!(game_state.deck, other_game.deck);
+    assert_eq!(game_state.public_view(&subway_map), other_game.public_view(&subway_map));
+}
+
+/// Diffs two views, sends the patches through JSON like the server does and
+/// checks that applying them to `old` gives `new`.
+fn round_trip(old: &GameStateView, new: &GameStateView) -> Vec<StatePatch> {
+    let patches = old.diff(new);
+    let sent: Vec<StatePatch> = serde_json::from_str(&serde_json::to_string(&patches).unwrap()).unwrap();
+    
+    let mut applied = old.clone();
+    for patch in sent {
+        applied.apply(patch).unwrap();
+    }
+    assert_eq!(&applied, new);
+    assert_eq!(applied.checksum(), new.checksum());
+    patches
+}
+
+#[test]
+fn equal_views_need_no_patches() {
+    let (game_state, subway_map) = seeded_game(1);
+    let view = game_state.public_view(&subway_map);
+    assert!(view.diff(&view.clone()).is_empty());
+}
+
+#[test]
+fn sheet_changes_round_trip_as_small_patches() {
+    let (game_state, subway_map) = seeded_game(1);
+    let player_id = Uuid::from_u128(1);
+    let old = game_state.public_view(&subway_map);
+    let line_id = old.players[&player_id].train_cars.keys().next().unwrap().clone();
+    let station_id = subway_map.stations.keys().next().unwrap().clone();
+    
+    let mut new = old.clone();
+    let sheet = new.players.get_mut(&player_id).unwrap();
+    sheet.train_cars.get_mut(&line_id).unwrap()[0] = Some("3".to_string());
+    sheet.marked_stations.insert(station_id.clone(), StationMark::Cross);
+    sheet.completed_lines.push(line_id.clone());
+    sheet.line_completion_status.insert(line_id.clone(), CompletionStatus::FirstToComplete(5));
+    new.scores.insert(player_id, 12);
+    
+    let patches = round_trip(&old, &new);
+    assert!(patches.contains(&StatePatch::WindowFilled { player_id, line_id: line_id.clone(), index: 0, value: Some("3".to_string()) }));
+    assert!(patches.contains(&StatePatch::StationMarked { player_id, station_id: station_id.clone(), mark: Some(StationMark::Cross) }));
+    assert!(patches.contains(&StatePatch::LineCompleted { player_id, line_id }));
+    assert!(!patches.iter().any(|patch| matches!(patch, StatePatch::SheetReplaced(_))));
+    
+    let mut unmarked = new.clone();
+    unmarked.players.get_mut(&player_id).unwrap().marked_stations.remove(&station_id);
+    let patches = round_trip(&new, &unmarked);
+    assert_eq!(patches, vec![StatePatch::StationMarked { player_id, station_id, mark: None }]);
+    
+    // Lines are never uncompleted in a game, so that takes the whole sheet
+    let patches = round_trip(&new, &old);
+    assert!(matches!(patches[..], [StatePatch::SheetReplaced(_), ..]));
+}
+
+#[test]
+fn players_joining_and_leaving_round_trip() {
+    let (mut game_state, subway_map) = seeded_game(1);
+    let old = game_state.public_view(&subway_map);
+    
+    let bob = Uuid::from_u128(3);
+    game_state.add_player(bob, "Bob".to_string(), &subway_map);
+    let new = game_state.public_view(&subway_map);
+    
+    let patches = round_trip(&old, &new);
+    assert!(patches.iter().any(|patch| matches!(patch, StatePatch::SheetReplaced(sheet) if sheet.player_id == bob)));
+    assert!(patches.iter().any(|patch| matches!(patch, StatePatch::RosterChanged(_))));
+    
+    let patches = round_trip(&new, &old);
+    assert!(patches.contains(&StatePatch::PlayerRemoved(bob)));
+}
+
+#[test]
+fn game_wide_changes_round_trip() {
+    let (game_state, subway_map) = seeded_game(1);
+    let old = game_state.public_view(&subway_map);
+    
+    let mut new = old.clone();
+    new.current_card = Some(Card::Number(4));
+    new.deck_count -= 1;
+    new.discard_pile.push(Card::Number(2));
+    new.round = 3;
+    new.phase = GamePhase::WaitingForActions;
+    new.conductor = Uuid::from_u128(3);
+    new.acted_this_round.insert(Uuid::from_u128(1));
+    
+    assert_eq!(round_trip(&old, &new).len(), 6);
+}
+
+#[test]
+fn a_whole_game_round_trips_one_round_at_a_time() {
+    let (mut game_state, subway_map) = seeded_game(7);
+    game_state.add_player(Uuid::from_u128(3), "Bob".to_string(), &subway_map);
+    let mut client_view = game_state.public_view(&subway_map);
+    
+    while !game_state.is_finished() {
+        if game_state.reveal_card().is_none() {
+            break;
+        }
+        let mut player_ids: Vec<Uuid> = game_state.players.keys().copied().collect();
+        player_ids.sort();
+        for player_id in player_ids {
+            let played = game_state.default_action(player_id)
+                .is_some_and(|action| game_state.process_player_action(player_id, action, &subway_map).is_ok());
+            if !played {
+                game_state.pass(player_id);
+            }
+        }
+        game_state.next_round().unwrap();
+        
+        let server_view = game_state.public_view(&subway_map);
+        round_trip(&client_view, &server_view);
+        client_view = server_view;
+    }
+    assert!(game_state.is_finished());
+}
+
+#[test]
+fn patches_for_unknown_players_are_rejected() {
+    let (game_state, subway_map) = seeded_game(1);
+    let mut view = game_state.public_view(&subway_map);
+    
+    let patch = StatePatch::LineCompleted { player_id: Uuid::from_u128(9), line_id: LineId("red".to_string()) };
+    assert!(view.apply(patch).is_err());
+}